@@ -1,5 +1,5 @@
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
-use stream_resp::parser::Parser;
+use stream_resp::parser::{Parser, ParserConfig};
 
 fn benchmark_parser(c: &mut Criterion) {
     let mut group = c.benchmark_group("RESP Parser");
@@ -20,6 +20,8 @@ fn benchmark_parser(c: &mut Criterion) {
     let nested_array = b"*2\r\n*2\r\n+a\r\n+b\r\n*2\r\n+c\r\n+d\r\n";
     let large_array = create_large_array(100);
     let large_bulk_string = create_large_bulk_string(1000);
+    let integer_array = create_integer_array(100);
+    let big_bulk_string = create_large_bulk_string(1024 * 1024);
     let mixed_types = b"*5\r\n:1\r\n+OK\r\n-Error\r\n$5\r\nhello\r\n*0\r\n";
     let real_command = b"*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n";
 
@@ -36,6 +38,37 @@ fn benchmark_parser(c: &mut Criterion) {
     bench_scenario(&mut group, "large_bulk_string", &large_bulk_string);
     bench_scenario(&mut group, "mixed_types", mixed_types);
     bench_scenario(&mut group, "real_command", real_command);
+    bench_scenario(&mut group, "integer_array", &integer_array);
+
+    // `large_array` again, but grown in stages past a small pre-allocation
+    group.bench_function("staged_large_array", |b| {
+        b.iter(|| {
+            let mut parser = Parser::with_config(ParserConfig {
+                prealloc_limit: 16,
+                ..ParserConfig::default()
+            });
+            parser.read_buf(&large_array);
+            let _ = parser.try_parse().unwrap();
+        })
+    });
+
+    // A header declaring far more elements than are sent must stay cheap
+    let hostile_array_header = b"*1000000000\r\n:1\r\n";
+    group.bench_function("hostile_array_header", |b| {
+        b.iter(|| {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(hostile_array_header);
+            let _ = parser.try_parse().unwrap_err();
+        })
+    });
+
+    group.bench_function("big_bulk_string", |b| {
+        b.iter(|| {
+            let mut parser = Parser::new(10, 2 * 1024 * 1024);
+            parser.read_buf(&big_bulk_string);
+            let _ = parser.try_parse().unwrap();
+        })
+    });
 
     // Benchmark batched commands
     let mut batched_commands = Vec::new();
@@ -103,6 +136,14 @@ fn create_large_array(size: usize) -> Vec<u8> {
     result
 }
 
+fn create_integer_array(size: usize) -> Vec<u8> {
+    let mut result = format!("*{}\r\n", size).into_bytes();
+    for i in 0..size {
+        result.extend_from_slice(format!(":{}\r\n", 1_234_567_890_123_i64 * i as i64).as_bytes());
+    }
+    result
+}
+
 fn create_large_bulk_string(size: usize) -> Vec<u8> {
     let data = "x".repeat(size);
     let mut result = format!("${}\r\n", size).into_bytes();
@@ -25,11 +25,11 @@ fn main() {
     );
 
     // Representing the Redis command: SET mykey "Hello"
-    let command = RespValue::Array(Some(vec![
+    let command = RespValue::Array(Some(Box::from(vec![
         RespValue::BulkString(Some(Cow::Borrowed("SET"))),
         RespValue::BulkString(Some(Cow::Borrowed("mykey"))),
         RespValue::BulkString(Some(Cow::Borrowed("Hello"))),
-    ]));
+    ])));
 
     // Get the RESP byte representation
     let expected_bytes = b"*3\r\n$3\r\nSET\r\n$5\r\nmykey\r\n$5\r\nHello\r\n";
@@ -69,17 +69,17 @@ fn main() {
     let array: RespValue = vec![RespValue::Integer(1), "two".into()].into();
     assert_eq!(
         array,
-        RespValue::Array(Some(vec![
+        RespValue::Array(Some(Box::from(vec![
             RespValue::Integer(1),
             RespValue::SimpleString(Cow::Borrowed("two"))
-        ]))
+        ])))
     );
 
     let simple_string = RespValue::SimpleString(Cow::Borrowed("OK"));
-    let ok_str: String = simple_string.into();
+    let ok_str: String = simple_string.try_into().unwrap();
     assert_eq!(ok_str, "OK");
 
     let integer = RespValue::Integer(42);
-    let num: i64 = integer.into(); // Panics if not Integer
+    let num: i64 = integer.try_into().unwrap(); // Fails if not Integer
     assert_eq!(num, 42);
 }
@@ -46,10 +46,10 @@ fn main() {
         let result = parser.try_parse();
         assert_eq!(
             result,
-            Ok(Some(RespValue::Array(Some(vec![
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
                 RespValue::Integer(1),
                 RespValue::Integer(2)
-            ]))))
+            ])))))
         );
     }
 }
@@ -0,0 +1,471 @@
+use crate::encoder::Encoder;
+use crate::resp::{RespKind, RespValue};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Builds a `RespValue::Set` without duplicate members.
+///
+/// Members are compared with `RespValue`'s `Eq`, as the parser does when it rejects
+/// duplicate set members, so `0.0` and `-0.0` are the same member while `+a` and
+/// `$1\r\na` are not. They are kept in insertion order unless `sorted` is requested.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::builder::SetBuilder;
+/// use stream_resp::resp::RespValue;
+///
+/// let set = SetBuilder::new()
+///     .insert("b")
+///     .insert("a")
+///     .insert("b")
+///     .sorted()
+///     .build();
+/// assert_eq!(set, RespValue::Set(Some(Box::from(vec!["a".into(), "b".into()]))));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SetBuilder<'a> {
+    /// Each member with its insertion index.
+    members: HashMap<RespValue<'a>, usize>,
+    sorted: bool,
+}
+
+impl<'a> SetBuilder<'a> {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a member, ignoring it if an equal member was already added.
+    pub fn insert(mut self, member: impl Into<RespValue<'a>>) -> Self {
+        self.push(member.into());
+        self
+    }
+
+    /// Adds a member in place; returns `false` if an equal member was already added.
+    pub fn push(&mut self, member: RespValue<'a>) -> bool {
+        let index = self.members.len();
+        match self.members.entry(member) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(index);
+                true
+            }
+        }
+    }
+
+    /// Orders members by `RespValue`'s `Ord`, so equal sets always build identical replies.
+    pub fn sorted(mut self) -> Self {
+        self.sorted = true;
+        self
+    }
+
+    /// Returns the number of distinct members added so far.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns `true` if no member was added.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Builds the set.
+    pub fn build(self) -> RespValue<'a> {
+        let mut members: Vec<_> = self.members.into_iter().collect();
+        if self.sorted {
+            members.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        } else {
+            members.sort_unstable_by_key(|&(_, index)| index);
+        }
+        RespValue::Set(Some(
+            members.into_iter().map(|(member, _)| member).collect(),
+        ))
+    }
+}
+
+impl<'a, V: Into<RespValue<'a>>> Extend<V> for SetBuilder<'a> {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        for member in iter {
+            self.push(member.into());
+        }
+    }
+}
+
+impl<'a, V: Into<RespValue<'a>>> FromIterator<V> for SetBuilder<'a> {
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let mut builder = SetBuilder::new();
+        builder.extend(iter);
+        builder
+    }
+}
+
+/// Builds a `RespValue::Array` one element at a time.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::builder::ArrayBuilder;
+/// use stream_resp::encoder::Encoder;
+///
+/// let mut builder = ArrayBuilder::with_capacity(2).element("name").element(7);
+/// builder.push(true.into());
+/// let mut encoder = Encoder::new();
+/// assert_eq!(builder.encode(&mut encoder), b"*3\r\n+name\r\n:7\r\n#t\r\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ArrayBuilder<'a> {
+    elements: Vec<RespValue<'a>>,
+}
+
+impl<'a> ArrayBuilder<'a> {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty builder with room for `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ArrayBuilder {
+            elements: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Adds an element.
+    pub fn element(mut self, element: impl Into<RespValue<'a>>) -> Self {
+        self.push(element.into());
+        self
+    }
+
+    /// Adds an element in place.
+    pub fn push(&mut self, element: RespValue<'a>) {
+        self.elements.push(element);
+    }
+
+    /// Returns the number of elements added so far.
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if no element was added.
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Builds the array.
+    pub fn build(self) -> RespValue<'a> {
+        RespValue::Array(Some(Box::from(self.elements)))
+    }
+
+    /// Builds the array and encodes it with `encoder`.
+    pub fn encode(self, encoder: &mut Encoder) -> &[u8] {
+        encoder.encode(&self.build())
+    }
+}
+
+impl<'a, V: Into<RespValue<'a>>> Extend<V> for ArrayBuilder<'a> {
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        self.elements.extend(iter.into_iter().map(Into::into));
+    }
+}
+
+impl<'a, V: Into<RespValue<'a>>> FromIterator<V> for ArrayBuilder<'a> {
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let mut builder = ArrayBuilder::new();
+        builder.extend(iter);
+        builder
+    }
+}
+
+/// Errors returned by `MapBuilder::build`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuilderError {
+    /// A key was pushed without a value to pair it with.
+    UnpairedKey,
+}
+
+impl fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuilderError::UnpairedKey => write!(f, "Map key without a value"),
+        }
+    }
+}
+
+impl std::error::Error for BuilderError {}
+
+/// Builds a `RespValue::Map` one entry at a time, or from keys and values pushed
+/// alternately, as a flat `HGETALL` reply lists them.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::builder::{BuilderError, MapBuilder};
+/// use stream_resp::resp::RespValue;
+///
+/// let mut builder = MapBuilder::new().entry("proto", 3);
+/// builder.push("mode".into());
+/// builder.push("standalone".into());
+/// let map = builder.build().unwrap();
+/// assert_eq!(map.as_bytes(), b"%2\r\n+proto\r\n:3\r\n+mode\r\n+standalone\r\n");
+///
+/// let mut builder = MapBuilder::new();
+/// builder.push("key".into());
+/// assert_eq!(builder.build(), Err(BuilderError::UnpairedKey));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MapBuilder<'a> {
+    entries: Vec<(RespValue<'a>, RespValue<'a>)>,
+    key: Option<RespValue<'a>>,
+}
+
+impl<'a> MapBuilder<'a> {
+    /// Creates an empty builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty builder with room for `capacity` entries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        MapBuilder {
+            entries: Vec::with_capacity(capacity),
+            key: None,
+        }
+    }
+
+    /// Adds an entry.
+    pub fn entry(mut self, key: impl Into<RespValue<'a>>, value: impl Into<RespValue<'a>>) -> Self {
+        self.push_entry(key.into(), value.into());
+        self
+    }
+
+    /// Adds an entry in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a key pushed with `push` is still waiting for its value.
+    pub fn push_entry(&mut self, key: RespValue<'a>, value: RespValue<'a>) {
+        assert!(self.key.is_none(), "a pushed key is waiting for its value");
+        self.entries.push((key, value));
+    }
+
+    /// Adds a key or value: the first item pushed is a key, the next its value, and
+    /// so on.
+    pub fn push(&mut self, item: RespValue<'a>) {
+        match self.key.take() {
+            Some(key) => self.entries.push((key, item)),
+            None => self.key = Some(item),
+        }
+    }
+
+    /// Returns the number of complete entries added so far.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if nothing was added.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.key.is_none()
+    }
+
+    /// Builds the map, failing if the last key pushed has no value.
+    pub fn build(self) -> Result<RespValue<'a>, BuilderError> {
+        if self.key.is_some() {
+            return Err(BuilderError::UnpairedKey);
+        }
+        Ok(RespValue::Map(Some(Box::from(self.entries))))
+    }
+
+    /// Builds the map and encodes it with `encoder`.
+    pub fn encode(self, encoder: &mut Encoder) -> Result<&[u8], BuilderError> {
+        Ok(encoder.encode(&self.build()?))
+    }
+}
+
+impl<'a, K, V> Extend<(K, V)> for MapBuilder<'a>
+where
+    K: Into<RespValue<'a>>,
+    V: Into<RespValue<'a>>,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.push_entry(key.into(), value.into());
+        }
+    }
+}
+
+impl<'a, K, V> FromIterator<(K, V)> for MapBuilder<'a>
+where
+    K: Into<RespValue<'a>>,
+    V: Into<RespValue<'a>>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut builder = MapBuilder::new();
+        builder.extend(iter);
+        builder
+    }
+}
+
+/// Writes a RESP3 streamed string, `$?\r\n` followed by `;<len>` chunks and a closing
+/// `;0\r\n`, for payloads whose length isn't known up front.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::builder::StreamedStringWriter;
+///
+/// let mut writer = StreamedStringWriter::begin(Vec::new()).unwrap();
+/// writer.chunk(b"hello").unwrap();
+/// writer.chunk(b" world").unwrap();
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(bytes, b"$?\r\n;5\r\nhello\r\n;6\r\n world\r\n;0\r\n");
+/// ```
+#[derive(Debug)]
+pub struct StreamedStringWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> StreamedStringWriter<W> {
+    /// Writes the `$?` header.
+    pub fn begin(mut writer: W) -> io::Result<Self> {
+        writer.write_all(b"$?\r\n")?;
+        Ok(StreamedStringWriter { writer })
+    }
+
+    /// Writes `data` as one chunk. Empty chunks are skipped, as a zero length would
+    /// end the string.
+    pub fn chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        write!(self.writer, ";{}\r\n", data.len())?;
+        self.writer.write_all(data)?;
+        self.writer.write_all(b"\r\n")
+    }
+
+    /// Writes the closing chunk and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(b";0\r\n")?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes a RESP3 streamed aggregate, `*?`, `%?` or `~?` followed by its elements and a
+/// closing `.\r\n`, for aggregates whose length isn't known up front.
+///
+/// The elements of a map alternate between keys and values; `entry` writes both.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::builder::StreamedAggregateWriter;
+/// use stream_resp::resp::{RespKind, RespValue};
+///
+/// let mut writer = StreamedAggregateWriter::begin(Vec::new(), RespKind::Array).unwrap();
+/// writer.element(&RespValue::Integer(1)).unwrap();
+/// writer.element(&RespValue::Integer(2)).unwrap();
+/// let bytes = writer.finish().unwrap();
+/// assert_eq!(bytes, b"*?\r\n:1\r\n:2\r\n.\r\n");
+/// ```
+#[derive(Debug)]
+pub struct StreamedAggregateWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> StreamedAggregateWriter<W> {
+    /// Writes the header of a streamed `kind`, which must be an array, map or set.
+    pub fn begin(mut writer: W, kind: RespKind) -> io::Result<Self> {
+        let header: &[u8] = match kind {
+            RespKind::Array => b"*?\r\n",
+            RespKind::Map => b"%?\r\n",
+            RespKind::Set => b"~?\r\n",
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "only arrays, maps and sets can be streamed",
+                ));
+            }
+        };
+        writer.write_all(header)?;
+        Ok(StreamedAggregateWriter { writer })
+    }
+
+    /// Writes one element.
+    pub fn element(&mut self, value: &RespValue<'_>) -> io::Result<()> {
+        value.write_to_io(&mut self.writer).map(drop)
+    }
+
+    /// Writes a map entry.
+    pub fn entry(&mut self, key: &RespValue<'_>, value: &RespValue<'_>) -> io::Result<()> {
+        self.element(key)?;
+        self.element(value)
+    }
+
+    /// Writes the end marker and returns the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(b".\r\n")?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes a bulk string of `len` bytes whose body is copied from `reader` in chunks,
+/// so a large file or blob is served without being loaded into memory. Returns the
+/// number of bytes written, header and CRLF included.
+///
+/// Fails with `UnexpectedEof` if `reader` ends before `len` bytes, leaving a partial
+/// frame on `writer`; the connection can't be used for further replies.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::builder::copy_bulk;
+///
+/// let mut out = Vec::new();
+/// let written = copy_bulk(&mut &b"file contents"[..], &mut out, 13).unwrap();
+/// assert_eq!(out, b"$13\r\nfile contents\r\n");
+/// assert_eq!(written, out.len() as u64);
+/// ```
+pub fn copy_bulk<R, W>(reader: &mut R, writer: &mut W, len: u64) -> io::Result<u64>
+where
+    R: Read + ?Sized,
+    W: Write + ?Sized,
+{
+    let header = format!("${}\r\n", len);
+    writer.write_all(header.as_bytes())?;
+    let copied = io::copy(&mut reader.take(len), writer)?;
+    if copied < len {
+        return Err(short_body(copied, len));
+    }
+    writer.write_all(b"\r\n")?;
+    Ok(header.len() as u64 + len + 2)
+}
+
+/// Same as `copy_bulk`, for async readers and writers.
+#[cfg(feature = "tokio")]
+pub async fn copy_bulk_async<R, W>(reader: &mut R, writer: &mut W, len: u64) -> io::Result<u64>
+where
+    R: tokio::io::AsyncRead + Unpin + ?Sized,
+    W: tokio::io::AsyncWrite + Unpin + ?Sized,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let header = format!("${}\r\n", len);
+    writer.write_all(header.as_bytes()).await?;
+    let copied = tokio::io::copy(&mut reader.take(len), writer).await?;
+    if copied < len {
+        return Err(short_body(copied, len));
+    }
+    writer.write_all(b"\r\n").await?;
+    Ok(header.len() as u64 + len + 2)
+}
+
+fn short_body(copied: u64, len: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("bulk body ended after {} of {} bytes", copied, len),
+    )
+}
+
+//EOF
@@ -0,0 +1,220 @@
+use crate::builder::{
+    ArrayBuilder, BuilderError, MapBuilder, SetBuilder, StreamedAggregateWriter,
+    StreamedStringWriter, copy_bulk,
+};
+use crate::encoder::Encoder;
+use crate::parser::{Parser, ParserConfig};
+use crate::resp::{RespKind, RespValue};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_builder_dedups_in_insertion_order() {
+        let mut builder = SetBuilder::new().insert("b").insert(1).insert("a");
+        assert!(!builder.push("b".into()));
+        assert!(builder.push(RespValue::BulkString(Some("b".into()))));
+        assert_eq!(builder.len(), 4);
+
+        assert_eq!(
+            builder.build(),
+            RespValue::Set(Some(Box::from(vec![
+                "b".into(),
+                RespValue::Integer(1),
+                "a".into(),
+                RespValue::BulkString(Some("b".into())),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_set_builder_sorted_is_canonical() {
+        let mut first = SetBuilder::new().sorted();
+        first.extend(["x", "y", "z", "x"]);
+        let mut second = SetBuilder::new().sorted();
+        second.extend(["z", "y", "x"]);
+
+        assert_eq!(first.build().as_bytes(), second.build().as_bytes());
+    }
+
+    #[test]
+    fn test_set_builder_empty() {
+        let builder = SetBuilder::new();
+        assert!(builder.is_empty());
+        assert_eq!(builder.build(), RespValue::Set(Some(Box::default())));
+    }
+
+    #[test]
+    fn test_streamed_string_writer_round_trips() {
+        let mut writer = StreamedStringWriter::begin(Vec::new()).unwrap();
+        writer.chunk(b"ab").unwrap();
+        writer.chunk(b"").unwrap();
+        writer.chunk(b"c\r\nd").unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes, b"$?\r\n;2\r\nab\r\n;4\r\nc\r\nd\r\n;0\r\n");
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(&bytes);
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkString(Some("abc\r\nd".into()))))
+        );
+    }
+
+    #[test]
+    fn test_streamed_aggregate_writer_round_trips() {
+        let mut writer = StreamedAggregateWriter::begin(Vec::new(), RespKind::Map).unwrap();
+        writer.entry(&"a".into(), &RespValue::Integer(1)).unwrap();
+        writer.entry(&"b".into(), &RespValue::Integer(2)).unwrap();
+        let bytes = writer.finish().unwrap();
+        assert_eq!(bytes, b"%?\r\n+a\r\n:1\r\n+b\r\n:2\r\n.\r\n");
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(&bytes);
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(Box::from(vec![
+                ("a".into(), RespValue::Integer(1)),
+                ("b".into(), RespValue::Integer(2)),
+            ])))))
+        );
+
+        assert!(StreamedAggregateWriter::begin(Vec::new(), RespKind::Push).is_err());
+    }
+
+    #[test]
+    fn test_collect_set_builder() {
+        let set = ["b", "a", "b"].into_iter().collect::<SetBuilder>().build();
+        assert_eq!(
+            set,
+            RespValue::Set(Some(Box::from(vec!["b".into(), "a".into()])))
+        );
+    }
+
+    #[test]
+    fn test_array_builder() {
+        let mut builder = ArrayBuilder::new().element("a").element(1);
+        builder.push(RespValue::Null);
+        builder.extend([2, 3]);
+        assert_eq!(builder.len(), 5);
+        let expected = RespValue::Array(Some(Box::from(vec![
+            "a".into(),
+            RespValue::Integer(1),
+            RespValue::Null,
+            RespValue::Integer(2),
+            RespValue::Integer(3),
+        ])));
+        assert_eq!(builder.clone().build(), expected);
+
+        let mut encoder = Encoder::new();
+        assert_eq!(builder.encode(&mut encoder), expected.as_bytes());
+
+        let collected: ArrayBuilder = (0..3).collect();
+        assert_eq!(collected.build().as_bytes(), b"*3\r\n:0\r\n:1\r\n:2\r\n");
+        assert!(ArrayBuilder::new().is_empty());
+        assert_eq!(ArrayBuilder::new().build().as_bytes(), b"*0\r\n");
+    }
+
+    #[test]
+    fn test_map_builder() {
+        let mut builder = MapBuilder::with_capacity(3).entry("a", 1);
+        builder.push("b".into());
+        assert_eq!(builder.len(), 1);
+        assert!(!builder.is_empty());
+        assert_eq!(builder.clone().build(), Err(BuilderError::UnpairedKey));
+        assert_eq!(
+            BuilderError::UnpairedKey.to_string(),
+            "Map key without a value"
+        );
+        let mut encoder = Encoder::new();
+        assert_eq!(
+            builder.clone().encode(&mut encoder),
+            Err(BuilderError::UnpairedKey)
+        );
+
+        builder.push(RespValue::Integer(2));
+        builder.extend([("c", 3)]);
+        let map = builder.clone().build().unwrap();
+        assert_eq!(map.get("b"), Some(&RespValue::Integer(2)));
+        assert_eq!(map.as_map().unwrap().len(), 3);
+        assert_eq!(builder.encode(&mut encoder).unwrap(), map.as_bytes());
+
+        let collected: MapBuilder = [("k", "v")].into_iter().collect();
+        assert_eq!(collected.build().unwrap().as_bytes(), b"%1\r\n+k\r\n+v\r\n");
+        assert!(MapBuilder::new().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "a pushed key is waiting for its value")]
+    fn test_map_builder_entry_after_unpaired_key() {
+        let mut builder = MapBuilder::new();
+        builder.push("key".into());
+        builder.push_entry("other".into(), RespValue::Null);
+    }
+
+    #[test]
+    fn test_copy_bulk() {
+        let body: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let mut out = Vec::new();
+        let written = copy_bulk(&mut std::io::Cursor::new(&body), &mut out, 100_000).unwrap();
+        assert_eq!(written, out.len() as u64);
+
+        assert_eq!(out, [&b"$100000\r\n"[..], &body, b"\r\n"].concat());
+
+        // Only `len` bytes are taken from a longer source
+        let mut source = &b"abcdef"[..];
+        let mut out = Vec::new();
+        assert_eq!(copy_bulk(&mut source, &mut out, 3).unwrap(), 9);
+        assert_eq!(out, b"$3\r\nabc\r\n");
+        assert_eq!(source, b"def");
+
+        let mut out = Vec::new();
+        let err = copy_bulk(&mut &b"abc"[..], &mut out, 10).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        assert_eq!(err.to_string(), "bulk body ended after 3 of 10 bytes");
+
+        let mut out = Vec::new();
+        copy_bulk(&mut std::io::empty(), &mut out, 0).unwrap();
+        assert_eq!(out, b"$0\r\n\r\n");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_copy_bulk_async() {
+        let body = vec![b'z'; 20_000];
+        let mut out = Vec::new();
+        let written = crate::builder::copy_bulk_async(&mut &body[..], &mut out, 20_000)
+            .await
+            .unwrap();
+        assert_eq!(written, out.len() as u64);
+        assert_eq!(out[..8], *b"$20000\r\n");
+        assert_eq!(out[8..20_008], body[..]);
+        assert!(out.ends_with(b"\r\n"));
+
+        let err = crate::builder::copy_bulk_async(&mut &body[..5], &mut Vec::new(), 6)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_set_builder_agrees_with_parser_duplicates() {
+        let builder = SetBuilder::new()
+            .insert(0.0)
+            .insert(-0.0)
+            .insert("a")
+            .insert(RespValue::BulkString(Some("a".into())));
+        assert_eq!(builder.len(), 3);
+
+        let bytes = builder.build().as_bytes();
+        assert_eq!(bytes, b"~3\r\n,0\r\n+a\r\n$1\r\na\r\n");
+        let mut parser = Parser::with_config(ParserConfig {
+            detect_duplicate_members: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(&bytes);
+        assert!(parser.try_parse().unwrap().is_some());
+        assert!(parser.take_warnings().is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+use crate::parser::{ParseError, Parser, ParserConfig};
+use crate::resp::RespValue;
+use bytes::BytesMut;
+use std::fmt;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Errors produced by `RespCodec`.
+#[derive(Debug)]
+pub enum CodecError {
+    Io(std::io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Io(error) => write!(f, "I/O error: {}", error),
+            CodecError::Parse(error) => write!(f, "Parse error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodecError::Io(error) => Some(error),
+            CodecError::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for CodecError {
+    fn from(error: std::io::Error) -> Self {
+        CodecError::Io(error)
+    }
+}
+
+impl From<ParseError> for CodecError {
+    fn from(error: ParseError) -> Self {
+        CodecError::Parse(error)
+    }
+}
+
+/// A `tokio_util` codec decoding RESP frames into `RespValue`s and encoding
+/// `RespValue`s back.
+///
+/// Decoding moves the read buffer's bytes into an internal `Parser`, taking over the
+/// allocation when the parser holds no partial frame.
+///
+/// `ParserConfig::max_iterations` bounds each `decode` call while the buffered input
+/// ends inside the frame: `decode` returns `None` like `stream::RespStream` yields,
+/// and picks up where it left off once `Framed` has read more. A frame that is fully
+/// buffered is finished within the call, since `Framed` only decodes again after a
+/// read and would otherwise wait for input that may never come.
+///
+/// # Example
+///
+/// ```
+/// use bytes::BytesMut;
+/// use stream_resp::codec::RespCodec;
+/// use stream_resp::resp::RespValue;
+/// use tokio_util::codec::Decoder;
+///
+/// let mut codec = RespCodec::default();
+/// let mut buf = BytesMut::from(&b"+OK\r\n"[..]);
+/// assert_eq!(
+///     codec.decode(&mut buf).unwrap(),
+///     Some(RespValue::SimpleString("OK".into()))
+/// );
+/// ```
+#[derive(Debug)]
+pub struct RespCodec {
+    parser: Parser,
+}
+
+impl RespCodec {
+    /// Creates a codec whose parser uses `config`.
+    pub fn with_config(config: ParserConfig) -> Self {
+        RespCodec {
+            parser: Parser::with_config(config),
+        }
+    }
+
+    /// Returns the underlying parser, e.g. to read its statistics.
+    pub fn parser(&self) -> &Parser {
+        &self.parser
+    }
+}
+
+impl Default for RespCodec {
+    fn default() -> Self {
+        Self::with_config(ParserConfig::default())
+    }
+}
+
+impl Decoder for RespCodec {
+    type Item = RespValue<'static>;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if !src.is_empty() {
+            self.parser.feed(src.split());
+        }
+        loop {
+            return match self.parser.try_parse() {
+                Ok(value) => Ok(value),
+                Err(ParseError::UnexpectedEof | ParseError::NotEnoughData) => Ok(None),
+                Err(ParseError::IterationBudgetExceeded) if self.parser.needs_input() => Ok(None),
+                // `Framed` has no way to yield without waiting for input
+                Err(ParseError::IterationBudgetExceeded) => continue,
+                Err(error) => Err(error.into()),
+            };
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(value) => Ok(Some(value)),
+            // The stream ended inside a frame
+            None if !self.parser.buffer().is_empty() => Err(ParseError::UnexpectedEof.into()),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Encoder<RespValue<'_>> for RespCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: RespValue<'_>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        item.encode_into(dst);
+        Ok(())
+    }
+}
+
+impl Encoder<&RespValue<'_>> for RespCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: &RespValue<'_>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        item.encode_into(dst);
+        Ok(())
+    }
+}
+
+//EOF
@@ -0,0 +1,85 @@
+use crate::codec::{CodecError, RespCodec};
+use crate::parser::{ParseError, ParserConfig};
+use crate::resp::RespValue;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_across_reads() {
+        let mut codec = RespCodec::default();
+        let mut buf = BytesMut::from(&b"*2\r\n$3\r\nGET\r\n$1"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert!(buf.is_empty());
+
+        buf.extend_from_slice(b"\r\nk\r\n:1\r\n");
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("GET".into())),
+                RespValue::BulkString(Some("k".into())),
+            ]))))
+        );
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(RespValue::Integer(1)));
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        assert_eq!(codec.decode_eof(&mut buf).unwrap(), None);
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut codec = RespCodec::default();
+        let mut buf = BytesMut::from(&b"?\r\n"[..]);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(CodecError::Parse(ParseError::InvalidFormat(_)))
+        ));
+
+        let mut codec = RespCodec::default();
+        let mut buf = BytesMut::from(&b"+trunc"[..]);
+        assert!(matches!(
+            codec.decode_eof(&mut buf),
+            Err(CodecError::Parse(ParseError::UnexpectedEof))
+        ));
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut codec = RespCodec::default();
+        let mut dst = BytesMut::new();
+        let value = RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)])));
+        codec.encode(&value, &mut dst).unwrap();
+        codec
+            .encode(RespValue::SimpleString("OK".into()), &mut dst)
+            .unwrap();
+        assert_eq!(&dst[..], b"*1\r\n:1\r\n+OK\r\n");
+
+        let mut decoded = Vec::new();
+        while let Some(value) = codec.decode(&mut dst).unwrap() {
+            decoded.push(value);
+        }
+        assert_eq!(decoded, vec![value, RespValue::SimpleString("OK".into())]);
+    }
+
+    #[test]
+    fn test_decode_with_iteration_budget() {
+        let mut codec = RespCodec::with_config(ParserConfig {
+            max_iterations: Some(4),
+            ..ParserConfig::default()
+        });
+        let expected = RespValue::Array(Some((1..=8).map(RespValue::Integer).collect()));
+
+        // The budget runs out in a frame the buffer can't complete
+        let mut buf = BytesMut::from(&b"*8\r\n:1\r\n:2\r\n:3\r\n:4\r\n:5\r\n:6\r\n"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+        buf.extend_from_slice(b":7\r\n:8\r\n");
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(expected.clone()));
+
+        // A fully buffered frame is returned even though the budget ran out
+        let mut buf = BytesMut::from(&expected.as_bytes()[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap(), Some(expected));
+        assert_eq!(codec.decode(&mut buf).unwrap(), None);
+    }
+}
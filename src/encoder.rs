@@ -0,0 +1,315 @@
+use crate::resp::{EncodeOptions, RespValue, decimal_len, put_header};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::borrow::Cow;
+
+/// Encodes values into a buffer it keeps between frames, so a steady stream of
+/// replies reuses one allocation instead of building a `Vec` for every value and
+/// every nested element as `as_bytes` does.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::encoder::Encoder;
+/// use stream_resp::resp::RespValue;
+///
+/// let mut encoder = Encoder::new();
+/// assert_eq!(encoder.encode(&RespValue::Integer(42)), b":42\r\n");
+/// let reply = RespValue::Array(Some(Box::from(vec![RespValue::OK])));
+/// assert_eq!(encoder.encode(&reply), b"*1\r\n+OK\r\n");
+/// ```
+#[derive(Debug, Default)]
+pub struct Encoder {
+    buffer: BytesMut,
+    options: EncodeOptions,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an encoder that encodes like `as_bytes_with` and `options`.
+    pub fn with_options(options: EncodeOptions) -> Self {
+        Encoder {
+            buffer: BytesMut::new(),
+            options,
+        }
+    }
+
+    /// Creates an encoder whose buffer starts out with room for `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Encoder {
+            buffer: BytesMut::with_capacity(capacity),
+            options: EncodeOptions::default(),
+        }
+    }
+
+    pub fn options(&self) -> EncodeOptions {
+        self.options
+    }
+
+    /// Encodes `value`, replacing the previous frame. The bytes borrow the buffer
+    /// until the next call.
+    pub fn encode(&mut self, value: &RespValue<'_>) -> &[u8] {
+        self.buffer.clear();
+        value.write_to_with(&mut self.buffer, self.options);
+        &self.buffer
+    }
+
+    /// Encodes `value` into `Bytes` that can outlive the encoder, such as for a
+    /// write queue. The allocation is reclaimed once every such `Bytes` has been
+    /// dropped.
+    pub fn encode_to_bytes(&mut self, value: &RespValue<'_>) -> Bytes {
+        self.buffer.clear();
+        value.write_to_with(&mut self.buffer, self.options);
+        self.buffer.split().freeze()
+    }
+
+    /// Appends a pipeline of frames back to back to the buffered ones, after
+    /// reserving room for all of them at once, and returns everything buffered.
+    ///
+    /// Frames accumulate until `clear`, `encode` or `encode_to_bytes` starts the
+    /// buffer over, so a pipeline can be built up across several calls.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::encoder::Encoder;
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let mut encoder = Encoder::new();
+    /// encoder.extend(&[RespValue::Integer(1)]);
+    /// assert_eq!(encoder.extend(&[RespValue::Integer(2)]), b":1\r\n:2\r\n");
+    /// encoder.clear();
+    /// assert_eq!(encoder.extend(&[RespValue::Null]), b"_\r\n");
+    /// ```
+    pub fn extend(&mut self, values: &[RespValue<'_>]) -> &[u8] {
+        let len = values
+            .iter()
+            .map(|value| value.encoded_len_with(self.options))
+            .sum();
+        self.buffer.reserve(len);
+        for value in values {
+            value.write_to_with(&mut self.buffer, self.options);
+        }
+        &self.buffer
+    }
+
+    /// Drops the buffered frames, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Returns the number of bytes the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+}
+
+/// Encodes a pipeline of frames back to back into one buffer, allocated once at
+/// the combined length of the frames.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::encoder::encode_pipeline;
+/// use stream_resp::resp::RespValue;
+///
+/// let ping: RespValue = [RespValue::from("PING")].into_iter().collect();
+/// let bytes = encode_pipeline(&[ping.clone(), ping]);
+/// assert_eq!(&bytes[..], b"*1\r\n+PING\r\n*1\r\n+PING\r\n");
+/// ```
+pub fn encode_pipeline(values: &[RespValue<'_>]) -> Bytes {
+    let len = values.iter().map(RespValue::encoded_len).sum();
+    let mut buffer = BytesMut::with_capacity(len);
+    for value in values {
+        value.write_to(&mut buffer);
+    }
+    buffer.freeze()
+}
+
+/// Encodes a command the way clients send it: an array of bulk strings, one per
+/// argument, in a buffer allocated once at its exact length.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::encoder::encode_command;
+///
+/// let key = String::from("greeting");
+/// let bytes = encode_command(["SET", key.as_str(), "hello"]);
+/// assert_eq!(&bytes[..], b"*3\r\n$3\r\nSET\r\n$8\r\ngreeting\r\n$5\r\nhello\r\n");
+/// ```
+pub fn encode_command<I>(args: I) -> Bytes
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let args: Vec<I::Item> = args.into_iter().collect();
+    let header_len = |len: usize| 1 + decimal_len(len as u64) + 2;
+    let len = header_len(args.len())
+        + args
+            .iter()
+            .map(|arg| header_len(arg.as_ref().len()) + arg.as_ref().len() + 2)
+            .sum::<usize>();
+    let mut buffer = BytesMut::with_capacity(len);
+    put_header(&mut buffer, b'*', args.len());
+    for arg in &args {
+        let arg = arg.as_ref();
+        put_header(&mut buffer, b'$', arg.len());
+        buffer.put_slice(arg);
+        buffer.put_slice(b"\r\n");
+    }
+    buffer.freeze()
+}
+
+// Room for the longest number argument, `i128::MIN`
+const MAX_NUMBER_ARG_LEN: usize = 40;
+
+/// The bytes of a command argument: borrowed from the value passed to `cmd!`, or a
+/// number formatted on the stack.
+#[derive(Debug, Clone, Copy)]
+pub struct Arg<'a>(ArgBytes<'a>);
+
+#[derive(Debug, Clone, Copy)]
+enum ArgBytes<'a> {
+    Borrowed(&'a [u8]),
+    Number([u8; MAX_NUMBER_ARG_LEN], u8),
+}
+
+impl<'a> Arg<'a> {
+    /// Borrows `bytes` as the argument.
+    pub fn borrowed(bytes: &'a [u8]) -> Self {
+        Arg(ArgBytes::Borrowed(bytes))
+    }
+
+    fn number(digits: &str) -> Self {
+        let mut bytes = [0; MAX_NUMBER_ARG_LEN];
+        bytes[..digits.len()].copy_from_slice(digits.as_bytes());
+        Arg(ArgBytes::Number(bytes, digits.len() as u8))
+    }
+}
+
+impl AsRef<[u8]> for Arg<'_> {
+    fn as_ref(&self) -> &[u8] {
+        match &self.0 {
+            ArgBytes::Borrowed(bytes) => bytes,
+            ArgBytes::Number(bytes, len) => &bytes[..usize::from(*len)],
+        }
+    }
+}
+
+/// A value that can be passed to `cmd!` as a command argument: strings and byte
+/// strings as they are, numbers in decimal without allocating.
+///
+/// Doubles are written as their shortest round-trip digits, with an exponent for
+/// very large or small values, such as `1e21`, which Redis parses as well.
+pub trait CommandArg {
+    fn to_arg(&self) -> Arg<'_>;
+}
+
+impl CommandArg for str {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::borrowed(self.as_bytes())
+    }
+}
+
+impl CommandArg for String {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::borrowed(self.as_bytes())
+    }
+}
+
+impl CommandArg for Cow<'_, str> {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::borrowed(self.as_bytes())
+    }
+}
+
+impl CommandArg for [u8] {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::borrowed(self)
+    }
+}
+
+impl<const N: usize> CommandArg for [u8; N] {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::borrowed(self)
+    }
+}
+
+impl CommandArg for Vec<u8> {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::borrowed(self)
+    }
+}
+
+impl CommandArg for Bytes {
+    fn to_arg(&self) -> Arg<'_> {
+        Arg::borrowed(self)
+    }
+}
+
+impl<T: CommandArg + ?Sized> CommandArg for &T {
+    fn to_arg(&self) -> Arg<'_> {
+        (**self).to_arg()
+    }
+}
+
+macro_rules! integer_args {
+    ($($ty:ty),*) => {
+        $(
+            impl CommandArg for $ty {
+                fn to_arg(&self) -> Arg<'_> {
+                    Arg::number(itoa::Buffer::new().format(*self))
+                }
+            }
+        )*
+    };
+}
+
+integer_args!(
+    i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize
+);
+
+macro_rules! float_args {
+    ($($ty:ty),*) => {
+        $(
+            impl CommandArg for $ty {
+                fn to_arg(&self) -> Arg<'_> {
+                    let mut buffer = ryu::Buffer::new();
+                    let formatted = buffer.format(*self);
+                    Arg::number(formatted.strip_suffix(".0").unwrap_or(formatted))
+                }
+            }
+        )*
+    };
+}
+
+float_args!(f32, f64);
+
+/// Encodes a command from arguments of mixed types, each converted with
+/// `CommandArg`, into `Bytes` ready to send; see `encode_command`.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::cmd;
+///
+/// let key = String::from("session");
+/// let bytes = cmd!("SET", key, b"\x00\x01", "EX", 60);
+/// assert_eq!(
+///     &bytes[..],
+///     b"*5\r\n$3\r\nSET\r\n$7\r\nsession\r\n$2\r\n\x00\x01\r\n$2\r\nEX\r\n$2\r\n60\r\n"
+/// );
+/// ```
+#[macro_export]
+macro_rules! cmd {
+    ($($arg:expr),+ $(,)?) => {
+        $crate::encoder::encode_command([
+            $($crate::encoder::CommandArg::to_arg(&$arg)),+
+        ])
+    };
+}
+
+//EOF
@@ -0,0 +1,170 @@
+use crate::encoder::{Encoder, encode_command, encode_pipeline};
+use crate::generator::{GeneratorConfig, frames};
+use crate::resp::{EncodeOptions, RespValue};
+use std::borrow::Cow;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_matches_as_bytes() {
+        let mut encoder = Encoder::new();
+        let config = GeneratorConfig {
+            kinds: GeneratorConfig::default()
+                .kinds
+                .into_iter()
+                .chain([(crate::resp::RespKind::Attribute, 1)])
+                .collect(),
+            ..GeneratorConfig::default()
+        };
+        for frame in frames(11, config).take(500) {
+            let value = frame.value.unwrap();
+            assert_eq!(encoder.encode(&value), value.as_bytes(), "{:?}", value);
+            assert_eq!(encoder.encode_to_bytes(&value), value.as_bytes());
+        }
+
+        let others = [
+            RespValue::BulkBytes(Box::new(bytes::Bytes::from_static(b"bytes"))),
+            RespValue::BulkStreamed { len: 12 },
+            RespValue::Double(f64::NAN),
+            RespValue::Double(f64::NEG_INFINITY),
+            RespValue::Double(1e21),
+            RespValue::Integer(i64::MIN),
+        ];
+        for value in &others {
+            assert_eq!(encoder.encode(value), value.as_bytes(), "{:?}", value);
+        }
+
+        let options = EncodeOptions {
+            double_exponents: true,
+            double_precision: Some(17),
+            resp2_nulls: true,
+        };
+        let mut encoder = Encoder::with_options(options);
+        assert_eq!(encoder.options(), options);
+        for value in others
+            .iter()
+            .chain([&RespValue::Null, &RespValue::Double(2.5)])
+        {
+            assert_eq!(encoder.encode(value), value.as_bytes_with(options));
+        }
+    }
+
+    #[test]
+    fn test_buffer_is_reused() {
+        let mut encoder = Encoder::with_capacity(64);
+        let large: RespValue = (0..10).map(RespValue::Integer).collect();
+        let first = encoder.encode(&large).as_ptr();
+        let second = encoder.encode(&RespValue::Integer(1)).as_ptr();
+        assert_eq!(first, second);
+        assert!(encoder.capacity() >= 64);
+
+        let bytes = encoder.encode_to_bytes(&large);
+        assert_eq!(bytes, large.as_bytes());
+        drop(bytes);
+        // Once the frames handed out are dropped, later ones land in the same
+        // allocation again instead of a new one
+        let allocation = first as usize..first as usize + 64;
+        for _ in 0..50 {
+            let bytes = encoder.encode_to_bytes(&RespValue::Integer(1));
+            assert_eq!(bytes, &b":1\r\n"[..]);
+            assert!(allocation.contains(&(bytes.as_ptr() as usize)));
+        }
+    }
+
+    #[test]
+    fn test_encode_pipeline() {
+        let values: Vec<RespValue> = frames(3, GeneratorConfig::default())
+            .take(50)
+            .map(|frame| frame.value.unwrap())
+            .collect();
+        let expected: Vec<u8> = values.iter().flat_map(|value| value.as_bytes()).collect();
+
+        let bytes = encode_pipeline(&values);
+        assert_eq!(bytes, expected);
+        assert!(encode_pipeline(&[]).is_empty());
+
+        let mut encoder = Encoder::new();
+        assert_eq!(encoder.extend(&values), expected);
+        assert_eq!(encoder.capacity(), expected.len());
+        // Later frames are appended to the earlier ones
+        let mut appended = expected.clone();
+        appended.extend(values[0].as_bytes());
+        assert_eq!(encoder.extend(&values[..1]), appended);
+        encoder.clear();
+        assert_eq!(encoder.extend(&values[..1]), values[0].as_bytes());
+
+        let options = EncodeOptions {
+            resp2_nulls: true,
+            ..EncodeOptions::default()
+        };
+        let mut encoder = Encoder::with_options(options);
+        let nulls = [RespValue::Null, RespValue::Null];
+        assert_eq!(encoder.extend(&nulls), b"$-1\r\n$-1\r\n");
+        assert_eq!(encoder.capacity(), 10);
+    }
+
+    #[test]
+    fn test_encode_command() {
+        let bytes = encode_command(["GET", "key"]);
+        assert_eq!(&bytes[..], b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n");
+        assert_eq!(bytes.len(), 22);
+
+        let binary: Vec<Vec<u8>> = vec![b"SET".to_vec(), vec![0, 255, b'\r', b'\n'], Vec::new()];
+        let bytes = encode_command(&binary);
+        assert_eq!(
+            &bytes[..],
+            b"*3\r\n$3\r\nSET\r\n$4\r\n\x00\xff\r\n\r\n$0\r\n\r\n"
+        );
+
+        let mut parser = crate::parser::Parser::new(10, 1024);
+        parser.read_buf(&encode_command(["HSET", "hash", "field", "value"]));
+        let value = parser.try_parse().unwrap().unwrap();
+        let args: Vec<&str> = value.iter().filter_map(RespValue::as_str).collect();
+        assert_eq!(args, ["HSET", "hash", "field", "value"]);
+
+        assert_eq!(&encode_command(Vec::<&str>::new())[..], b"*0\r\n");
+    }
+
+    #[test]
+    fn test_cmd_macro() {
+        let key = String::from("counter");
+        let payload = bytes::Bytes::from_static(b"\x00raw");
+        assert_eq!(
+            crate::cmd!("INCRBY", key, -5),
+            encode_command(["INCRBY", "counter", "-5"])
+        );
+        assert_eq!(
+            crate::cmd!("SET", &key, payload, "PX", 1500u64, "GET",),
+            encode_command([&b"SET"[..], b"counter", b"\x00raw", b"PX", b"1500", b"GET"])
+        );
+        assert_eq!(
+            crate::cmd!("ZADD", key, 2.5, vec![b'm'], Cow::Borrowed("n")),
+            encode_command(["ZADD", "counter", "2.5", "m", "n"])
+        );
+        assert_eq!(
+            crate::cmd!(
+                "SET",
+                i128::MIN,
+                u128::MAX,
+                1.0,
+                1e21,
+                -0.5f32,
+                f64::INFINITY
+            ),
+            encode_command([
+                "SET",
+                "-170141183460469231731687303715884105728",
+                "340282366920938463463374607431768211455",
+                "1",
+                "1e21",
+                "-0.5",
+                "inf",
+            ])
+        );
+        // The arguments are borrowed, not moved
+        assert_eq!(key, "counter");
+        assert_eq!(crate::cmd!("PING"), encode_command(["PING"]));
+    }
+}
@@ -0,0 +1,210 @@
+//! Canonical error reply texts, identical to the ones redis-server sends.
+//!
+//! Servers built on this crate can use them so clients see the same errors as from
+//! redis-server. Texts include the error code prefix but no leading `-` or CRLF;
+//! `error_reply` turns them into a `RespValue::Error`.
+//!
+//! Clients can split the errors they receive into code and message with `ErrorReply`.
+
+use crate::resp::RespValue;
+use std::borrow::Cow;
+
+pub const INVALID_MULTIBULK_LENGTH: &str = "ERR Protocol error: invalid multibulk length";
+pub const INVALID_BULK_LENGTH: &str = "ERR Protocol error: invalid bulk length";
+pub const UNBALANCED_QUOTES: &str = "ERR Protocol error: unbalanced quotes in request";
+pub const TOO_BIG_INLINE_REQUEST: &str = "ERR Protocol error: too big inline request";
+pub const TOO_BIG_MBULK_COUNT: &str = "ERR Protocol error: too big mbulk count string";
+pub const TOO_BIG_BULK_COUNT: &str = "ERR Protocol error: too big bulk count string";
+pub const UNAUTHENTICATED: &str = "NOAUTH Authentication required.";
+pub const SYNTAX_ERROR: &str = "ERR syntax error";
+pub const NOT_AN_INTEGER: &str = "ERR value is not an integer or out of range";
+pub const NOT_A_FLOAT: &str = "ERR value is not a valid float";
+pub const WRONG_TYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+pub const NO_SUCH_KEY: &str = "ERR no such key";
+pub const OUT_OF_RANGE: &str = "ERR index out of range";
+
+/// Longest command name quoted by `unknown_command`, and the budget for its quoted
+/// arguments, as in redis-server.
+const MAX_QUOTED_LEN: usize = 128;
+
+/// `ERR Protocol error: expected '$', got '<byte>'`, sent when a multibulk element
+/// does not start with `$`.
+pub fn expected_dollar(got: u8) -> String {
+    format!("ERR Protocol error: expected '$', got '{}'", got as char)
+}
+
+/// `ERR unknown command '<name>', with args beginning with: '<arg>' ...`
+///
+/// Like redis-server, the name is cut at 128 bytes, and the arguments share a
+/// 128-byte budget: each one is cut to what is left of it, and no more are quoted
+/// once the quoted arguments reach 128 bytes.
+pub fn unknown_command<'a>(name: &str, args: impl IntoIterator<Item = &'a str>) -> String {
+    let mut quoted = String::new();
+    for arg in args {
+        if quoted.len() >= MAX_QUOTED_LEN {
+            break;
+        }
+        let budget = MAX_QUOTED_LEN - quoted.len();
+        quoted.push('\'');
+        quoted.push_str(truncate(arg, budget));
+        quoted.push_str("' ");
+    }
+    format!(
+        "ERR unknown command '{}', with args beginning with: {}",
+        truncate(name, MAX_QUOTED_LEN),
+        quoted
+    )
+}
+
+/// `ERR wrong number of arguments for '<name>' command`
+pub fn wrong_arity(name: &str) -> String {
+    format!("ERR wrong number of arguments for '{}' command", name)
+}
+
+/// Wraps an error text into the value to reply with.
+pub fn error_reply(text: impl Into<Cow<'static, str>>) -> RespValue<'static> {
+    RespValue::Error(text.into())
+}
+
+/// The code of an error reply, the first word of its text by Redis convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode<'a> {
+    Err,
+    WrongType,
+    Moved,
+    Ask,
+    TryAgain,
+    CrossSlot,
+    ClusterDown,
+    NoAuth,
+    NoPerm,
+    WrongPass,
+    NoProto,
+    NoScript,
+    Busy,
+    BusyKey,
+    Loading,
+    ReadOnly,
+    MasterDown,
+    Misconf,
+    ExecAbort,
+    Oom,
+    NoReplicas,
+    /// Any other code, such as one defined by a module.
+    Other(&'a str),
+}
+
+impl<'a> ErrorCode<'a> {
+    /// Returns the well-known code spelled `code`, or `Other`.
+    pub fn from_code(code: &'a str) -> Self {
+        match code {
+            "ERR" => ErrorCode::Err,
+            "WRONGTYPE" => ErrorCode::WrongType,
+            "MOVED" => ErrorCode::Moved,
+            "ASK" => ErrorCode::Ask,
+            "TRYAGAIN" => ErrorCode::TryAgain,
+            "CROSSSLOT" => ErrorCode::CrossSlot,
+            "CLUSTERDOWN" => ErrorCode::ClusterDown,
+            "NOAUTH" => ErrorCode::NoAuth,
+            "NOPERM" => ErrorCode::NoPerm,
+            "WRONGPASS" => ErrorCode::WrongPass,
+            "NOPROTO" => ErrorCode::NoProto,
+            "NOSCRIPT" => ErrorCode::NoScript,
+            "BUSY" => ErrorCode::Busy,
+            "BUSYKEY" => ErrorCode::BusyKey,
+            "LOADING" => ErrorCode::Loading,
+            "READONLY" => ErrorCode::ReadOnly,
+            "MASTERDOWN" => ErrorCode::MasterDown,
+            "MISCONF" => ErrorCode::Misconf,
+            "EXECABORT" => ErrorCode::ExecAbort,
+            "OOM" => ErrorCode::Oom,
+            "NOREPLICAS" => ErrorCode::NoReplicas,
+            other => ErrorCode::Other(other),
+        }
+    }
+
+    /// Returns the code as it is spelled on the wire.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            ErrorCode::Err => "ERR",
+            ErrorCode::WrongType => "WRONGTYPE",
+            ErrorCode::Moved => "MOVED",
+            ErrorCode::Ask => "ASK",
+            ErrorCode::TryAgain => "TRYAGAIN",
+            ErrorCode::CrossSlot => "CROSSSLOT",
+            ErrorCode::ClusterDown => "CLUSTERDOWN",
+            ErrorCode::NoAuth => "NOAUTH",
+            ErrorCode::NoPerm => "NOPERM",
+            ErrorCode::WrongPass => "WRONGPASS",
+            ErrorCode::NoProto => "NOPROTO",
+            ErrorCode::NoScript => "NOSCRIPT",
+            ErrorCode::Busy => "BUSY",
+            ErrorCode::BusyKey => "BUSYKEY",
+            ErrorCode::Loading => "LOADING",
+            ErrorCode::ReadOnly => "READONLY",
+            ErrorCode::MasterDown => "MASTERDOWN",
+            ErrorCode::Misconf => "MISCONF",
+            ErrorCode::ExecAbort => "EXECABORT",
+            ErrorCode::Oom => "OOM",
+            ErrorCode::NoReplicas => "NOREPLICAS",
+            ErrorCode::Other(code) => code,
+        }
+    }
+}
+
+/// An error text split into its code and message, `-<CODE> <message>` by Redis
+/// convention.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::error_text::{ErrorCode, ErrorReply};
+///
+/// let reply = ErrorReply::parse("MOVED 3999 127.0.0.1:6381");
+/// assert_eq!(reply.code, Some(ErrorCode::Moved));
+/// assert_eq!(reply.message, "3999 127.0.0.1:6381");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorReply<'a> {
+    /// The code, or `None` if the text doesn't start with an uppercase word.
+    pub code: Option<ErrorCode<'a>>,
+    /// The text after the code, or the whole text if there is none.
+    pub message: &'a str,
+}
+
+impl<'a> ErrorReply<'a> {
+    /// Splits an error text, as held by `RespValue::Error`.
+    pub fn parse(text: &'a str) -> Self {
+        let (word, rest) = text.split_once(' ').unwrap_or((text, ""));
+        let is_code = !word.is_empty()
+            && word.starts_with(|c: char| c.is_ascii_uppercase())
+            && word
+                .bytes()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_' || b == b'-');
+        if is_code {
+            ErrorReply {
+                code: Some(ErrorCode::from_code(word)),
+                message: rest,
+            }
+        } else {
+            ErrorReply {
+                code: None,
+                message: text,
+            }
+        }
+    }
+}
+
+/// Cuts `s` to at most `max` bytes on a character boundary.
+fn truncate(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+//EOF
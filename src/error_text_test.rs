@@ -0,0 +1,111 @@
+use crate::error_text::{
+    ErrorCode, ErrorReply, INVALID_BULK_LENGTH, WRONG_TYPE, error_reply, expected_dollar,
+    unknown_command, wrong_arity,
+};
+use crate::resp::RespValue;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formatted_texts() {
+        assert_eq!(
+            expected_dollar(b':'),
+            "ERR Protocol error: expected '$', got ':'"
+        );
+        assert_eq!(
+            wrong_arity("get"),
+            "ERR wrong number of arguments for 'get' command"
+        );
+        assert_eq!(
+            unknown_command("foo", ["a", "b"]),
+            "ERR unknown command 'foo', with args beginning with: 'a' 'b' "
+        );
+        assert_eq!(
+            unknown_command("foo", []),
+            "ERR unknown command 'foo', with args beginning with: "
+        );
+    }
+
+    #[test]
+    fn test_unknown_command_truncates_long_args() {
+        let long = "é".repeat(100);
+        let text = unknown_command("x", [long.as_str()]);
+        let quoted = text.rsplit_once(": '").unwrap().1;
+        assert_eq!(quoted, format!("{}' ", "é".repeat(64)));
+    }
+
+    #[test]
+    fn test_error_reply() {
+        assert_eq!(
+            error_reply(INVALID_BULK_LENGTH).as_bytes(),
+            b"-ERR Protocol error: invalid bulk length\r\n"
+        );
+        assert_eq!(
+            error_reply(wrong_arity("set")),
+            RespValue::Error("ERR wrong number of arguments for 'set' command".into())
+        );
+    }
+
+    #[test]
+    fn test_error_reply_code_and_message() {
+        let reply = ErrorReply::parse(WRONG_TYPE);
+        assert_eq!(reply.code, Some(ErrorCode::WrongType));
+        assert_eq!(
+            reply.message,
+            "Operation against a key holding the wrong kind of value"
+        );
+
+        let reply = ErrorReply::parse("ERR");
+        assert_eq!(reply.code, Some(ErrorCode::Err));
+        assert_eq!(reply.message, "");
+
+        let reply = ErrorReply::parse("MYMODULE_ERR2 failed");
+        assert_eq!(reply.code, Some(ErrorCode::Other("MYMODULE_ERR2")));
+        assert_eq!(reply.code.unwrap().as_str(), "MYMODULE_ERR2");
+
+        for text in ["something went wrong", "Err x", "", " ERR x"] {
+            let reply = ErrorReply::parse(text);
+            assert_eq!(reply.code, None);
+            assert_eq!(reply.message, text);
+        }
+
+        for code in ["ERR", "ASK", "NOAUTH", "OOM", "BUSYKEY"] {
+            assert_eq!(ErrorCode::from_code(code).as_str(), code);
+            assert_ne!(ErrorCode::from_code(code), ErrorCode::Other(code));
+        }
+
+        let value = error_reply("NOAUTH Authentication required.");
+        assert_eq!(
+            value.error_reply(),
+            Some(ErrorReply {
+                code: Some(ErrorCode::NoAuth),
+                message: "Authentication required.",
+            })
+        );
+        let value = RespValue::BulkError(Some("SYNTAX invalid".into()));
+        assert_eq!(
+            value.error_reply().unwrap().code,
+            Some(ErrorCode::Other("SYNTAX"))
+        );
+        assert_eq!(RespValue::SimpleString("ERR x".into()).error_reply(), None);
+    }
+
+    #[test]
+    fn test_unknown_command_shares_budget_across_args() {
+        let (a, b, c) = ("a".repeat(100), "b".repeat(100), "c".repeat(100));
+        let text = unknown_command("x", [a.as_str(), b.as_str(), c.as_str()]);
+        let quoted = text.split_once("with: ").unwrap().1;
+        assert_eq!(quoted, format!("'{}' '{}' ", a, "b".repeat(25)));
+
+        // Quoting stops once the budget is used up
+        let text = unknown_command("x", ["x"; 40]);
+        let quoted = text.split_once("with: ").unwrap().1;
+        assert_eq!(quoted, "'x' ".repeat(32));
+
+        let name = "n".repeat(200);
+        let text = unknown_command(&name, []);
+        assert!(text.contains(&format!("'{}'", "n".repeat(128))));
+    }
+}
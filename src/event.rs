@@ -0,0 +1,334 @@
+use crate::parser::{ParseError, find_crlf, is_verbatim_prefix, parse_decimal};
+use bytes::BytesMut;
+
+const CRLF_LEN: usize = 2;
+const DEFAULT_BUFFER_INIT_SIZE: usize = 4096;
+// The element count of a streamed aggregate, which its `.` end marker closes
+const STREAMED: usize = usize::MAX;
+
+/// A single parsing event produced by `EventParser`.
+///
+/// Aggregates are reported as a start event carrying their element count, followed by
+/// their elements and a closing `End`; streamed aggregates start with `StreamedStart`
+/// instead and end at their `.` end marker. Bulk strings are reported as `BulkStart`, any
+/// number of `BulkChunk`s as the payload arrives, and `BulkEnd`; streamed strings as
+/// `StreamedStart`, the `BulkChunk`s of every `;<len>` chunk, and `BulkEnd` at `;0`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event<'a> {
+    ArrayStart(usize),
+    /// Number of key-value pairs; `2 * len` elements follow.
+    MapStart(usize),
+    SetStart(usize),
+    PushStart(usize),
+    /// Number of attribute key-value pairs; `2 * len` elements follow, then the value
+    /// they annotate, then `End`.
+    AttributeStart(usize),
+    /// A RESP3 streamed aggregate or string of unknown length, carrying its type
+    /// marker. The elements of an aggregate (`*`, `%`, `~` or `>`) follow until
+    /// `End`, the chunks of a string (`$`) until `BulkEnd`.
+    StreamedStart(u8),
+    /// Closes the innermost aggregate.
+    End,
+    BulkStart(usize),
+    BulkChunk(&'a [u8]),
+    BulkEnd,
+    SimpleString(&'a [u8]),
+    Error(&'a [u8]),
+    IntegerValue(i64),
+    Double(f64),
+    Boolean(bool),
+    BigNumber(&'a [u8]),
+    BulkError(&'a [u8]),
+    VerbatimString(&'a [u8]),
+    /// A null value, carrying the type marker it was encoded with (`_`, `$`, `*`, ...).
+    Null(u8),
+}
+
+/// A pull-based (SAX-style) RESP parser.
+///
+/// Unlike `Parser`, it never materializes `RespValue` trees, so gigantic aggregates and
+/// bulk strings can be processed with memory bounded by the size of the fed chunks.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::event::{Event, EventParser};
+///
+/// let mut parser = EventParser::new(10, 1024);
+/// parser.read_buf(b"*2\r\n:1\r\n$3\r\nfoo\r\n");
+/// assert_eq!(parser.next_event(), Ok(Event::ArrayStart(2)));
+/// assert_eq!(parser.next_event(), Ok(Event::IntegerValue(1)));
+/// assert_eq!(parser.next_event(), Ok(Event::BulkStart(3)));
+/// assert_eq!(parser.next_event(), Ok(Event::BulkChunk(b"foo")));
+/// assert_eq!(parser.next_event(), Ok(Event::BulkEnd));
+/// assert_eq!(parser.next_event(), Ok(Event::End));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventParser {
+    buffer: BytesMut,
+    pos: usize,
+    max_depth: usize,
+    max_length: usize,
+    // Remaining element counts of the aggregates currently open, `STREAMED` for
+    // streamed aggregates
+    stack: Vec<usize>,
+    // Remaining payload bytes of the bulk string or chunk currently streamed
+    bulk_remaining: Option<usize>,
+    // Inside a streamed string, where another chunk follows each one until `;0`
+    streamed_bulk: bool,
+}
+
+impl EventParser {
+    /// Creates a new event parser.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The maximum depth of nested aggregates.
+    /// * `max_length` - The maximum length of bulk strings.
+    pub fn new(max_depth: usize, max_length: usize) -> Self {
+        EventParser {
+            buffer: BytesMut::with_capacity(DEFAULT_BUFFER_INIT_SIZE),
+            pos: 0,
+            max_depth,
+            max_length,
+            stack: Vec::with_capacity(max_depth),
+            bulk_remaining: None,
+            streamed_bulk: false,
+        }
+    }
+
+    /// Appends bytes to the internal buffer, dropping already consumed data first.
+    pub fn read_buf(&mut self, buf: &[u8]) {
+        if self.pos > 0 {
+            let _ = self.buffer.split_to(self.pos);
+            self.pos = 0;
+        }
+        self.buffer.extend_from_slice(buf);
+    }
+
+    /// Returns the current nesting depth of open aggregates.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the next event.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::UnexpectedEof` or `ParseError::NotEnoughData` if more input is
+    /// needed; the call can be retried after feeding more bytes.
+    pub fn next_event(&mut self) -> Result<Event<'_>, ParseError> {
+        if let Some(&0) = self.stack.last() {
+            self.stack.pop();
+            self.finish_value();
+            return Ok(Event::End);
+        }
+
+        if let Some(remaining) = self.bulk_remaining {
+            return self.next_bulk_event(remaining);
+        }
+        if self.streamed_bulk {
+            return self.next_chunk_event();
+        }
+
+        let marker = *self.buffer.get(self.pos).ok_or(ParseError::UnexpectedEof)?;
+        let line_end = find_crlf(&self.buffer, self.pos + 1).ok_or(ParseError::UnexpectedEof)?;
+        let line_start = self.pos + 1;
+        let line = &self.buffer[line_start..line_end];
+
+        let event = match marker {
+            b'$' if line == b"?" => {
+                self.streamed_bulk = true;
+                self.pos = line_end + CRLF_LEN;
+                return Ok(Event::StreamedStart(marker));
+            }
+            b'*' | b'%' | b'~' | b'>' if line == b"?" => {
+                if self.stack.len() >= self.max_depth {
+                    return Err(ParseError::InvalidDepth);
+                }
+                self.stack.push(STREAMED);
+                self.pos = line_end + CRLF_LEN;
+                return Ok(Event::StreamedStart(marker));
+            }
+            b'.' if self.stack.last() == Some(&STREAMED) => {
+                if !line.is_empty() {
+                    return Err(ParseError::InvalidFormat("Invalid end marker".into()));
+                }
+                self.stack.pop();
+                self.pos = line_end + CRLF_LEN;
+                self.finish_value();
+                return Ok(Event::End);
+            }
+            b'|' if parse_decimal(line)? < 0 => {
+                return Err(ParseError::InvalidFormat("Invalid attribute length".into()));
+            }
+            b'*' | b'%' | b'~' | b'>' | b'|' => {
+                let len = parse_decimal(line)?;
+                if len < 0 {
+                    Event::Null(marker)
+                } else {
+                    if self.stack.len() >= self.max_depth {
+                        return Err(ParseError::InvalidDepth);
+                    }
+                    let len = len as usize;
+                    self.stack.push(match marker {
+                        b'%' => len * 2,
+                        b'|' => len * 2 + 1,
+                        _ => len,
+                    });
+                    self.pos = line_end + CRLF_LEN;
+                    return Ok(match marker {
+                        b'*' => Event::ArrayStart(len),
+                        b'%' => Event::MapStart(len),
+                        b'~' => Event::SetStart(len),
+                        b'|' => Event::AttributeStart(len),
+                        _ => Event::PushStart(len),
+                    });
+                }
+            }
+            b'$' => {
+                let len = parse_decimal(line)?;
+                if len < 0 {
+                    Event::Null(marker)
+                } else {
+                    let len = len as usize;
+                    if len >= self.max_length {
+                        return Err(ParseError::InvalidLength);
+                    }
+                    self.bulk_remaining = Some(len);
+                    self.pos = line_end + CRLF_LEN;
+                    return Ok(Event::BulkStart(len));
+                }
+            }
+            b'=' | b'!' => {
+                let len = parse_decimal(line)?;
+                if len < 0 {
+                    Event::Null(marker)
+                } else {
+                    // Verbatim strings and bulk errors are short, so they are buffered whole
+                    let len = len as usize;
+                    if len >= self.max_length {
+                        return Err(ParseError::InvalidLength);
+                    }
+                    let start = line_end + CRLF_LEN;
+                    if self.buffer.len() < start + len + CRLF_LEN {
+                        return Err(ParseError::NotEnoughData);
+                    }
+                    let payload = &self.buffer[start..start + len];
+                    if marker == b'=' && !is_verbatim_prefix(payload) {
+                        return Err(ParseError::InvalidFormat(
+                            "Invalid verbatim string format prefix".into(),
+                        ));
+                    }
+                    if self.buffer[start + len..start + len + CRLF_LEN] != *b"\r\n" {
+                        return Err(ParseError::InvalidFormat("Missing CRLF terminator".into()));
+                    }
+                    self.pos = start + len + CRLF_LEN;
+                    self.finish_value();
+                    let payload = &self.buffer[start..start + len];
+                    return Ok(match marker {
+                        b'=' => Event::VerbatimString(payload),
+                        _ => Event::BulkError(payload),
+                    });
+                }
+            }
+            b':' => Event::IntegerValue(parse_decimal(line)?),
+            b',' => match std::str::from_utf8(line) {
+                Ok(s) => Event::Double(
+                    s.parse::<f64>()
+                        .map_err(|_| ParseError::InvalidFormat("Invalid double value".into()))?,
+                ),
+                Err(_) => return Err(ParseError::InvalidUtf8),
+            },
+            b'#' => match line {
+                b"t" => Event::Boolean(true),
+                b"f" => Event::Boolean(false),
+                _ => return Err(ParseError::InvalidFormat("Invalid boolean value".into())),
+            },
+            b'_' if line.is_empty() => Event::Null(marker),
+            b'_' => return Err(ParseError::InvalidFormat("Invalid null value".into())),
+            b'+' | b'-' | b'(' => {
+                self.pos = line_end + CRLF_LEN;
+                self.finish_value();
+                let line = &self.buffer[line_start..line_end];
+                return Ok(match marker {
+                    b'+' => Event::SimpleString(line),
+                    b'-' => Event::Error(line),
+                    _ => Event::BigNumber(line),
+                });
+            }
+            _ => return Err(ParseError::InvalidFormat("Invalid type marker".into())),
+        };
+
+        self.pos = line_end + CRLF_LEN;
+        self.finish_value();
+        Ok(event)
+    }
+
+    fn next_bulk_event(&mut self, remaining: usize) -> Result<Event<'_>, ParseError> {
+        if remaining == 0 {
+            if self.buffer.len() < self.pos + CRLF_LEN {
+                return Err(ParseError::NotEnoughData);
+            }
+            if self.buffer[self.pos..self.pos + CRLF_LEN] != *b"\r\n" {
+                return Err(ParseError::InvalidFormat("Missing CRLF terminator".into()));
+            }
+            self.pos += CRLF_LEN;
+            self.bulk_remaining = None;
+            if self.streamed_bulk {
+                return self.next_chunk_event();
+            }
+            self.finish_value();
+            return Ok(Event::BulkEnd);
+        }
+
+        let available = remaining.min(self.buffer.len() - self.pos);
+        if available == 0 {
+            return Err(ParseError::NotEnoughData);
+        }
+        let start = self.pos;
+        self.pos += available;
+        self.bulk_remaining = Some(remaining - available);
+        Ok(Event::BulkChunk(&self.buffer[start..start + available]))
+    }
+
+    /// Reads the header of the next chunk of a streamed string, `;<len>`, and returns
+    /// the start of its payload, or `BulkEnd` for the closing `;0`.
+    fn next_chunk_event(&mut self) -> Result<Event<'_>, ParseError> {
+        let marker = *self.buffer.get(self.pos).ok_or(ParseError::UnexpectedEof)?;
+        if marker != b';' {
+            return Err(ParseError::InvalidFormat(
+                "Expected a streamed string chunk".into(),
+            ));
+        }
+        let line_end = find_crlf(&self.buffer, self.pos + 1).ok_or(ParseError::UnexpectedEof)?;
+        let len = parse_decimal(&self.buffer[self.pos + 1..line_end])?;
+        if len < 0 {
+            return Err(ParseError::InvalidFormat("Invalid chunk length".into()));
+        }
+        let len = len as usize;
+        if len >= self.max_length {
+            return Err(ParseError::InvalidLength);
+        }
+        self.pos = line_end + CRLF_LEN;
+        if len == 0 {
+            self.streamed_bulk = false;
+            self.finish_value();
+            return Ok(Event::BulkEnd);
+        }
+        self.bulk_remaining = Some(len);
+        self.next_bulk_event(len)
+    }
+
+    /// Counts a completed value against the innermost open aggregate.
+    #[inline(always)]
+    fn finish_value(&mut self) {
+        if let Some(remaining) = self.stack.last_mut()
+            && *remaining != STREAMED
+        {
+            *remaining -= 1;
+        }
+    }
+}
+
+//EOF
@@ -0,0 +1,186 @@
+use crate::event::{Event, EventParser};
+use crate::parser::ParseError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scalar_events() {
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"+OK\r\n-ERR bad\r\n:-42\r\n,1.5\r\n#t\r\n_\r\n(123\r\n$-1\r\n");
+
+        assert_eq!(parser.next_event(), Ok(Event::SimpleString(b"OK")));
+        assert_eq!(parser.next_event(), Ok(Event::Error(b"ERR bad")));
+        assert_eq!(parser.next_event(), Ok(Event::IntegerValue(-42)));
+        assert_eq!(parser.next_event(), Ok(Event::Double(1.5)));
+        assert_eq!(parser.next_event(), Ok(Event::Boolean(true)));
+        assert_eq!(parser.next_event(), Ok(Event::Null(b'_')));
+        assert_eq!(parser.next_event(), Ok(Event::BigNumber(b"123")));
+        assert_eq!(parser.next_event(), Ok(Event::Null(b'$')));
+        assert_eq!(parser.next_event(), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_nested_aggregate_events() {
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"*3\r\n%1\r\n+k\r\n*0\r\n~1\r\n:1\r\n*-1\r\n");
+
+        assert_eq!(parser.next_event(), Ok(Event::ArrayStart(3)));
+        assert_eq!(parser.next_event(), Ok(Event::MapStart(1)));
+        assert_eq!(parser.next_event(), Ok(Event::SimpleString(b"k")));
+        assert_eq!(parser.next_event(), Ok(Event::ArrayStart(0)));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.next_event(), Ok(Event::SetStart(1)));
+        assert_eq!(parser.depth(), 2);
+        assert_eq!(parser.next_event(), Ok(Event::IntegerValue(1)));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.next_event(), Ok(Event::Null(b'*')));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.depth(), 0);
+        assert_eq!(parser.next_event(), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_bulk_chunk_events() {
+        let mut parser = EventParser::new(10, 1024);
+
+        parser.read_buf(b"$10\r\nhello");
+        assert_eq!(parser.next_event(), Ok(Event::BulkStart(10)));
+        assert_eq!(parser.next_event(), Ok(Event::BulkChunk(b"hello")));
+        assert_eq!(parser.next_event(), Err(ParseError::NotEnoughData));
+
+        parser.read_buf(b"world");
+        assert_eq!(parser.next_event(), Ok(Event::BulkChunk(b"world")));
+        assert_eq!(parser.next_event(), Err(ParseError::NotEnoughData));
+
+        parser.read_buf(b"\r\n$0\r\n\r\n");
+        assert_eq!(parser.next_event(), Ok(Event::BulkEnd));
+        assert_eq!(parser.next_event(), Ok(Event::BulkStart(0)));
+        assert_eq!(parser.next_event(), Ok(Event::BulkEnd));
+    }
+
+    #[test]
+    fn test_event_errors() {
+        let mut parser = EventParser::new(1, 8);
+        parser.read_buf(b"*1\r\n*1\r\n");
+        assert_eq!(parser.next_event(), Ok(Event::ArrayStart(1)));
+        assert_eq!(parser.next_event(), Err(ParseError::InvalidDepth));
+
+        let mut parser = EventParser::new(1, 8);
+        parser.read_buf(b"$100\r\n");
+        assert_eq!(parser.next_event(), Err(ParseError::InvalidLength));
+
+        let mut parser = EventParser::new(1, 8);
+        parser.read_buf(b"?\r\n");
+        assert!(matches!(
+            parser.next_event(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_verbatim_string_events() {
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"*2\r\n=9\r\ntxt:a");
+        assert_eq!(parser.next_event(), Ok(Event::ArrayStart(2)));
+        assert_eq!(parser.next_event(), Err(ParseError::NotEnoughData));
+        parser.read_buf(b"\r\nbc\r\n=-1\r\n");
+        assert_eq!(
+            parser.next_event(),
+            Ok(Event::VerbatimString(b"txt:a\r\nbc"))
+        );
+        assert_eq!(parser.next_event(), Ok(Event::Null(b'=')));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+    }
+
+    #[test]
+    fn test_bulk_error_events() {
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"!21\r\nSYNTAX invalid syntax\r\n!-1\r\n");
+        assert_eq!(
+            parser.next_event(),
+            Ok(Event::BulkError(b"SYNTAX invalid syntax"))
+        );
+        assert_eq!(parser.next_event(), Ok(Event::Null(b'!')));
+    }
+
+    #[test]
+    fn test_attribute_events() {
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"*1\r\n|1\r\n+ttl\r\n:3600\r\n+OK\r\n");
+        assert_eq!(parser.next_event(), Ok(Event::ArrayStart(1)));
+        assert_eq!(parser.next_event(), Ok(Event::AttributeStart(1)));
+        assert_eq!(parser.next_event(), Ok(Event::SimpleString(b"ttl")));
+        assert_eq!(parser.next_event(), Ok(Event::IntegerValue(3600)));
+        assert_eq!(parser.next_event(), Ok(Event::SimpleString(b"OK")));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.depth(), 0);
+    }
+
+    #[test]
+    fn test_streamed_aggregate_events() {
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"*?\r\n:1\r\n%?\r\n+k\r\n:2\r\n.\r\n*2\r\n~?\r\n.\r\n:3\r\n.\r\n");
+
+        assert_eq!(parser.next_event(), Ok(Event::StreamedStart(b'*')));
+        assert_eq!(parser.next_event(), Ok(Event::IntegerValue(1)));
+        assert_eq!(parser.next_event(), Ok(Event::StreamedStart(b'%')));
+        assert_eq!(parser.next_event(), Ok(Event::SimpleString(b"k")));
+        assert_eq!(parser.next_event(), Ok(Event::IntegerValue(2)));
+        assert_eq!(parser.depth(), 2);
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.next_event(), Ok(Event::ArrayStart(2)));
+        assert_eq!(parser.next_event(), Ok(Event::StreamedStart(b'~')));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.next_event(), Ok(Event::IntegerValue(3)));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+        assert_eq!(parser.depth(), 0);
+        assert_eq!(parser.next_event(), Err(ParseError::UnexpectedEof));
+
+        // An end marker outside a streamed aggregate is not a value
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b".\r\n");
+        assert!(matches!(
+            parser.next_event(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_streamed_string_events() {
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"$?\r\n;2\r\nhi\r\n;0\r\n");
+        assert_eq!(parser.next_event(), Ok(Event::StreamedStart(b'$')));
+        assert_eq!(parser.next_event(), Ok(Event::BulkChunk(b"hi")));
+        assert_eq!(parser.next_event(), Ok(Event::BulkEnd));
+        assert_eq!(parser.next_event(), Err(ParseError::UnexpectedEof));
+
+        // Chunks may arrive in pieces, inside an aggregate
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"*2\r\n$?\r\n;5\r\nab");
+        assert_eq!(parser.next_event(), Ok(Event::ArrayStart(2)));
+        assert_eq!(parser.next_event(), Ok(Event::StreamedStart(b'$')));
+        assert_eq!(parser.next_event(), Ok(Event::BulkChunk(b"ab")));
+        assert_eq!(parser.next_event(), Err(ParseError::NotEnoughData));
+        parser.read_buf(b"cde\r\n;1");
+        assert_eq!(parser.next_event(), Ok(Event::BulkChunk(b"cde")));
+        assert_eq!(parser.next_event(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"\r\nf\r\n;0\r\n:1\r\n");
+        assert_eq!(parser.next_event(), Ok(Event::BulkChunk(b"f")));
+        assert_eq!(parser.next_event(), Ok(Event::BulkEnd));
+        assert_eq!(parser.next_event(), Ok(Event::IntegerValue(1)));
+        assert_eq!(parser.next_event(), Ok(Event::End));
+
+        let mut parser = EventParser::new(10, 1024);
+        parser.read_buf(b"$?\r\n+hi\r\n");
+        assert_eq!(parser.next_event(), Ok(Event::StreamedStart(b'$')));
+        assert!(matches!(
+            parser.next_event(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::resp::{Attributed, RespKind, RespValue, Verbatim};
+use std::borrow::Cow;
+
+// Bytes that can never start a frame, used to corrupt one
+const INVALID_MARKERS: &[u8] = b"?@&^";
+const PAYLOAD_ALPHABET: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 -_.:";
+
+/// Shapes the frames produced by `frames`.
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    /// Relative weights of the frame types. Aggregate types, attributes included, are
+    /// only picked while `max_depth` allows it.
+    pub kinds: Vec<(RespKind, u32)>,
+    /// The maximum nesting depth of aggregates; 0 produces flat values only.
+    pub max_depth: usize,
+    /// The maximum number of elements of an aggregate, or of entries of a map.
+    pub max_elements: usize,
+    /// The maximum length of string payloads.
+    pub max_length: usize,
+    /// The probability, from 0.0 to 1.0, of a frame being corrupted.
+    pub invalid_ratio: f64,
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        GeneratorConfig {
+            kinds: vec![
+                (RespKind::SimpleString, 1),
+                (RespKind::Error, 1),
+                (RespKind::Integer, 1),
+                (RespKind::BulkString, 1),
+                (RespKind::Array, 1),
+                (RespKind::Null, 1),
+                (RespKind::Boolean, 1),
+                (RespKind::Double, 1),
+                (RespKind::BigNumber, 1),
+                (RespKind::BulkError, 1),
+                (RespKind::VerbatimString, 1),
+                (RespKind::Map, 1),
+                (RespKind::Set, 1),
+                (RespKind::Push, 1),
+            ],
+            max_depth: 3,
+            max_elements: 8,
+            max_length: 64,
+            invalid_ratio: 0.0,
+        }
+    }
+}
+
+/// A generated frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// The encoded frame.
+    pub bytes: Vec<u8>,
+    /// The value encoded, or `None` if the frame was corrupted.
+    pub value: Option<RespValue<'static>>,
+}
+
+/// Returns an endless, deterministic stream of encoded frames.
+///
+/// The same `seed` and `config` always produce the same frames, on every platform, so
+/// a stress scenario can be reproduced from the seed alone. Corrupted frames have
+/// their type marker replaced with a byte no frame starts with.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::generator::{GeneratorConfig, frames};
+/// use stream_resp::parser::Parser;
+///
+/// let mut parser = Parser::new(10, 1024);
+/// for frame in frames(42, GeneratorConfig::default()).take(100) {
+///     parser.read_buf(&frame.bytes);
+///     assert_eq!(parser.try_parse().unwrap(), frame.value);
+/// }
+/// ```
+pub fn frames(seed: u64, config: GeneratorConfig) -> Frames {
+    Frames {
+        rng: SplitMix64(seed),
+        config,
+    }
+}
+
+/// The iterator returned by `frames`.
+#[derive(Debug, Clone)]
+pub struct Frames {
+    rng: SplitMix64,
+    config: GeneratorConfig,
+}
+
+impl Iterator for Frames {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let value = self.value(self.config.max_depth);
+        let mut bytes = value.as_bytes();
+        if self.rng.chance(self.config.invalid_ratio) {
+            bytes[0] = INVALID_MARKERS[self.rng.below(INVALID_MARKERS.len())];
+            return Some(Frame { bytes, value: None });
+        }
+        Some(Frame {
+            bytes,
+            value: Some(value),
+        })
+    }
+}
+
+impl Frames {
+    fn value(&mut self, depth: usize) -> RespValue<'static> {
+        let kind = self.kind(depth);
+        match kind {
+            RespKind::SimpleString => RespValue::SimpleString(self.line()),
+            RespKind::Error => RespValue::Error(self.line()),
+            RespKind::Integer => RespValue::Integer(self.rng.next() as i64 >> self.rng.below(64)),
+            RespKind::BulkString => RespValue::BulkString(self.nullable(Self::line)),
+            RespKind::Null => RespValue::Null,
+            RespKind::Boolean => RespValue::Boolean(self.rng.chance(0.5)),
+            RespKind::Double => RespValue::Double((self.rng.next() as i32) as f64 / 1024.0),
+            RespKind::BigNumber => {
+                let digits = 1 + self.rng.below(40);
+                let mut n: String = (0..digits)
+                    .map(|_| (b'0' + self.rng.below(10) as u8) as char)
+                    .collect();
+                if self.rng.chance(0.5) {
+                    n.insert(0, '-');
+                }
+                RespValue::BigNumber(Cow::Owned(n))
+            }
+            RespKind::BulkError => RespValue::BulkError(self.nullable(Self::line)),
+            RespKind::VerbatimString => RespValue::VerbatimString(
+                self.nullable(|this| Box::new(Verbatim::txt(this.line()))),
+            ),
+            RespKind::Array => RespValue::Array(
+                self.nullable(|this| this.elements(depth))
+                    .map(Vec::into_boxed_slice),
+            ),
+            RespKind::Set => RespValue::Set(
+                self.nullable(|this| this.elements(depth))
+                    .map(Vec::into_boxed_slice),
+            ),
+            RespKind::Push => RespValue::Push(
+                self.nullable(|this| this.elements(depth))
+                    .map(Vec::into_boxed_slice),
+            ),
+            RespKind::Map => RespValue::Map(
+                self.nullable(|this| {
+                    let len = this.rng.below(this.config.max_elements + 1);
+                    (0..len)
+                        .map(|_| (this.value(depth - 1), this.value(depth - 1)))
+                        .collect::<Vec<_>>()
+                })
+                .map(Vec::into_boxed_slice),
+            ),
+            RespKind::Attribute => {
+                let len = self.rng.below(self.config.max_elements + 1);
+                let attributes = (0..len)
+                    .map(|_| (self.value(depth - 1), self.value(depth - 1)))
+                    .collect();
+                RespValue::Attribute(Box::new(Attributed {
+                    attributes,
+                    value: self.value(depth - 1),
+                }))
+            }
+        }
+    }
+
+    /// Picks a kind by weight, leaving aggregates out at the maximum depth.
+    fn kind(&mut self, depth: usize) -> RespKind {
+        let allowed = |kind: &RespKind| {
+            depth > 0
+                || !matches!(
+                    kind,
+                    RespKind::Array
+                        | RespKind::Map
+                        | RespKind::Set
+                        | RespKind::Push
+                        | RespKind::Attribute
+                )
+        };
+        let total: u64 = self
+            .config
+            .kinds
+            .iter()
+            .filter(|(kind, _)| allowed(kind))
+            .map(|(_, weight)| *weight as u64)
+            .sum();
+        if total == 0 {
+            return RespKind::Null;
+        }
+        let mut pick = self.rng.next() % total;
+        for (kind, weight) in self.config.kinds.iter().filter(|(kind, _)| allowed(kind)) {
+            if pick < *weight as u64 {
+                return *kind;
+            }
+            pick -= *weight as u64;
+        }
+        unreachable!()
+    }
+
+    fn elements(&mut self, depth: usize) -> Vec<RespValue<'static>> {
+        let len = self.rng.below(self.config.max_elements + 1);
+        (0..len).map(|_| self.value(depth - 1)).collect()
+    }
+
+    /// A payload without CR or LF, valid in line and bulk types alike.
+    fn line(&mut self) -> Cow<'static, str> {
+        let len = self.rng.below(self.config.max_length + 1);
+        let line = (0..len)
+            .map(|_| PAYLOAD_ALPHABET[self.rng.below(PAYLOAD_ALPHABET.len())] as char)
+            .collect();
+        Cow::Owned(line)
+    }
+
+    /// Returns `None` one time in sixteen.
+    fn nullable<T>(&mut self, generate: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        if self.rng.below(16) == 0 {
+            None
+        } else {
+            Some(generate(self))
+        }
+    }
+}
+
+/// The SplitMix64 generator: tiny, fast and fully specified, so sequences never
+/// change between versions.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        // 53 random bits, uniform in [0, 1)
+        ((self.next() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+//EOF
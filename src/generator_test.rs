@@ -0,0 +1,74 @@
+use crate::generator::{GeneratorConfig, frames};
+use crate::parser::Parser;
+use crate::resp::RespKind;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_are_deterministic() {
+        let a: Vec<_> = frames(7, GeneratorConfig::default()).take(200).collect();
+        let b: Vec<_> = frames(7, GeneratorConfig::default()).take(200).collect();
+        let c: Vec<_> = frames(8, GeneratorConfig::default()).take(200).collect();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_valid_frames_round_trip() {
+        let mut parser = Parser::new(10, 1024);
+        for frame in frames(1, GeneratorConfig::default()).take(2000) {
+            parser.read_buf(&frame.bytes);
+            assert_eq!(parser.try_parse().unwrap(), frame.value, "{:?}", frame);
+        }
+        assert!(parser.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_frames_are_rejected() {
+        let config = GeneratorConfig {
+            invalid_ratio: 0.5,
+            ..GeneratorConfig::default()
+        };
+        let mut invalid = 0;
+        for frame in frames(3, config).take(500) {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(&frame.bytes);
+            match frame.value {
+                Some(value) => assert_eq!(parser.try_parse(), Ok(Some(value))),
+                None => {
+                    assert!(parser.try_parse().is_err());
+                    invalid += 1;
+                }
+            }
+        }
+        assert!((150..350).contains(&invalid), "{} invalid frames", invalid);
+    }
+
+    #[test]
+    fn test_kind_mix_and_depth() {
+        let config = GeneratorConfig {
+            kinds: vec![(RespKind::Array, 1), (RespKind::Integer, 1)],
+            max_depth: 0,
+            ..GeneratorConfig::default()
+        };
+        for frame in frames(5, config).take(100) {
+            assert_eq!(frame.bytes[0], b':');
+        }
+    }
+
+    #[test]
+    fn test_attribute_frames_round_trip() {
+        let mut config = GeneratorConfig::default();
+        config.kinds.push((RespKind::Attribute, 4));
+        let mut parser = Parser::new(10, 1024);
+        let mut attributes = 0;
+        for frame in frames(7, config).take(500) {
+            attributes += usize::from(frame.bytes[0] == b'|');
+            parser.read_buf(&frame.bytes);
+            assert_eq!(parser.try_parse().unwrap(), frame.value, "{:?}", frame);
+        }
+        assert!(attributes > 0);
+    }
+}
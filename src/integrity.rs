@@ -0,0 +1,56 @@
+//! CRC32 framing for frames that leave the process.
+//!
+//! Frames written to capture files or handed to another process can be sealed with a
+//! trailing checksum. Unsealing verifies it, so corruption in the handoff path shows
+//! up as `ParseError::FrameIntegrityError` instead of a confusing parse error later.
+//!
+//! A sealed frame is the RESP frame followed by its CRC32 as 4 big-endian bytes.
+
+use crate::parser::{ParseError, ParserConfig, frame_end};
+
+/// Length of the checksum trailer.
+pub const CHECKSUM_LEN: usize = 4;
+
+/// Appends `frame` and its checksum to `out`.
+///
+/// `frame` must hold exactly one complete RESP frame.
+pub fn seal_into(frame: &[u8], out: &mut Vec<u8>) {
+    out.reserve(frame.len() + CHECKSUM_LEN);
+    out.extend_from_slice(frame);
+    out.extend_from_slice(&crc32fast::hash(frame).to_be_bytes());
+}
+
+/// Returns `frame` followed by its checksum.
+pub fn seal(frame: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    seal_into(frame, &mut out);
+    out
+}
+
+/// Verifies the sealed frame at the start of `data`.
+///
+/// Returns the frame without its checksum and the number of bytes the sealed frame
+/// takes, so consecutive sealed frames can be read from one buffer.
+///
+/// # Errors
+///
+/// Returns `ParseError::UnexpectedEof` or `ParseError::NotEnoughData` if the sealed
+/// frame is incomplete, `ParseError::FrameIntegrityError` if the checksum does not
+/// match, and the usual framing errors if the frame itself is malformed.
+pub fn unseal(data: &[u8]) -> Result<(&[u8], usize), ParseError> {
+    let end = frame_end(data, 0, &ParserConfig::default())?;
+    let trailer = data
+        .get(end..end + CHECKSUM_LEN)
+        .ok_or(ParseError::NotEnoughData)?;
+
+    let mut expected = [0u8; CHECKSUM_LEN];
+    expected.copy_from_slice(trailer);
+    let expected = u32::from_be_bytes(expected);
+    let actual = crc32fast::hash(&data[..end]);
+    if expected != actual {
+        return Err(ParseError::FrameIntegrityError { expected, actual });
+    }
+    Ok((&data[..end], end + CHECKSUM_LEN))
+}
+
+//EOF
@@ -0,0 +1,41 @@
+use crate::integrity::{CHECKSUM_LEN, seal, seal_into, unseal};
+use crate::parser::ParseError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_roundtrip() {
+        let mut capture = Vec::new();
+        seal_into(b"*1\r\n$3\r\nGET\r\n", &mut capture);
+        seal_into(b"+OK\r\n", &mut capture);
+
+        let (frame, used) = unseal(&capture).unwrap();
+        assert_eq!(frame, b"*1\r\n$3\r\nGET\r\n");
+        assert_eq!(used, frame.len() + CHECKSUM_LEN);
+
+        let (frame, rest) = unseal(&capture[used..]).unwrap();
+        assert_eq!(frame, b"+OK\r\n");
+        assert_eq!(used + rest, capture.len());
+    }
+
+    #[test]
+    fn test_unseal_detects_corruption() {
+        let mut sealed = seal(b"$5\r\nhello\r\n");
+        sealed[5] = b'j';
+        let error = unseal(&sealed).unwrap_err();
+        assert!(matches!(error, ParseError::FrameIntegrityError { .. }));
+        assert!(error.is_recoverable());
+    }
+
+    #[test]
+    fn test_unseal_incomplete() {
+        let sealed = seal(b":1\r\n");
+        assert_eq!(
+            unseal(&sealed[..sealed.len() - 1]),
+            Err(ParseError::NotEnoughData)
+        );
+        assert_eq!(unseal(&sealed[..2]), Err(ParseError::UnexpectedEof));
+    }
+}
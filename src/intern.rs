@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+/// Replies common enough to be interned by default: status replies and the type
+/// names returned by `TYPE`.
+pub const COMMON_REPLIES: &[&str] = &[
+    "OK", "PONG", "QUEUED", "string", "list", "set", "zset", "hash", "stream", "none",
+];
+
+/// Strings the parser returns as `Cow::Borrowed` instead of allocating a `String`
+/// for every occurrence; see `ParserConfig::intern`.
+///
+/// Simple and bulk strings are looked up by their exact contents. Only strings no
+/// longer than the longest entry are hashed, so a table of short replies costs
+/// nothing for other values.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::intern::InternTable;
+/// use stream_resp::parser::{Parser, ParserConfig};
+/// use stream_resp::resp::RespValue;
+/// use std::borrow::Cow;
+///
+/// let mut intern = InternTable::default();
+/// intern.insert("MOVED");
+/// let mut parser = Parser::with_config(ParserConfig {
+///     intern,
+///     ..ParserConfig::default()
+/// });
+/// parser.read_buf(b"+MOVED\r\n");
+/// match parser.try_parse() {
+///     Ok(Some(RespValue::SimpleString(s))) => assert!(matches!(s, Cow::Borrowed("MOVED"))),
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InternTable {
+    strings: HashSet<&'static str>,
+    max_len: usize,
+}
+
+impl InternTable {
+    /// Creates an empty table, which interns nothing.
+    pub fn new() -> Self {
+        InternTable {
+            strings: HashSet::new(),
+            max_len: 0,
+        }
+    }
+
+    /// Adds a string to intern. Strings only known at runtime can be registered with
+    /// `String::leak`.
+    pub fn insert(&mut self, s: &'static str) {
+        self.max_len = self.max_len.max(s.len());
+        self.strings.insert(s);
+    }
+
+    /// Returns the interned copy of `bytes`, if there is one.
+    #[inline(always)]
+    pub fn get(&self, bytes: &[u8]) -> Option<&'static str> {
+        if bytes.len() > self.max_len {
+            return None;
+        }
+        let s = std::str::from_utf8(bytes).ok()?;
+        self.strings.get(s).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// Interns `COMMON_REPLIES`.
+impl Default for InternTable {
+    fn default() -> Self {
+        COMMON_REPLIES.iter().copied().collect()
+    }
+}
+
+impl FromIterator<&'static str> for InternTable {
+    fn from_iter<I: IntoIterator<Item = &'static str>>(iter: I) -> Self {
+        let mut table = InternTable::new();
+        table.extend(iter);
+        table
+    }
+}
+
+impl Extend<&'static str> for InternTable {
+    fn extend<I: IntoIterator<Item = &'static str>>(&mut self, iter: I) {
+        for s in iter {
+            self.insert(s);
+        }
+    }
+}
+
+//EOF
@@ -0,0 +1,54 @@
+use crate::intern::{COMMON_REPLIES, InternTable};
+use crate::parser::{Parser, ParserConfig};
+use crate::resp::RespValue;
+use std::borrow::Cow;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_borrowed(value: &RespValue) -> bool {
+        match value {
+            RespValue::SimpleString(s) | RespValue::BulkString(Some(s)) => {
+                matches!(*s, Cow::Borrowed(_))
+            }
+            _ => panic!("not a string: {:?}", value),
+        }
+    }
+
+    #[test]
+    fn test_common_replies_are_interned() {
+        let table = InternTable::default();
+        assert_eq!(table.len(), COMMON_REPLIES.len());
+        assert_eq!(table.get(b"QUEUED"), Some("QUEUED"));
+        assert_eq!(table.get(b"queued"), None);
+        assert_eq!(table.get(b"a longer reply"), None);
+        assert!(InternTable::new().is_empty());
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"+OK\r\n$4\r\nPONG\r\n+OKAY\r\n$6\r\nstring\r\n");
+        for expected in [true, true, false, true] {
+            let value = parser.try_parse().unwrap().unwrap();
+            assert_eq!(is_borrowed(&value), expected, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn test_registered_strings() {
+        let mut intern = InternTable::new();
+        intern.extend(["user:1000", "ASK"]);
+        let mut parser = Parser::with_config(ParserConfig {
+            intern,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*3\r\n$9\r\nuser:1000\r\n+ASK\r\n+OK\r\n");
+        let value = parser.try_parse().unwrap().unwrap();
+        let RespValue::Array(Some(elements)) = &value else {
+            panic!("not an array: {:?}", value);
+        };
+        assert!(is_borrowed(&elements[0]));
+        assert!(is_borrowed(&elements[1]));
+        assert!(!is_borrowed(&elements[2]));
+        assert_eq!(elements[0], RespValue::BulkString(Some("user:1000".into())));
+    }
+}
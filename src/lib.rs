@@ -1,4 +1,5 @@
 #![warn(unused_assignments)]
+#![cfg_attr(feature = "forbid-unsafe", forbid(unsafe_code))]
 
 #[cfg(feature = "jemalloc")]
 use jemallocator::Jemalloc;
@@ -7,9 +8,61 @@ use jemallocator::Jemalloc;
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
 
+pub mod builder;
+#[cfg(test)]
+mod builder_test;
+#[cfg(feature = "codec")]
+pub mod codec;
+#[cfg(all(test, feature = "codec"))]
+mod codec_test;
+pub mod encoder;
+#[cfg(test)]
+mod encoder_test;
+pub mod error_text;
+#[cfg(test)]
+mod error_text_test;
+pub mod event;
+#[cfg(test)]
+mod event_test;
+pub mod generator;
+#[cfg(test)]
+mod generator_test;
+#[cfg(feature = "integrity")]
+pub mod integrity;
+#[cfg(all(test, feature = "integrity"))]
+mod integrity_test;
+pub mod intern;
+#[cfg(test)]
+mod intern_test;
 pub mod parser;
 #[cfg(test)]
 mod parser_test;
+pub mod pool;
+#[cfg(test)]
+mod pool_test;
 pub mod resp;
 #[cfg(test)]
 mod resp_test;
+pub mod scanner;
+#[cfg(test)]
+mod scanner_test;
+#[cfg(feature = "simd")]
+mod simd;
+#[cfg(all(test, feature = "simd"))]
+mod simd_test;
+pub mod simple;
+#[cfg(test)]
+mod simple_test;
+pub mod span;
+#[cfg(test)]
+mod span_test;
+pub mod stats;
+#[cfg(test)]
+mod stats_test;
+#[cfg(feature = "stream")]
+pub mod stream;
+#[cfg(all(test, feature = "stream"))]
+mod stream_test;
+pub mod visit;
+#[cfg(test)]
+mod visit_test;
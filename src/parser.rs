@@ -1,13 +1,38 @@
-use crate::resp::RespValue;
-use bytes::BytesMut; // Add Buf trait
-use memchr::memchr;
+use crate::error_text;
+use crate::intern::InternTable;
+use crate::resp::{Attributed, RespKind, RespValue, Verbatim};
+use crate::scanner::{DefaultScanner, Scanner, ScannerKind};
+use crate::stats::ParserStats;
+use bytes::{Buf, Bytes, BytesMut};
 use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fmt; // Import fmt
-use tracing::debug;
+use std::sync::Arc;
+
+// Logs through `tracing` with the `tracing` feature and compiles to nothing otherwise,
+// while still type-checking the arguments
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($arg)*);
+        #[cfg(not(feature = "tracing"))]
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
 
-const MAX_ITERATIONS: usize = 1024;
 const CRLF_LEN: usize = 2;
 const DEFAULT_BUFFER_INIT_SIZE: usize = 4096;
+const DEFAULT_MAX_DEPTH: usize = 128;
+const DEFAULT_PREALLOC_LIMIT: usize = 1024;
+// The most spare capacity zeroed and offered to a reader per read, so a large
+// buffer isn't zeroed in full for every small read
+const READ_WINDOW: usize = 64 * 1024;
+// Matches the default `proto-max-bulk-len` of redis-server
+const DEFAULT_MAX_LENGTH: usize = 512 * 1024 * 1024;
+// A verbatim string payload starts with a format like `txt:`
+const VERBATIM_PREFIX_LEN: usize = 4;
 
 type ParseResult = Result<Option<RespValue<'static>>, ParseError>;
 
@@ -20,6 +45,34 @@ pub enum ParseError {
     NotEnoughData,
     InvalidDepth,
     InvalidUtf8,
+    /// A sealed frame's checksum does not match its bytes; see the `integrity` module.
+    FrameIntegrityError {
+        expected: u32,
+        actual: u32,
+    },
+    /// `ParserConfig::max_iterations` ran out before the frame completed. The input is
+    /// fine: calling `try_parse` again resumes where parsing stopped.
+    IterationBudgetExceeded,
+    /// The values of a frame would take up more memory than
+    /// `ParserConfig::max_frame_memory` allows.
+    MemoryBudgetExceeded,
+}
+
+impl ParseError {
+    /// Returns `true` for errors caused by malformed input, as opposed to input that is
+    /// merely incomplete. Only these trigger error recovery.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            ParseError::InvalidFormat(_)
+                | ParseError::InvalidLength
+                | ParseError::Overflow
+                | ParseError::InvalidDepth
+                | ParseError::InvalidUtf8
+                | ParseError::FrameIntegrityError { .. }
+                | ParseError::MemoryBudgetExceeded
+        )
+    }
 }
 
 impl fmt::Display for ParseError {
@@ -32,6 +85,433 @@ impl fmt::Display for ParseError {
             ParseError::NotEnoughData => write!(f, "Not enough data in buffer"),
             ParseError::InvalidDepth => write!(f, "Maximum nesting depth exceeded"),
             ParseError::InvalidUtf8 => write!(f, "Invalid UTF-8 sequence"),
+            ParseError::FrameIntegrityError { expected, actual } => write!(
+                f,
+                "Frame checksum mismatch: expected {:08x}, got {:08x}",
+                expected, actual
+            ),
+            ParseError::IterationBudgetExceeded => write!(f, "Iteration budget exceeded"),
+            ParseError::MemoryBudgetExceeded => write!(f, "Memory budget exceeded"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// Attaches the context `parser` captured for its last error.
+    ///
+    /// Context is only captured when `ParserConfig::detailed_errors` is enabled, since
+    /// it copies part of the buffer on every error. The result implements
+    /// `std::error::Error` with this error as its source, so it converts into
+    /// `anyhow::Error` or `eyre::Report` with a readable chain.
+    pub fn detailed(self, parser: &Parser) -> DetailedError {
+        DetailedError {
+            error: self,
+            context: parser.last_error_context.clone(),
+        }
+    }
+}
+
+/// Maximum number of buffered bytes copied into an `ErrorContext`.
+const ERROR_SNIPPET_LEN: usize = 32;
+
+/// Where in the input a parse error happened.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ErrorContext {
+    /// Offset of the failing value from the start of its frame.
+    pub offset: usize,
+    /// Up to 32 bytes of input starting at the failing value.
+    pub snippet: Vec<u8>,
+    /// What the parser was reading when it failed.
+    pub state: &'static str,
+    /// Number of aggregates enclosing the failing value.
+    pub depth: usize,
+}
+
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "while {} at offset {} (depth {}), near \"{}\"",
+            self.state,
+            self.offset,
+            self.depth,
+            self.snippet.escape_ascii()
+        )
+    }
+}
+
+/// A `ParseError` with optional context, produced by `ParseError::detailed`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DetailedError {
+    pub error: ParseError,
+    pub context: Option<ErrorContext>,
+}
+
+impl fmt::Display for DetailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.context {
+            Some(context) => write!(f, "Failed to parse RESP frame {}", context),
+            None => write!(f, "Failed to parse RESP frame"),
+        }
+    }
+}
+
+impl std::error::Error for DetailedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A well-formed but suspicious construct found in a parsed frame.
+///
+/// Rejected with `ParseError::InvalidFormat` when `ParserConfig::strict` is set.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParseWarning {
+    /// A map key is or contains a NaN double, which breaks hashing.
+    NanMapKey,
+    /// A set member is or contains a NaN double, which breaks hashing.
+    NanSetMember,
+    /// A map has the same key more than once; see
+    /// `ParserConfig::detect_duplicate_keys`.
+    DuplicateMapKey,
+    /// A set has the same member more than once; see
+    /// `ParserConfig::detect_duplicate_members`.
+    DuplicateSetMember,
+}
+
+impl ParseWarning {
+    fn message(self) -> &'static str {
+        match self {
+            ParseWarning::NanMapKey => "NaN map key",
+            ParseWarning::NanSetMember => "NaN set member",
+            ParseWarning::DuplicateMapKey => "Duplicate map key",
+            ParseWarning::DuplicateSetMember => "Duplicate set member",
+        }
+    }
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+/// Returns `true` if `value` is or contains a NaN double.
+fn contains_nan(value: &RespValue) -> bool {
+    match value {
+        RespValue::Double(d) => d.is_nan(),
+        RespValue::Array(Some(items))
+        | RespValue::Set(Some(items))
+        | RespValue::Push(Some(items)) => items.iter().any(contains_nan),
+        RespValue::Map(Some(pairs)) => pairs
+            .iter()
+            .any(|(k, v)| contains_nan(k) || contains_nan(v)),
+        _ => false,
+    }
+}
+
+/// Returns `true` if two of `values` are equal.
+fn has_duplicates<'a>(values: impl Iterator<Item = &'a RespValue<'static>>) -> bool {
+    let mut seen = HashSet::new();
+    values.into_iter().any(|value| !seen.insert(value))
+}
+
+/// Returns the heap size of the payload of a string value, 0 for other values.
+fn payload_size(value: &RespValue) -> usize {
+    match value {
+        RespValue::SimpleString(s)
+        | RespValue::Error(s)
+        | RespValue::BigNumber(s)
+        | RespValue::BulkString(Some(s))
+        | RespValue::BulkError(Some(s)) => s.len(),
+        RespValue::VerbatimString(Some(v)) => v.text().len(),
+        RespValue::BulkBytes(b) => b.len(),
+        _ => 0,
+    }
+}
+
+/// Returns `true` if a verbatim string payload starts with a three character format
+/// such as `txt` followed by `:`.
+#[inline(always)]
+pub(crate) fn is_verbatim_prefix(payload: &[u8]) -> bool {
+    payload.len() >= VERBATIM_PREFIX_LEN
+        && payload[..VERBATIM_PREFIX_LEN - 1]
+            .iter()
+            .all(u8::is_ascii_alphanumeric)
+        && payload[VERBATIM_PREFIX_LEN - 1] == b':'
+}
+
+/// Converts a bulk payload to `&str`.
+///
+/// ASCII payloads skip UTF-8 validation through `from_utf8_unchecked`; the
+/// `forbid-unsafe` feature replaces this with a fully checked conversion.
+#[inline(always)]
+fn bulk_str(bytes: &[u8]) -> Result<&str, std::str::Utf8Error> {
+    #[cfg(not(feature = "forbid-unsafe"))]
+    {
+        if bytes.is_ascii() {
+            // SAFETY: ASCII is always valid UTF-8
+            return Ok(unsafe { std::str::from_utf8_unchecked(bytes) });
+        }
+    }
+    std::str::from_utf8(bytes)
+}
+
+/// Finds the position of the next CRLF in `buf` at or after `start`.
+#[inline(always)]
+pub(crate) fn find_crlf(buf: &[u8], start: usize) -> Option<usize> {
+    DefaultScanner::find_crlf(buf, start)
+}
+
+/// Finds the end of the line containing `start`, returning the end of its content and
+/// the position after its terminator. With `lenient`, a bare `\n` ends a line as well.
+#[inline(always)]
+pub(crate) fn find_line(
+    buf: &[u8],
+    start: usize,
+    scanner: ScannerKind,
+    lenient: bool,
+) -> Option<(usize, usize)> {
+    if lenient {
+        let lf = start + memchr::memchr(b'\n', buf.get(start..)?)?;
+        // Type markers are never \r, so the byte before `start` can be checked too
+        return Some(match lf.checked_sub(1).map(|cr| buf[cr]) {
+            Some(b'\r') => (lf - 1, lf + 1),
+            _ => (lf, lf + 1),
+        });
+    }
+    scanner
+        .find_crlf(buf, start)
+        .map(|end| (end, end + CRLF_LEN))
+}
+
+/// Starts reading the value whose type marker is at the given buffer position.
+type MarkerHandler<const MAX_DEPTH: usize> = fn(&mut Parser<MAX_DEPTH>, usize) -> ParseState;
+
+type MarkerTable<const MAX_DEPTH: usize> = [Option<MarkerHandler<MAX_DEPTH>>; 256];
+
+/// Returns `true` if `byte` starts a RESP value.
+#[inline(always)]
+pub(crate) fn is_type_marker(byte: u8) -> bool {
+    Parser::<0>::MARKER_HANDLERS[byte as usize].is_some()
+}
+
+/// Parses a decimal line such as a length header (`-1`, `42`).
+#[inline(always)]
+pub(crate) fn parse_decimal(line: &[u8]) -> Result<i64, ParseError> {
+    let digits = line.strip_prefix(b"-").unwrap_or(line);
+    if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+        return Err(ParseError::InvalidFormat(
+            "Invalid character in length".into(),
+        ));
+    }
+    atoi::atoi::<i64>(line).ok_or(ParseError::Overflow)
+}
+
+/// Reads the streamed string chunk at `pos`, `;<len>\r\n<payload>\r\n`.
+///
+/// Returns the position and length of the payload and the position after the chunk.
+/// A zero length is the chunk closing the string.
+fn streamed_chunk(
+    buf: &[u8],
+    pos: usize,
+    config: &ParserConfig,
+) -> Result<(usize, usize, usize), ParseError> {
+    match buf.get(pos) {
+        Some(b';') => {}
+        Some(_) => {
+            return Err(ParseError::InvalidFormat(
+                "Expected a streamed string chunk".into(),
+            ));
+        }
+        None => return Err(ParseError::UnexpectedEof),
+    }
+    let (line_end, start) = find_line(buf, pos + 1, config.scanner, config.lenient_line_endings)
+        .ok_or(ParseError::UnexpectedEof)?;
+    let len = parse_decimal(&buf[pos + 1..line_end])?;
+    if len < 0 {
+        return Err(ParseError::InvalidFormat("Invalid chunk length".into()));
+    }
+    let len = len as usize;
+    if len == 0 {
+        return Ok((start, 0, start));
+    }
+    if len >= config.max_length {
+        return Err(ParseError::InvalidLength);
+    }
+    if buf.len() < start + len + CRLF_LEN {
+        return Err(ParseError::NotEnoughData);
+    }
+    if buf[start + len..start + len + CRLF_LEN] != *b"\r\n" {
+        return Err(ParseError::InvalidFormat("Missing CRLF terminator".into()));
+    }
+    Ok((start, len, start + len + CRLF_LEN))
+}
+
+/// The error `ParserConfig::requests_only` rejects a value of the wrong type with.
+fn unexpected_marker(expected: u8, marker: u8) -> ParseError {
+    ParseError::InvalidFormat(
+        format!(
+            "Protocol error: expected '{}', got '{}'",
+            expected as char,
+            marker.escape_ascii()
+        )
+        .into(),
+    )
+}
+
+/// Finds the end position of the frame starting at `start` without decoding it.
+pub(crate) fn frame_end(
+    buf: &[u8],
+    start: usize,
+    config: &ParserConfig,
+) -> Result<usize, ParseError> {
+    let mut pos = start;
+    // Remaining element counts of the aggregates currently open, `STREAMED` for
+    // streamed aggregates
+    let mut pending: Vec<usize> = Vec::new();
+    const STREAMED: usize = usize::MAX;
+
+    loop {
+        let marker = *buf.get(pos).ok_or(ParseError::UnexpectedEof)?;
+        if config.requests_only {
+            let expected = if pending.is_empty() { b'*' } else { b'$' };
+            if marker != expected {
+                if expected != b'*' || !config.inline_commands {
+                    return Err(unexpected_marker(expected, marker));
+                }
+                let Some((line_end, next_pos)) =
+                    find_line(buf, pos, config.scanner, config.lenient_line_endings)
+                else {
+                    if buf.len() - pos > config.max_length {
+                        return Err(ParseError::InvalidLength);
+                    }
+                    return Err(ParseError::UnexpectedEof);
+                };
+                let line = bulk_str(&buf[pos..line_end]).map_err(|_| ParseError::InvalidUtf8)?;
+                if line.split_ascii_whitespace().next().is_some() {
+                    return Ok(next_pos);
+                }
+                // Blank lines are skipped, so they belong to the frame after them
+                pos = next_pos;
+                continue;
+            }
+        }
+        let (line_end, next_pos) =
+            find_line(buf, pos + 1, config.scanner, config.lenient_line_endings)
+                .ok_or(ParseError::UnexpectedEof)?;
+        let line = &buf[(pos + 1)..line_end];
+        pos = next_pos;
+
+        if config.requests_only {
+            if line == b"?" {
+                return Err(ParseError::InvalidFormat(
+                    "Invalid character in length".into(),
+                ));
+            }
+            match marker {
+                b'$' if parse_decimal(line)? < 0 => {
+                    return Err(ParseError::InvalidFormat(
+                        error_text::INVALID_BULK_LENGTH.into(),
+                    ));
+                }
+                // Commands without arguments are skipped, so they belong to the
+                // frame after them
+                b'*' if parse_decimal(line)? <= 0 => continue,
+                _ => {}
+            }
+        }
+
+        if config.protocol == Protocol::Resp2
+            && (!matches!(marker, b'+' | b'-' | b':' | b'$' | b'*')
+                || (matches!(marker, b'$' | b'*') && line == b"?"))
+        {
+            return Err(ParseError::InvalidFormat("Invalid type marker".into()));
+        }
+
+        let children = match marker {
+            b'+' | b'-' | b':' | b',' | b'(' => 0,
+            b'=' if config.legacy_verbatim_strings => 0,
+            b'!' if config.legacy_bulk_errors => 0,
+            b'_' if line.is_empty() => 0,
+            b'#' if line == b"t" || line == b"f" => 0,
+            b'_' | b'#' => {
+                return Err(ParseError::InvalidFormat("Invalid line value".into()));
+            }
+            b'.' if line.is_empty() && pending.last() == Some(&STREAMED) => {
+                pending.pop();
+                0
+            }
+            b'*' | b'%' | b'~' if line == b"?" => STREAMED,
+            b'$' if line == b"?" => {
+                loop {
+                    let (_, len, next) = streamed_chunk(buf, pos, config)?;
+                    pos = next;
+                    if len == 0 {
+                        break;
+                    }
+                }
+                0
+            }
+            b'$' | b'=' | b'!' => {
+                let len = parse_decimal(line)?;
+                if len >= 0 {
+                    let len = len as usize;
+                    if marker == b'=' && len < VERBATIM_PREFIX_LEN {
+                        return Err(ParseError::InvalidFormat(
+                            "Verbatim string without format prefix".into(),
+                        ));
+                    }
+                    if len >= config.max_length {
+                        return Err(ParseError::InvalidLength);
+                    }
+                    if buf.len() < pos + len + CRLF_LEN {
+                        return Err(ParseError::NotEnoughData);
+                    }
+                    if buf[pos + len..pos + len + CRLF_LEN] != *b"\r\n" {
+                        return Err(ParseError::InvalidFormat("Missing CRLF terminator".into()));
+                    }
+                    pos += len + CRLF_LEN;
+                }
+                0
+            }
+            b'|' => {
+                let len = parse_decimal(line)?;
+                if len < 0 {
+                    return Err(ParseError::InvalidFormat("Invalid attribute length".into()));
+                }
+                len as usize * 2 + 1
+            }
+            b'*' | b'~' | b'>' | b'%' => {
+                let len = parse_decimal(line)?.max(0) as usize;
+                if marker == b'%' { len * 2 } else { len }
+            }
+            _ => return Err(ParseError::InvalidFormat("Invalid type marker".into())),
+        };
+
+        if children > 0 {
+            if pending.len() >= config.max_depth {
+                return Err(ParseError::InvalidDepth);
+            }
+            pending.push(children);
+            continue;
+        }
+
+        // A value finished: it may complete one or more enclosing aggregates
+        loop {
+            match pending.last_mut() {
+                None => return Ok(pos),
+                Some(&mut STREAMED) => break,
+                Some(remaining) => {
+                    *remaining -= 1;
+                    if *remaining > 0 {
+                        break;
+                    }
+                    pending.pop();
+                }
+            }
         }
     }
 }
@@ -51,15 +531,48 @@ pub enum ParseState {
     ReadingBulkString {
         start_pos: usize,
         remaining: usize,
+        type_char: u8, // `$`, or `=` and `!` for verbatim strings and bulk errors
+    },
+    // A body handed to `Parser::stream_bulks`, dropped from the buffer as it arrives
+    StreamingBulkString {
+        pos: usize,
+        len: usize,
+        remaining: usize,
+    },
+    // A `$?` string, whose chunks stay buffered until the closing `;0` chunk
+    ReadingStreamedString {
+        start: usize,
+        pos: usize,
+        len: usize,
     },
+    // Line states remember how far the CRLF scan got, so fragmented lines are scanned once
     ReadingSimpleString {
         pos: usize,
+        scanned: usize,
     },
     ReadingError {
         pos: usize,
+        scanned: usize,
     },
     ReadingInteger {
         pos: usize,
+        scanned: usize,
+    },
+    ReadingDouble {
+        pos: usize,
+        scanned: usize,
+    },
+    ReadingBigNumber {
+        pos: usize,
+        scanned: usize,
+    },
+    ReadingBulkError {
+        pos: usize,
+        scanned: usize,
+    },
+    ReadingVerbatimString {
+        pos: usize,
+        scanned: usize,
     },
     // Nested structures whitch use stack to store and parse
     ReadingArray {
@@ -69,20 +582,419 @@ pub enum ParseState {
         elements: Vec<RespValue<'static>>,
         original_type_char: u8, // Added to distinguish between Array (*) and Map (%)
     },
+    // A `*?`, `%?` or `~?` aggregate, which ends at a `.` marker instead of after a count
+    ReadingStreamedAggregate {
+        pos: usize,
+        elements: Vec<RespValue<'static>>,
+        original_type_char: u8,
+    },
     // Outcomes
     Error(ParseError),
     Complete(Option<(RespValue<'static>, usize)>),
 }
 
+impl ParseState {
+    /// Returns the buffer position of the value being read and a description of it.
+    fn describe(&self) -> (usize, &'static str) {
+        match self {
+            ParseState::Index { pos } => (*pos, "reading a type marker"),
+            ParseState::ReadingLength { pos, .. } => (*pos, "reading a length"),
+            ParseState::ReadingBulkString { start_pos, .. } => {
+                (*start_pos, "reading a bulk string")
+            }
+            ParseState::StreamingBulkString { pos, .. } => (*pos, "streaming a bulk string"),
+            ParseState::ReadingStreamedString { pos, .. } => (*pos, "reading a streamed string"),
+            ParseState::ReadingStreamedAggregate { pos, .. } => {
+                (*pos, "reading a streamed aggregate")
+            }
+            ParseState::ReadingSimpleString { pos, .. } => (*pos, "reading a simple string"),
+            ParseState::ReadingError { pos, .. } => (*pos, "reading an error"),
+            ParseState::ReadingInteger { pos, .. } => (*pos, "reading an integer"),
+            ParseState::ReadingDouble { pos, .. } => (*pos, "reading a double"),
+            ParseState::ReadingBigNumber { pos, .. } => (*pos, "reading a big number"),
+            ParseState::ReadingBulkError { pos, .. } => (*pos, "reading a bulk error"),
+            ParseState::ReadingVerbatimString { pos, .. } => (*pos, "reading a verbatim string"),
+            ParseState::ReadingArray { pos, .. } => (*pos, "reading an aggregate"),
+            ParseState::Error(_) | ParseState::Complete(_) => (0, "completing a value"),
+        }
+    }
+}
+
+/// What is known about the next buffered frame, as reported by `Parser::peek`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NextFrame {
+    /// A complete, well-framed frame of `len` bytes is buffered.
+    Complete { len: usize },
+    /// The next frame is not fully buffered yet.
+    Incomplete,
+    /// The next frame is malformed.
+    Invalid(ParseError),
+}
+
+/// Parser options.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::parser::{Parser, ParserConfig};
+///
+/// let parser = Parser::with_config(ParserConfig {
+///     recover_errors: true,
+///     ..ParserConfig::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserConfig {
+    /// The maximum depth of nested aggregates.
+    ///
+    /// The aggregate stack is allocated once with this capacity. A frame nesting deeper
+    /// fails with `ParseError::InvalidDepth` and is dropped, so parsing resumes at the
+    /// next frame.
+    pub max_depth: usize,
+    /// The maximum length of bulk strings.
+    pub max_length: usize,
+    /// Skip malformed frames instead of failing; see `Parser::skipped_bytes`.
+    pub recover_errors: bool,
+    /// Reject frames that are well-formed but unsafe to consume, such as NaN map keys.
+    ///
+    /// When disabled such frames are returned and reported through
+    /// `Parser::take_warnings` instead.
+    pub strict: bool,
+    /// Capture an `ErrorContext` for every malformed-input error, retrieved through
+    /// `ParseError::detailed`. Costs an allocation per error.
+    pub detailed_errors: bool,
+    /// The implementation of the line and digit scanning hot paths.
+    pub scanner: ScannerKind,
+    /// After each frame returned by `try_parse`, validate the framing of the next
+    /// buffered frame right away; see `Parser::peek`.
+    pub speculate: bool,
+    /// The most elements reserved for an aggregate from its header alone.
+    ///
+    /// Larger aggregates start at this capacity and grow geometrically, never past the
+    /// declared length, as their elements arrive. A hostile header thus cannot make the
+    /// parser allocate more than the input it actually sends.
+    pub prealloc_limit: usize,
+    /// The most state machine steps a single `try_parse` call may take, or `None` for
+    /// no limit.
+    ///
+    /// Every step consumes input or completes a value, so parsing always terminates
+    /// within a number of steps proportional to the buffered input. A budget bounds
+    /// the latency of one call instead: once it runs out `try_parse` returns
+    /// `ParseError::IterationBudgetExceeded`, and the next call picks up from there.
+    pub max_iterations: Option<usize>,
+    /// Accept a bare `\n` wherever a line ends, as hand-written input from telnet or
+    /// test scripts often has. Bulk string payloads must still end with `\r\n`.
+    pub lenient_line_endings: bool,
+    /// The most heap memory the values of a single frame may take up, or `None` for
+    /// no limit.
+    ///
+    /// The size is estimated as it is built: string payloads count their length and
+    /// aggregates the capacity of their element vectors. Depth and length limits alone
+    /// do not stop a wide fan-out of small aggregates, so a frame going over this
+    /// fails with `ParseError::MemoryBudgetExceeded` instead. The frame is dropped, so
+    /// parsing resumes at the next frame.
+    pub max_frame_memory: Option<usize>,
+    /// Read verbatim strings as a single line after `=`, as older versions of this
+    /// crate wrote them, instead of the length-prefixed `=<len>\r\n<fmt>:<text>\r\n`
+    /// form of the RESP3 spec.
+    pub legacy_verbatim_strings: bool,
+    /// Read bulk errors as a single line after `!`, as older versions of this crate
+    /// wrote them, instead of the length-prefixed `!<len>\r\n<error>\r\n` form sent
+    /// by Redis.
+    pub legacy_bulk_errors: bool,
+    /// What to do with big numbers not in canonical form, with leading zeros or `-0`.
+    pub big_numbers: BigNumberPolicy,
+    /// Check maps for keys sent more than once, raising
+    /// `ParseWarning::DuplicateMapKey`, or rejecting the frame in strict mode.
+    ///
+    /// Keys are compared with `RespValue`'s `Eq`, so `+a` and `$1\r\na` are different
+    /// keys. Costs a hash set insertion per key.
+    pub detect_duplicate_keys: bool,
+    /// Check sets for members sent more than once, raising
+    /// `ParseWarning::DuplicateSetMember`, or rejecting the frame in strict mode.
+    ///
+    /// Members are compared like map keys with `detect_duplicate_keys`.
+    pub detect_duplicate_members: bool,
+    /// The protocol version to accept; see `Parser::set_protocol`.
+    ///
+    /// RESP2 rejects the type markers and streamed values RESP3 added with
+    /// `ParseError::InvalidFormat`.
+    pub protocol: Protocol,
+    /// Accept only client requests, top-level arrays of bulk strings such as
+    /// `*2\r\n$3\r\nGET\r\n$1\r\nk\r\n`, as a server reading commands would.
+    ///
+    /// Any other type marker fails with a `ParseError::InvalidFormat` naming the
+    /// expected one, `Protocol error: expected '$', got ':'`, so command handlers
+    /// need not validate the shape of their arguments.
+    pub requests_only: bool,
+    /// With `requests_only`, also accept inline commands, lines of space separated
+    /// arguments such as `PING\r\n`, read as an array of bulk strings.
+    ///
+    /// Blank lines are skipped. Quoting is not supported.
+    pub inline_commands: bool,
+    /// Return bulk string payloads as `RespValue::BulkBytes`, sharing the parser's
+    /// buffer instead of being copied into a `String`.
+    ///
+    /// Payloads are not checked for UTF-8, so proxies can forward binary values
+    /// unchanged. The buffer's allocation is only reused once every payload handed
+    /// out from it has been dropped.
+    pub shared_bulks: bool,
+    /// Simple and bulk strings returned as `Cow::Borrowed` rather than allocated;
+    /// `intern::COMMON_REPLIES` such as `OK` and `QUEUED` by default.
+    pub intern: InternTable,
+}
+
+/// A RESP protocol version; see `ParserConfig::protocol`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Protocol {
+    Resp2,
+    #[default]
+    Resp3,
+}
+
+/// Handling of big numbers not in canonical form; see `ParserConfig::big_numbers`.
+///
+/// A leading `+` is valid RESP3 and accepted in every mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BigNumberPolicy {
+    /// Return the number as sent, `(+007` as `+007`.
+    #[default]
+    Keep,
+    /// Rewrite the number in canonical form, `(+007` as `7` and `(-0` as `0`.
+    Normalize,
+    /// Reject leading zeros and `-0` with `ParseError::InvalidFormat`.
+    Reject,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        ParserConfig {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_length: DEFAULT_MAX_LENGTH,
+            recover_errors: false,
+            strict: false,
+            detailed_errors: false,
+            scanner: ScannerKind::default(),
+            speculate: false,
+            prealloc_limit: DEFAULT_PREALLOC_LIMIT,
+            max_iterations: None,
+            lenient_line_endings: false,
+            max_frame_memory: None,
+            legacy_verbatim_strings: false,
+            legacy_bulk_errors: false,
+            big_numbers: BigNumberPolicy::Keep,
+            detect_duplicate_keys: false,
+            detect_duplicate_members: false,
+            protocol: Protocol::Resp3,
+            requests_only: false,
+            inline_commands: false,
+            shared_bulks: false,
+            intern: InternTable::default(),
+        }
+    }
+}
+
+/// The remainder of a frame rejected mid-way, dropped before the next frame is parsed.
+#[derive(Debug, Clone, Default)]
+struct Discard {
+    values: usize,
+    payload: usize,
+    // Values owed outside each open streamed aggregate, outermost first. Those end at
+    // a `.` marker rather than after a count.
+    streamed: Vec<usize>,
+}
+
+/// A callback receiving decoded frames of one `RespKind`; see `Parser::on_kind`.
+pub type DecodeHook = Arc<dyn Fn(&RespValue<'static>) + Send + Sync>;
+
+/// A callback receiving every decoded frame with its length; see `Parser::on_frame`.
+pub type FrameHook = Arc<dyn Fn(&RespValue<'static>, usize) + Send + Sync>;
+
+/// A callback receiving large bulk string bodies piece by piece; see
+/// `Parser::stream_bulks`.
+pub type BulkChunkHook = Arc<dyn Fn(&BulkChunk<'_>) + Send + Sync>;
+
+/// A piece of a streamed bulk string body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BulkChunk<'a> {
+    /// The bytes of this piece.
+    pub data: &'a [u8],
+    /// Where `data` starts within the body.
+    pub offset: usize,
+    /// The length of the whole body.
+    pub len: usize,
+}
+
+impl BulkChunk<'_> {
+    /// Returns `true` if this piece ends the body.
+    pub fn is_last(&self) -> bool {
+        self.offset + self.data.len() == self.len
+    }
+}
+
+#[derive(Clone, Default)]
+struct DecodeHooks {
+    by_kind: Vec<(RespKind, DecodeHook)>,
+    on_frame: Vec<FrameHook>,
+    // Bodies longer than the threshold are streamed to the hook
+    bulk_chunks: Option<(usize, BulkChunkHook)>,
+    #[cfg(feature = "metrics")]
+    frame_sizes: Option<::metrics::Histogram>,
+}
+
+impl DecodeHooks {
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        self.by_kind.is_empty() && self.on_frame.is_empty()
+    }
+
+    fn run(&self, value: &RespValue<'static>, frame_len: usize) {
+        for hook in &self.on_frame {
+            hook(value, frame_len);
+        }
+        if !self.by_kind.is_empty() {
+            let kind = value.kind();
+            for (_, hook) in self.by_kind.iter().filter(|(k, _)| *k == kind) {
+                hook(value);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DecodeHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecodeHooks")
+            .field(
+                "by_kind",
+                &self
+                    .by_kind
+                    .iter()
+                    .map(|(kind, _)| kind)
+                    .collect::<Vec<_>>(),
+            )
+            .field("on_frame", &self.on_frame.len())
+            .field(
+                "bulk_chunks",
+                &self.bulk_chunks.as_ref().map(|(threshold, _)| threshold),
+            )
+            .finish()
+    }
+}
+
+/// The open aggregates of a frame, innermost last.
+///
+/// Kept inline when `MAX_DEPTH` is non-zero, on the heap otherwise.
 #[derive(Debug, Clone)]
-pub struct Parser {
+struct NestedStack<const MAX_DEPTH: usize> {
+    inline: [Option<ParseState>; MAX_DEPTH],
+    len: usize,
+    heap: Vec<ParseState>,
+}
+
+impl<const MAX_DEPTH: usize> NestedStack<MAX_DEPTH> {
+    fn with_capacity(capacity: usize) -> Self {
+        NestedStack {
+            inline: [const { None }; MAX_DEPTH],
+            len: 0,
+            heap: if MAX_DEPTH == 0 {
+                Vec::with_capacity(capacity)
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    #[inline(always)]
+    fn len(&self) -> usize {
+        if MAX_DEPTH == 0 {
+            self.heap.len()
+        } else {
+            self.len
+        }
+    }
+
+    #[inline(always)]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Panics if an inline stack is full; `ParserConfig::max_depth` is clamped so
+    /// that the parser never pushes past it.
+    #[inline(always)]
+    fn push(&mut self, state: ParseState) {
+        if MAX_DEPTH == 0 {
+            self.heap.push(state);
+        } else {
+            self.inline[self.len] = Some(state);
+            self.len += 1;
+        }
+    }
+
+    #[inline(always)]
+    fn pop(&mut self) -> Option<ParseState> {
+        if MAX_DEPTH == 0 {
+            self.heap.pop()
+        } else {
+            self.len = self.len.checked_sub(1)?;
+            self.inline[self.len].take()
+        }
+    }
+
+    #[inline(always)]
+    fn last_mut(&mut self) -> Option<&mut ParseState> {
+        if MAX_DEPTH == 0 {
+            self.heap.last_mut()
+        } else {
+            self.inline[..self.len].last_mut()?.as_mut()
+        }
+    }
+
+    fn clear(&mut self) {
+        self.heap.clear();
+        self.inline[..self.len].fill(None);
+        self.len = 0;
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &ParseState> {
+        // Only one of the two is ever in use
+        self.heap
+            .iter()
+            .chain(self.inline[..self.len].iter().flatten())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Parser<const MAX_DEPTH: usize = 0> {
     pub buffer: BytesMut,
     state: ParseState,
-    max_length: usize,
-    max_depth: usize,
-    nested_stack: Vec<ParseState>,
+    config: ParserConfig,
+    nested_stack: NestedStack<MAX_DEPTH>,
+    // Set after a recoverable error until the next frame boundary is found
+    resyncing: bool,
+    // Bytes of the current frame already dropped from the buffer
+    frame_offset: usize,
+    // Estimated heap size of the values of the current frame, see `charge`
+    frame_memory: usize,
+    // End position and remainder of a frame rejected mid-way
+    rejected_frame: Option<(usize, Discard)>,
+    // Values and payload bytes of a rejected frame still to be dropped
+    discard: Option<Discard>,
+    warnings: Vec<ParseWarning>,
+    last_error_context: Option<ErrorContext>,
+    // Speculative validation of the next frame, see `peek`
+    next_frame: Option<NextFrame>,
+    stats: ParserStats,
+    hooks: DecodeHooks,
+    // Set by `peek_frame`, which must not stream bulk string bodies away
+    peeking: bool,
+    // The type markers of `config.protocol`
+    markers: &'static MarkerTable<MAX_DEPTH>,
 }
 
+/// A `Parser` whose stack of open aggregates lives inline, holding at most
+/// `MAX_DEPTH` levels; see `Parser::with_fixed_depth`.
+pub type FixedDepthParser<const MAX_DEPTH: usize> = Parser<MAX_DEPTH>;
+
 /// A parser for RESP (REdis Serialization Protocol) messages.
 ///
 /// # Example
@@ -102,25 +1014,82 @@ pub struct Parser {
 /// - `new(max_depth: usize, max_length: usize) -> Self`
 ///   Creates a new `Parser` instance with the specified maximum depth and length.
 ///
+/// - `with_config(config: ParserConfig) -> Self`
+///   Creates a new `Parser` instance from a `ParserConfig`.
+///
+/// - `with_fixed_depth(config: ParserConfig) -> Parser<MAX_DEPTH>`
+///   Creates a parser whose aggregate stack is an inline array of `MAX_DEPTH` levels.
+///
+/// - `from_buffer(buffer: BytesMut) -> Self` / `into_buffer(self) -> BytesMut`
+///   Transfers an existing read buffer into or out of the parser without copying.
+///
 /// - `read_buf(&mut self, buf: &[u8])`
 ///   Reads a buffer of bytes into the parser's internal buffer.
 ///
+/// - `read_from<R: Read>(&mut self, reader: &mut R) -> io::Result<usize>`
+///   Reads once from `reader` straight into the internal buffer.
+///
+/// - `read_from_async<R: AsyncRead + Unpin>(&mut self, reader: &mut R) -> io::Result<usize>`
+///   Same as `read_from` for tokio readers; requires the `tokio` feature.
+///
+/// - `feed_bytes(&mut self, bytes: Bytes)` / `feed(&mut self, buf: impl Buf)`
+///   Appends owned input, taking over its allocation when nothing is buffered.
+///
 /// - `get_buffer(&self) -> &BytesMut`
 ///   Returns a reference to the parser's internal buffer.
 ///
-/// - `clear_buffer(&mut self)`
-///   Clears the parser's internal buffer and resets the state.
+/// - `clear_buffer(&mut self, pos: usize)`
+///   Drops the consumed bytes before `pos` and resets the state.
+///
+/// - `reset(&mut self)`
+///   Returns the parser to a fresh state, keeping its allocations.
 ///
 /// - `try_parse(&mut self) -> ParseResult`
 ///   Attempts to parse the data in the buffer and returns a `ParseResult`.
 ///
+/// - `try_parse_with_len(&mut self) -> Result<Option<(RespValue, usize)>, ParseError>`
+///   Same as `try_parse`, also returning the number of bytes the frame took up.
+///
+/// - `parse_in_place(&mut self, buf: &mut BytesMut) -> ParseResult`
+///   Same as `try_parse`, parsing out of and advancing a caller-owned buffer.
+///
+/// - `parse_with(&mut self, handler: impl FnMut(RespValue)) -> Result<usize, ParseError>`
+///   Hands every complete frame in the buffer to `handler`.
+///
+/// - `peek_frame(&mut self) -> ParseResult`
+///   Parses the next complete frame without consuming it.
+///
+/// - `peek(&self) -> Option<&NextFrame>`
+///   Returns the speculative validation result of the next buffered frame.
+///
+/// - `stats(&self) -> ParserStats`
+///   Returns a snapshot of the parser's statistics.
+///
+/// - `on_kind(&mut self, kind: RespKind, hook: impl Fn(&RespValue))`
+///   Registers a hook receiving every decoded frame of the given kind.
+///
+/// - `on_frame(&mut self, hook: impl Fn(&RespValue, usize))`
+///   Registers a hook receiving every decoded frame and its length in bytes.
+///
+/// - `stream_bulks(&mut self, threshold: usize, hook: impl Fn(&BulkChunk))`
+///   Streams bulk string bodies longer than `threshold` to `hook` instead of buffering them.
+///
+/// - `split_frame(&mut self) -> Result<Bytes, ParseError>`
+///   Splits off one complete frame as raw bytes without decoding it.
+///
+/// - `set_protocol(&mut self, protocol: Protocol)`
+///   Switches between RESP2 and RESP3 without losing buffered input.
+///
+/// - `take_warnings(&mut self) -> Vec<ParseWarning>`
+///   Returns and clears the warnings raised by the frames parsed so far.
+///
 /// # Internal Methods
 ///
 /// - `find_crlf(&self, start: usize) -> Option<usize>`
 ///   Finds the position of the CRLF sequence starting from the given position.
 ///
 /// - `handle_index(&mut self, index: usize) -> ParseState`
-///   Handles the initial parsing state based on the type marker at the given index.
+///   Dispatches on the type marker at the given index through `MARKER_HANDLERS`.
 ///
 /// - `handle_length(&mut self, pos: usize, value: i64, negative: bool, type_char: u8) -> ParseState`
 ///   Handles the parsing of length-prefixed types (bulk strings and arrays).
@@ -139,6 +1108,9 @@ pub struct Parser {
 ///
 /// - `handle_integer(&mut self, pos: usize) -> ParseState`
 ///   Handles the parsing of integer values.
+///
+/// - `handle_double`, `handle_big_number`, `handle_bulk_error`, `handle_verbatim_string`
+///   Handle the parsing of the remaining line-terminated RESP3 types.
 impl Parser {
     /// Creates a new parser instance.
     ///
@@ -151,246 +1123,589 @@ impl Parser {
     ///
     /// Returns a new `Parser` instance.
     pub fn new(max_depth: usize, max_length: usize) -> Self {
+        Self::with_config(ParserConfig {
+            max_depth,
+            max_length,
+            ..ParserConfig::default()
+        })
+    }
+
+    /// Creates a new parser instance from a `ParserConfig`.
+    pub fn with_config(config: ParserConfig) -> Self {
+        Self::build(config)
+    }
+
+    /// Creates a parser with the default configuration that starts parsing the bytes
+    /// already in `buffer`, taking over its allocation.
+    ///
+    /// Use `with_config` followed by `feed` to combine a custom configuration with a
+    /// buffer handoff; `feed` takes over the allocation as well.
+    pub fn from_buffer(buffer: BytesMut) -> Self {
+        let mut parser = Self::with_config(ParserConfig::default());
+        parser.buffer = buffer;
+        parser
+    }
+}
+
+impl<const MAX_DEPTH: usize> Parser<MAX_DEPTH> {
+    /// Table of `MarkerHandler`s indexed by type marker byte, `None` for bytes that
+    /// start no value.
+    ///
+    /// Supporting a new type only takes a table entry and its handler.
+    const MARKER_HANDLERS: &'static MarkerTable<MAX_DEPTH> = &{
+        let mut table: MarkerTable<MAX_DEPTH> = [None; 256];
+        table[b'+' as usize] = Some(Self::start_simple_string);
+        table[b'-' as usize] = Some(Self::start_error);
+        table[b':' as usize] = Some(Self::start_integer);
+        table[b'$' as usize] = Some(Self::start_length);
+        table[b'*' as usize] = Some(Self::start_length);
+        table[b'%' as usize] = Some(Self::start_length);
+        table[b'~' as usize] = Some(Self::start_length);
+        table[b'>' as usize] = Some(Self::start_length);
+        table[b'|' as usize] = Some(Self::start_length);
+        table[b'_' as usize] = Some(Self::start_null);
+        table[b'#' as usize] = Some(Self::start_boolean);
+        table[b',' as usize] = Some(Self::start_double);
+        table[b'(' as usize] = Some(Self::start_big_number);
+        table[b'!' as usize] = Some(Self::start_bulk_error);
+        table[b'=' as usize] = Some(Self::start_verbatim_string);
+        table
+    };
+
+    /// The subset of `MARKER_HANDLERS` for the types RESP2 has.
+    const RESP2_MARKER_HANDLERS: &'static MarkerTable<MAX_DEPTH> = &{
+        let mut table: MarkerTable<MAX_DEPTH> = [None; 256];
+        table[b'+' as usize] = Some(Self::start_simple_string);
+        table[b'-' as usize] = Some(Self::start_error);
+        table[b':' as usize] = Some(Self::start_integer);
+        table[b'$' as usize] = Some(Self::start_length);
+        table[b'*' as usize] = Some(Self::start_length);
+        table
+    };
+
+    fn marker_table(protocol: Protocol) -> &'static MarkerTable<MAX_DEPTH> {
+        match protocol {
+            Protocol::Resp2 => Self::RESP2_MARKER_HANDLERS,
+            Protocol::Resp3 => Self::MARKER_HANDLERS,
+        }
+    }
+
+    /// Creates a parser that keeps its stack of open aggregates inline, so parsing
+    /// nested frames never allocates for it.
+    ///
+    /// `config.max_depth` is capped at `MAX_DEPTH`; deeper frames fail with
+    /// `ParseError::InvalidDepth`. `Parser<0>`, the default, keeps the stack on the heap.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::parser::{FixedDepthParser, ParserConfig};
+    ///
+    /// let mut parser = FixedDepthParser::<4>::with_fixed_depth(ParserConfig::default());
+    /// assert_eq!(parser.config().max_depth, 4);
+    /// parser.read_buf(b"*1\r\n*1\r\n:1\r\n");
+    /// assert!(parser.try_parse().unwrap().is_some());
+    /// ```
+    pub fn with_fixed_depth(mut config: ParserConfig) -> Self {
+        if MAX_DEPTH > 0 {
+            config.max_depth = config.max_depth.min(MAX_DEPTH);
+        }
+        Self::build(config)
+    }
+
+    fn build(config: ParserConfig) -> Self {
+        let markers = Self::marker_table(config.protocol);
         Parser {
             buffer: BytesMut::with_capacity(DEFAULT_BUFFER_INIT_SIZE),
             state: ParseState::Index { pos: 0 },
-            max_length,
-            max_depth,
-            nested_stack: Vec::with_capacity(max_depth),
+            nested_stack: NestedStack::with_capacity(config.max_depth),
+            config,
+            resyncing: false,
+            frame_offset: 0,
+            frame_memory: 0,
+            rejected_frame: None,
+            discard: None,
+            warnings: Vec::new(),
+            last_error_context: None,
+            next_frame: None,
+            stats: ParserStats::default(),
+            hooks: DecodeHooks::default(),
+            peeking: false,
+            markers,
+        }
+    }
+
+    /// Consumes the parser and returns its buffer, starting at the first byte not yet
+    /// consumed.
+    ///
+    /// Elements of a partially parsed aggregate may already have been dropped from the
+    /// buffer, so hand the buffer back between frames to get the exact input back.
+    pub fn into_buffer(mut self) -> BytesMut {
+        if let ParseState::Index { pos } = self.state {
+            self.buffer.advance(pos.min(self.buffer.len()));
+        }
+        self.buffer
+    }
+
+    /// Returns the parser's configuration.
+    pub fn config(&self) -> &ParserConfig {
+        &self.config
+    }
+
+    /// Switches the protocol of the values read from now on, e.g. after a `HELLO 3`.
+    ///
+    /// Buffered input is kept, and so is a partially parsed frame: only values whose
+    /// type marker is read after the switch are affected.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::parser::{ParseError, Parser, ParserConfig, Protocol};
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let mut parser = Parser::with_config(ParserConfig {
+    ///     protocol: Protocol::Resp2,
+    ///     ..ParserConfig::default()
+    /// });
+    /// parser.read_buf(b"#t\r\n");
+    /// assert!(matches!(parser.try_parse(), Err(ParseError::InvalidFormat(_))));
+    ///
+    /// parser.set_protocol(Protocol::Resp3);
+    /// assert_eq!(parser.try_parse(), Ok(Some(RespValue::Boolean(true))));
+    /// ```
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.config.protocol = protocol;
+        self.markers = Self::marker_table(protocol);
+        // Validated under the previous protocol
+        self.next_frame = None;
+    }
+
+    /// Registers `hook` to receive every frame of type `kind` returned by `try_parse`,
+    /// before it is returned.
+    ///
+    /// Only complete frames are passed, not the values nested inside them. Hooks are
+    /// shared with clones of the parser.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use stream_resp::parser::Parser;
+    /// use stream_resp::resp::RespKind;
+    ///
+    /// let errors = Arc::new(Mutex::new(Vec::new()));
+    /// let mut parser = Parser::new(10, 1024);
+    /// let seen = errors.clone();
+    /// parser.on_kind(RespKind::Error, move |value| seen.lock().unwrap().push(value.clone()));
+    ///
+    /// parser.read_buf(b"+OK\r\n-ERR oops\r\n");
+    /// while let Ok(Some(_)) = parser.try_parse() {}
+    /// assert_eq!(errors.lock().unwrap().len(), 1);
+    /// ```
+    pub fn on_kind<F>(&mut self, kind: RespKind, hook: F)
+    where
+        F: Fn(&RespValue<'static>) + Send + Sync + 'static,
+    {
+        self.hooks.by_kind.push((kind, Arc::new(hook)));
+    }
+
+    /// Registers `hook` to receive every frame returned by `try_parse` together with
+    /// the number of bytes it took up in the input, before it is returned.
+    ///
+    /// Hooks run in registration order and are shared with clones of the parser.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use stream_resp::parser::Parser;
+    ///
+    /// let bytes = Arc::new(AtomicUsize::new(0));
+    /// let mut parser = Parser::new(10, 1024);
+    /// let counter = bytes.clone();
+    /// parser.on_frame(move |_, len| {
+    ///     counter.fetch_add(len, Ordering::Relaxed);
+    /// });
+    ///
+    /// parser.read_buf(b"+OK\r\n:1\r\n");
+    /// while let Ok(Some(_)) = parser.try_parse() {}
+    /// assert_eq!(bytes.load(Ordering::Relaxed), 9);
+    /// ```
+    pub fn on_frame<F>(&mut self, hook: F)
+    where
+        F: Fn(&RespValue<'static>, usize) + Send + Sync + 'static,
+    {
+        self.hooks.on_frame.push(Arc::new(hook));
+    }
+
+    /// Streams the bodies of bulk strings longer than `threshold` bytes to `hook` as
+    /// they arrive, instead of buffering them.
+    ///
+    /// Each body is handed over in one or more `BulkChunk`s and then dropped from the
+    /// buffer, so a huge value never has to fit in memory. The value itself decodes to
+    /// `RespValue::BulkStreamed` with the body's length. Bodies are not checked for
+    /// valid UTF-8, and `ParserConfig::max_length` still applies. `peek_frame` reports
+    /// the same value but does not call `hook`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use stream_resp::parser::Parser;
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let body = Arc::new(Mutex::new(Vec::new()));
+    /// let mut parser = Parser::new(10, 1024);
+    /// let sink = body.clone();
+    /// parser.stream_bulks(4, move |chunk| sink.lock().unwrap().extend_from_slice(chunk.data));
+    ///
+    /// parser.read_buf(b"*2\r\n$3\r\nSET\r\n$10\r\n01234");
+    /// assert!(parser.try_parse().is_err());
+    /// parser.read_buf(b"56789\r\n");
+    /// assert_eq!(
+    ///     parser.try_parse(),
+    ///     Ok(Some(RespValue::Array(Some(Box::from(vec![
+    ///         RespValue::BulkString(Some("SET".into())),
+    ///         RespValue::BulkStreamed { len: 10 },
+    ///     ])))))
+    /// );
+    /// assert_eq!(&body.lock().unwrap()[..], b"0123456789");
+    /// ```
+    pub fn stream_bulks<F>(&mut self, threshold: usize, hook: F)
+    where
+        F: Fn(&BulkChunk<'_>) + Send + Sync + 'static,
+    {
+        self.hooks.bulk_chunks = Some((threshold, Arc::new(hook)));
+    }
+
+    /// Records the size of every frame returned by `try_parse` or `split_frame` in
+    /// `histogram`, as it is parsed; see `stats::MetricsExporter::frame_size_histogram`.
+    #[cfg(feature = "metrics")]
+    pub fn record_frame_sizes(&mut self, histogram: ::metrics::Histogram) {
+        self.hooks.frame_sizes = Some(histogram);
+    }
+
+    fn observe_frame_size(&mut self, len: usize) {
+        self.stats.frame_sizes.observe(len);
+        #[cfg(feature = "metrics")]
+        if let Some(histogram) = &self.hooks.frame_sizes {
+            histogram.record(len as f64);
+        }
+    }
+
+    /// Returns the total number of bytes discarded by error recovery.
+    ///
+    /// Always zero unless `ParserConfig::recover_errors` is enabled.
+    pub fn skipped_bytes(&self) -> u64 {
+        self.stats.skipped_bytes
+    }
+
+    /// Returns a snapshot of the parser's statistics.
+    pub fn stats(&self) -> ParserStats {
+        ParserStats {
+            buffered_bytes: self.buffer.len() as u64,
+            peak_buffered_bytes: self.stats.peak_buffered_bytes.max(self.buffer.len() as u64),
+            ..self.stats.clone()
+        }
+    }
+
+    /// Returns and clears the warnings raised by the frames parsed so far.
+    ///
+    /// Warnings are only raised when `ParserConfig::strict` is disabled; in strict mode
+    /// the same constructs fail the frame instead.
+    pub fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn read_buf(&mut self, buf: &[u8]) {
+        self.make_room(buf.len());
+        self.buffer.extend_from_slice(buf);
+    }
+
+    /// Reads once from `reader` directly into the buffer's spare capacity.
+    ///
+    /// Returns the number of bytes read; `Ok(0)` means `reader` reached end of file.
+    /// Errors, including `ErrorKind::Interrupted`, are returned as is.
+    pub fn read_from<R: std::io::Read>(&mut self, reader: &mut R) -> std::io::Result<usize> {
+        self.make_room(DEFAULT_BUFFER_INIT_SIZE);
+
+        // Reading into uninitialized memory is unsound without `unsafe`, so zero it first
+        let len = self.buffer.len();
+        let spare = (self.buffer.capacity() - len).min(READ_WINDOW);
+        self.buffer.resize(len + spare, 0);
+        let result = reader.read(&mut self.buffer[len..]);
+        let read = *result.as_ref().unwrap_or(&0);
+        self.buffer.truncate(len + read);
+        result
+    }
+
+    /// Reads once from `reader` directly into the buffer's spare capacity.
+    ///
+    /// Returns the number of bytes read; `Ok(0)` means `reader` reached end of file.
+    /// Cancelling the future loses no data, so it can be used in `select!`.
+    #[cfg(feature = "tokio")]
+    pub async fn read_from_async<R>(&mut self, reader: &mut R) -> std::io::Result<usize>
+    where
+        R: tokio::io::AsyncRead + Unpin,
+    {
+        self.make_room(DEFAULT_BUFFER_INIT_SIZE);
+        tokio::io::AsyncReadExt::read_buf(reader, &mut self.buffer).await
+    }
+
+    /// Polls `reader` once for input, reading into the buffer's spare capacity.
+    #[cfg(feature = "stream")]
+    pub(crate) fn poll_read_from<R>(
+        &mut self,
+        cx: &mut std::task::Context<'_>,
+        reader: std::pin::Pin<&mut R>,
+    ) -> std::task::Poll<std::io::Result<usize>>
+    where
+        R: tokio::io::AsyncRead + ?Sized,
+    {
+        self.make_room(DEFAULT_BUFFER_INIT_SIZE);
+
+        // Zeroed, and bounded, for the same reasons as in `read_from`
+        let len = self.buffer.len();
+        let spare = (self.buffer.capacity() - len).min(READ_WINDOW);
+        self.buffer.resize(len + spare, 0);
+        let mut read_buf = tokio::io::ReadBuf::new(&mut self.buffer[len..]);
+        let result = reader.poll_read(cx, &mut read_buf);
+        let read = match result {
+            std::task::Poll::Ready(Ok(())) => read_buf.filled().len(),
+            _ => 0,
+        };
+        self.buffer.truncate(len + read);
+        result.map_ok(|()| read)
+    }
+
+    /// Drops consumed bytes and makes sure `additional` bytes fit without reallocating.
+    fn make_room(&mut self, additional: usize) {
+        // More input may complete the next frame
+        if self.next_frame == Some(NextFrame::Incomplete) {
+            self.next_frame = None;
+        }
+
+        // Completed frames are already dropped by `clear_buffer`. Inside an aggregate the
+        // elements parsed so far live on the nested stack, so the bytes before the next
+        // element can be dropped as well.
+        if let ParseState::Index { pos } = self.state
+            && pos > 0
+        {
+            self.buffer.advance(pos);
+            self.frame_offset += pos;
+            self.state = ParseState::Index { pos: 0 };
+        }
+
+        // `reserve` reclaims the space of dropped bytes before growing
+        if self.buffer.capacity() - self.buffer.len() < additional {
+            self.buffer
+                .reserve(additional.max(DEFAULT_BUFFER_INIT_SIZE));
+        }
+    }
+
+    /// Appends an owned chunk of input.
+    ///
+    /// If no input is buffered and `bytes` is the only handle to its allocation, the
+    /// allocation becomes the parser's buffer without copying. Otherwise the bytes are
+    /// copied like `read_buf` does.
+    pub fn feed_bytes(&mut self, bytes: Bytes) {
+        if !self.buffer.is_empty() {
+            self.read_buf(&bytes);
+            return;
+        }
+        match bytes.try_into_mut() {
+            Ok(buffer) => {
+                // Every state position is 0 while the buffer is empty
+                self.buffer = buffer;
+                self.next_frame = None;
+                if let ParseState::Index { .. } = self.state {
+                    self.state = ParseState::Index { pos: 0 };
+                }
+            }
+            Err(bytes) => self.read_buf(&bytes),
+        }
+    }
+
+    /// Appends all remaining bytes of `buf`.
+    ///
+    /// Buffers that hand out `Bytes` without copying, such as `Bytes` and `BytesMut`,
+    /// are taken over through `feed_bytes`; others are copied chunk by chunk.
+    pub fn feed(&mut self, mut buf: impl Buf) {
+        if self.buffer.is_empty() {
+            let len = buf.remaining();
+            let bytes = buf.copy_to_bytes(len);
+            // Release the source's handle so the allocation can be taken over
+            drop(buf);
+            self.feed_bytes(bytes);
+            return;
+        }
+        while buf.has_remaining() {
+            let chunk = buf.chunk();
+            let len = chunk.len();
+            self.read_buf(chunk);
+            buf.advance(len);
+        }
+    }
+
+    /// Returns a reference to the parser's internal buffer.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the internal buffer.
+    pub fn buffer(&self) -> &BytesMut {
+        &self.buffer
+    }
+
+    #[inline(always)]
+    fn handle_index(&mut self, index: usize) -> ParseState {
+        let Some(&marker) = self.buffer.get(index) else {
+            return ParseState::Error(ParseError::UnexpectedEof);
+        };
+
+        if self.config.requests_only {
+            let expected = if self.nested_stack.is_empty() {
+                b'*'
+            } else {
+                b'$'
+            };
+            if marker != expected {
+                if expected == b'*' && self.config.inline_commands {
+                    return self.read_inline_command(index);
+                }
+                return ParseState::Error(unexpected_marker(expected, marker));
+            }
+        }
+
+        match self.markers[marker as usize] {
+            Some(handler) => handler(self, index),
+            None if marker == b'\r' => {
+                // Handle CRLF for array elements
+                if index + 1 >= self.buffer.len() {
+                    ParseState::Error(ParseError::UnexpectedEof)
+                } else if self.buffer[index + 1] == b'\n' {
+                    ParseState::Index { pos: index + 2 }
+                } else {
+                    ParseState::Error(ParseError::InvalidFormat("Expected \\n after \\r".into()))
+                }
+            }
+            None if marker == b'\n' && self.config.lenient_line_endings => {
+                ParseState::Index { pos: index + 1 }
+            }
+            None if marker == b'.' => self.end_streamed_aggregate(index),
+            None => ParseState::Error(ParseError::InvalidFormat("Invalid type marker".into())),
+        }
+    }
+
+    /// Reads an inline command starting at `index` as an array of bulk strings; see
+    /// `ParserConfig::inline_commands`.
+    fn read_inline_command(&mut self, index: usize) -> ParseState {
+        let Some((end, next_pos)) = find_line(
+            &self.buffer,
+            index,
+            self.config.scanner,
+            self.config.lenient_line_endings,
+        ) else {
+            if self.buffer.len() - index > self.config.max_length {
+                return ParseState::Error(ParseError::InvalidLength);
+            }
+            return ParseState::Error(ParseError::UnexpectedEof);
+        };
+        let line = match bulk_str(&self.buffer[index..end]) {
+            Ok(line) => line,
+            Err(_) => return ParseState::Error(ParseError::InvalidUtf8),
+        };
+        let args: Vec<RespValue<'static>> = line
+            .split_ascii_whitespace()
+            .map(|arg| RespValue::BulkString(Some(Cow::Owned(arg.to_string()))))
+            .collect();
+        if args.is_empty() {
+            return ParseState::Index { pos: next_pos };
+        }
+        ParseState::Complete(Some((RespValue::Array(Some(Box::from(args))), next_pos)))
+    }
+
+    fn start_simple_string(&mut self, index: usize) -> ParseState {
+        ParseState::ReadingSimpleString {
+            pos: index + 1,
+            scanned: index + 1,
         }
     }
 
-    pub fn read_buf(&mut self, buf: &[u8]) {
-        // Create more efficient sliding window buffer
-        if self.buffer.len() > 0 && self.buffer.capacity() < self.buffer.len() + buf.len() {
-            // If we've processed part of the data, we can keep the unprocessed part
-            if let ParseState::Index { pos } = self.state {
-                if pos > 0 {
-                    // Create a new buffer with the remaining data
-                    let remaining = self.buffer.split_off(pos);
-                    self.buffer = remaining;
-                    self.state = ParseState::Index { pos: 0 };
-                }
-            }
+    fn start_error(&mut self, index: usize) -> ParseState {
+        ParseState::ReadingError {
+            pos: index + 1,
+            scanned: index + 1,
         }
+    }
 
-        // If the buffer is still too small, consider clearing it
-        if self.buffer.capacity() < buf.len() {
-            self.buffer.clear();
-            self.buffer.reserve(buf.len() + DEFAULT_BUFFER_INIT_SIZE);
+    fn start_integer(&mut self, index: usize) -> ParseState {
+        ParseState::ReadingInteger {
+            pos: index + 1,
+            scanned: index + 1,
         }
-
-        self.buffer.extend_from_slice(buf);
     }
 
-    /// Returns a reference to the parser's internal buffer.
-    ///
-    /// # Returns
-    ///
-    /// A reference to the internal buffer.
-    pub fn buffer(&self) -> &BytesMut {
-        &self.buffer
+    fn start_double(&mut self, index: usize) -> ParseState {
+        ParseState::ReadingDouble {
+            pos: index + 1,
+            scanned: index + 1,
+        }
     }
 
-    #[inline(always)]
-    fn find_crlf(&self, start: usize) -> Option<usize> {
-        // Use memchr's more optimized implementation
-        let buf = &self.buffer[start..];
-        let r_position = memchr(b'\r', buf)?;
-        let pos = start + r_position;
-
-        // Check if there's a \n after the \r
-        if pos + 1 < self.buffer.len() && self.buffer[pos + 1] == b'\n' {
-            Some(pos)
-        } else {
-            // Keep searching past this \r
-            self.find_crlf(pos + 1)
+    fn start_big_number(&mut self, index: usize) -> ParseState {
+        ParseState::ReadingBigNumber {
+            pos: index + 1,
+            scanned: index + 1,
         }
     }
 
-    #[inline(always)]
-    fn handle_index(&mut self, index: usize) -> ParseState {
-        if index >= self.buffer.len() {
-            return ParseState::Error(ParseError::UnexpectedEof);
+    fn start_bulk_error(&mut self, index: usize) -> ParseState {
+        if !self.config.legacy_bulk_errors {
+            return self.start_length(index);
         }
+        ParseState::ReadingBulkError {
+            pos: index + 1,
+            scanned: index + 1,
+        }
+    }
 
-        match self.buffer[index] {
-            b'+' => ParseState::ReadingSimpleString { pos: index + 1 },
-            b'-' => ParseState::ReadingError { pos: index + 1 },
-            b':' => ParseState::ReadingInteger { pos: index + 1 },
-            b'$' => ParseState::ReadingLength {
-                value: 0,
-                negative: false,
-                pos: index + 1,
-                type_char: b'$',
-            },
-            b'*' => ParseState::ReadingLength {
-                value: 0,
-                negative: false,
-                pos: index + 1,
-                type_char: b'*',
-            },
-            b'%' => ParseState::ReadingLength {
-                // Added Map type marker
-                value: 0,
-                negative: false,
-                pos: index + 1,
-                type_char: b'%',
-            },
-            b'~' => ParseState::ReadingLength {
-                // Added Set type marker
-                value: 0,
-                negative: false,
-                pos: index + 1,
-                type_char: b'~',
-            },
-            b'>' => ParseState::ReadingLength {
-                // Added Push type marker
-                value: 0,
-                negative: false,
-                pos: index + 1,
-                type_char: b'>',
-            },
-            b'_' => {
-                // Handle Null type
-                if index + 2 < self.buffer.len()
-                    && self.buffer[index + 1] == b'\r'
-                    && self.buffer[index + 2] == b'\n'
-                {
-                    ParseState::Complete(Some((RespValue::Null, index + 3)))
-                } else {
-                    ParseState::Error(ParseError::UnexpectedEof)
-                }
-            }
-            b'#' => {
-                // Handle Boolean type
-                if index + 2 < self.buffer.len()
-                    && self.buffer[index + 2] == b'\r'
-                    && index + 3 < self.buffer.len()
-                    && self.buffer[index + 3] == b'\n'
-                {
-                    match self.buffer[index + 1] {
-                        b't' => ParseState::Complete(Some((RespValue::Boolean(true), index + 4))),
-                        b'f' => ParseState::Complete(Some((RespValue::Boolean(false), index + 4))),
-                        _ => ParseState::Error(ParseError::InvalidFormat(
-                            "Invalid boolean value".into(),
-                        )),
-                    }
-                } else {
-                    ParseState::Error(ParseError::UnexpectedEof)
-                }
-            }
-            b',' => {
-                // Handle Double type
-                match self.find_crlf(index + 1) {
-                    Some(end_pos) => {
-                        let bytes = &self.buffer[(index + 1)..end_pos];
-                        let double_str = std::str::from_utf8(bytes);
-
-                        match double_str {
-                            Ok(s) => match s.parse::<f64>() {
-                                Ok(value) => ParseState::Complete(Some((
-                                    RespValue::Double(value),
-                                    end_pos + CRLF_LEN,
-                                ))),
-                                Err(_) => ParseState::Error(ParseError::InvalidFormat(
-                                    "Invalid double value".into(),
-                                )),
-                            },
-                            Err(_) => ParseState::Error(ParseError::InvalidUtf8),
-                        }
-                    }
-                    None => ParseState::Error(ParseError::UnexpectedEof),
-                }
-            }
-            b'(' => {
-                // Handle Big Number type
-                match self.find_crlf(index + 1) {
-                    Some(end_pos) => {
-                        let bytes = &self.buffer[(index + 1)..end_pos];
-
-                        // Verify that the big number contains only valid characters (digits and optional leading minus)
-                        let is_valid = bytes
-                            .iter()
-                            .enumerate()
-                            .all(|(i, &b)| (b'0'..=b'9').contains(&b) || (i == 0 && b == b'-'));
-
-                        if !is_valid {
-                            return ParseState::Error(ParseError::InvalidFormat(
-                                "Invalid big number format".into(),
-                            ));
-                        }
-
-                        match std::str::from_utf8(bytes) {
-                            Ok(s) => ParseState::Complete(Some((
-                                RespValue::BigNumber(Cow::Owned(s.to_string())),
-                                end_pos + CRLF_LEN,
-                            ))),
-                            Err(_) => ParseState::Error(ParseError::InvalidUtf8),
-                        }
-                    }
-                    None => ParseState::Error(ParseError::UnexpectedEof),
-                }
-            }
-            b'!' => {
-                // Handle Bulk Error type
-                match self.find_crlf(index + 1) {
-                    Some(end_pos) => {
-                        let bytes = &self.buffer[(index + 1)..end_pos];
-
-                        // Check for null bulk error (-1)
-                        if bytes.len() == 2 && bytes[0] == b'-' && bytes[1] == b'1' {
-                            return ParseState::Complete(Some((
-                                RespValue::BulkError(None),
-                                end_pos + CRLF_LEN,
-                            )));
-                        }
+    fn start_verbatim_string(&mut self, index: usize) -> ParseState {
+        if !self.config.legacy_verbatim_strings {
+            return self.start_length(index);
+        }
+        ParseState::ReadingVerbatimString {
+            pos: index + 1,
+            scanned: index + 1,
+        }
+    }
 
-                        match std::str::from_utf8(bytes) {
-                            Ok(s) => ParseState::Complete(Some((
-                                RespValue::BulkError(Some(Cow::Owned(s.to_string()))),
-                                end_pos + CRLF_LEN,
-                            ))),
-                            Err(_) => ParseState::Error(ParseError::InvalidUtf8),
-                        }
-                    }
-                    None => ParseState::Error(ParseError::UnexpectedEof),
-                }
-            }
-            b'=' => {
-                // Handle Verbatim String type
-                match self.find_crlf(index + 1) {
-                    Some(end_pos) => {
-                        let bytes = &self.buffer[(index + 1)..end_pos];
+    /// Starts a bulk string, verbatim string or bulk error or an aggregate (`$`, `=`,
+    /// `!`, `*`, `%`, `~`, `>`, `|`) with its length.
+    fn start_length(&mut self, index: usize) -> ParseState {
+        ParseState::ReadingLength {
+            value: 0,
+            negative: false,
+            pos: index + 1,
+            type_char: self.buffer[index],
+        }
+    }
 
-                        // Check for null verbatim string (-1)
-                        if bytes.len() == 2 && bytes[0] == b'-' && bytes[1] == b'1' {
-                            return ParseState::Complete(Some((
-                                RespValue::VerbatimString(None),
-                                end_pos + CRLF_LEN,
-                            )));
-                        }
+    fn start_null(&mut self, index: usize) -> ParseState {
+        match self.terminator_at(index + 1) {
+            Some(len) => ParseState::Complete(Some((RespValue::Null, index + 1 + len))),
+            None => ParseState::Error(ParseError::UnexpectedEof),
+        }
+    }
 
-                        match std::str::from_utf8(bytes) {
-                            Ok(s) => ParseState::Complete(Some((
-                                RespValue::VerbatimString(Some(Cow::Owned(s.to_string()))),
-                                end_pos + CRLF_LEN,
-                            ))),
-                            Err(_) => ParseState::Error(ParseError::InvalidUtf8),
-                        }
-                    }
-                    None => ParseState::Error(ParseError::UnexpectedEof),
-                }
-            }
-            b'\r' => {
-                // Handle CRLF for array elements
-                if index + 1 < self.buffer.len() && self.buffer[index + 1] == b'\n' {
-                    ParseState::Index { pos: index + 2 }
-                } else {
-                    ParseState::Error(ParseError::InvalidFormat("Expected \\n after \\r".into()))
-                }
-            }
-            _ => ParseState::Error(ParseError::InvalidFormat("Invalid type marker".into())),
+    fn start_boolean(&mut self, index: usize) -> ParseState {
+        match self.terminator_at(index + 2) {
+            Some(len) => match self.buffer[index + 1] {
+                b't' => ParseState::Complete(Some((RespValue::Boolean(true), index + 2 + len))),
+                b'f' => ParseState::Complete(Some((RespValue::Boolean(false), index + 2 + len))),
+                _ => ParseState::Error(ParseError::InvalidFormat("Invalid boolean value".into())),
+            },
+            None => ParseState::Error(ParseError::UnexpectedEof),
         }
     }
 
@@ -402,27 +1717,35 @@ impl Parser {
         negative: bool,
         type_char: u8,
     ) -> ParseState {
-        return match self.buffer.get(pos) {
+        match self.buffer.get(pos) {
             Some(&b) => match b {
                 b'0'..=b'9' => {
-                    let new_value = match value.checked_mul(10).and_then(|v| {
+                    // Consume every buffered digit at once
+                    let scanner = self.config.scanner;
+                    let run = scanner.digit_run(&self.buffer[pos..]);
+                    let digits = match scanner.parse_digits(&self.buffer[pos..pos + run]) {
+                        Ok(digits) => i64::try_from(digits).ok(),
+                        Err(_) => None,
+                    };
+                    let new_value = digits.and_then(|digits| {
+                        let scaled = match value {
+                            0 => 0,
+                            _ => value.checked_mul(10i64.checked_pow(run as u32)?)?,
+                        };
                         if negative {
-                            v.checked_sub((b - b'0') as i64)
+                            scaled.checked_sub(digits)
                         } else {
-                            v.checked_add((b - b'0') as i64)
-                        }
-                    }) {
-                        Some(v) => v,
-                        None => {
-                            return ParseState::Error(ParseError::Overflow);
+                            scaled.checked_add(digits)
                         }
-                    };
-
-                    ParseState::ReadingLength {
-                        pos: pos + 1,
-                        value: new_value,
-                        negative,
-                        type_char,
+                    });
+                    match new_value {
+                        Some(value) => ParseState::ReadingLength {
+                            pos: pos + run,
+                            value,
+                            negative,
+                            type_char,
+                        },
+                        None => ParseState::Error(ParseError::Overflow),
                     }
                 }
                 b'-' => ParseState::ReadingLength {
@@ -431,38 +1754,103 @@ impl Parser {
                     negative: true,
                     type_char,
                 },
-                b'\r' => match self.buffer.get(pos + 1) {
-                    Some(&b'\n') => {
-                        let next_pos = pos + CRLF_LEN; // Position after CRLF
+                // `$?`, `*?`, `%?` and `~?` start streamed values of unknown length
+                b'?' if matches!(type_char, b'$' | b'*' | b'%' | b'~')
+                    && self.buffer[pos - 1] == type_char
+                    && self.config.protocol == Protocol::Resp3
+                    && !self.config.requests_only =>
+                {
+                    match self.terminator_at(pos + 1) {
+                        Some(len) if type_char == b'$' => ParseState::ReadingStreamedString {
+                            start: pos + 1 + len,
+                            pos: pos + 1 + len,
+                            len: 0,
+                        },
+                        Some(len) => ParseState::ReadingStreamedAggregate {
+                            pos: pos + 1 + len,
+                            elements: Vec::new(),
+                            original_type_char: type_char,
+                        },
+                        None if self.buffer.len() < pos + 1 + CRLF_LEN => {
+                            ParseState::Error(ParseError::UnexpectedEof)
+                        }
+                        None => ParseState::Error(ParseError::InvalidFormat(
+                            "Invalid character in length".into(),
+                        )),
+                    }
+                }
+                b'\r' | b'\n' => match self.terminator_at(pos) {
+                    Some(len) => {
+                        let next_pos = pos + len; // Position after the line ending
                         match type_char {
-                            b'$' => {
+                            b'$' if value < 0 && self.config.requests_only => {
+                                // Arguments are never null
+                                ParseState::Error(ParseError::InvalidFormat(
+                                    error_text::INVALID_BULK_LENGTH.into(),
+                                ))
+                            }
+                            // Like redis-server, skip commands without arguments
+                            b'*' if value <= 0 && self.config.requests_only => {
+                                ParseState::Index { pos: next_pos }
+                            }
+                            b'$' | b'=' | b'!' => {
                                 if value < 0 {
                                     // RESP3 Null Bulk String $-1\r\n
-                                    ParseState::Complete(Some((
-                                        RespValue::BulkString(None),
-                                        next_pos,
-                                    )))
+                                    let null_value = match type_char {
+                                        b'$' => RespValue::BulkString(None),
+                                        b'=' => RespValue::VerbatimString(None),
+                                        _ => RespValue::BulkError(None),
+                                    };
+                                    ParseState::Complete(Some((null_value, next_pos)))
+                                } else if type_char == b'=' && value < VERBATIM_PREFIX_LEN as i64 {
+                                    ParseState::Error(ParseError::InvalidFormat(
+                                        "Verbatim string without format prefix".into(),
+                                    ))
                                 } else if value == 0 {
                                     // RESP3 Empty Bulk String $0\r\n\r\n
                                     // Need to check for the second CRLF
                                     if self.buffer.len() >= next_pos + CRLF_LEN
                                         && self.buffer[next_pos..next_pos + CRLF_LEN] == *b"\r\n"
                                     {
+                                        let empty = Some(Cow::Borrowed(""));
+                                        let empty_value = match type_char {
+                                            b'$' if self.config.shared_bulks => {
+                                                RespValue::BulkBytes(Box::default())
+                                            }
+                                            b'$' => RespValue::BulkString(empty),
+                                            _ => RespValue::BulkError(empty),
+                                        };
                                         ParseState::Complete(Some((
-                                            RespValue::BulkString(Some(Cow::Borrowed(""))),
+                                            empty_value,
                                             next_pos + CRLF_LEN,
                                         )))
                                     } else {
                                         ParseState::Error(ParseError::UnexpectedEof) // Or NotEnoughData
                                     }
+                                } else if value as usize >= self.config.max_length {
+                                    // Reject before any of the payload is buffered
+                                    ParseState::Error(ParseError::InvalidLength)
+                                } else if type_char == b'$'
+                                    && let Some((threshold, _)) = self.hooks.bulk_chunks
+                                    && value as usize > threshold
+                                {
+                                    ParseState::StreamingBulkString {
+                                        pos: next_pos,
+                                        len: value as usize,
+                                        remaining: value as usize,
+                                    }
                                 } else {
                                     ParseState::ReadingBulkString {
                                         start_pos: next_pos,
                                         remaining: value as usize,
+                                        type_char,
                                     }
                                 }
                             }
-                            b'*' | b'%' | b'~' | b'>' => {
+                            b'|' if value < 0 => ParseState::Error(ParseError::InvalidFormat(
+                                "Invalid attribute length".into(),
+                            )),
+                            b'*' | b'%' | b'~' | b'>' | b'|' => {
                                 // Handle Array, Map, Set, Push length
                                 if value < 0 {
                                     // RESP3 Null Aggregate Type
@@ -474,27 +1862,37 @@ impl Parser {
                                         _ => unreachable!(), // Should be covered by outer match
                                     };
                                     ParseState::Complete(Some((null_value, next_pos)))
-                                } else if value == 0 {
+                                } else if value == 0 && type_char != b'|' {
                                     // RESP3 Empty Aggregate Type
                                     let empty_value = match type_char {
-                                        b'*' => RespValue::Array(Some(vec![])),
-                                        b'%' => RespValue::Map(Some(vec![])),
-                                        b'~' => RespValue::Set(Some(vec![])),
-                                        b'>' => RespValue::Push(Some(vec![])),
+                                        b'*' => RespValue::Array(Some(Box::default())),
+                                        b'%' => RespValue::Map(Some(Box::default())),
+                                        b'~' => RespValue::Set(Some(Box::default())),
+                                        b'>' => RespValue::Push(Some(Box::default())),
                                         _ => unreachable!(),
                                     };
                                     ParseState::Complete(Some((empty_value, next_pos)))
                                 } else {
-                                    let total_elements = if type_char == b'%' {
-                                        (value * 2) as usize // Maps have key-value pairs
-                                    } else {
-                                        value as usize
+                                    let total_elements = match type_char {
+                                        b'%' => (value * 2) as usize, // Maps have key-value pairs
+                                        // Attributes are followed by the value they annotate
+                                        b'|' => (value * 2) as usize + 1,
+                                        _ => value as usize,
                                     };
+                                    let capacity = total_elements.min(self.config.prealloc_limit);
+                                    if let Err(error) =
+                                        self.charge(capacity * std::mem::size_of::<RespValue>())
+                                    {
+                                        // Skip the elements, like a frame nested too deep
+                                        self.rejected_frame =
+                                            Some((next_pos, self.owed(total_elements)));
+                                        return ParseState::Error(error);
+                                    }
                                     ParseState::ReadingArray {
                                         // Use ReadingArray for all aggregate types
                                         pos: next_pos,
                                         total: total_elements,
-                                        elements: Vec::with_capacity(total_elements),
+                                        elements: Vec::with_capacity(capacity),
                                         current: 0, // Start counting from 0 elements read
                                         original_type_char: type_char, // Store the original type
                                     }
@@ -508,20 +1906,32 @@ impl Parser {
                             )),
                         }
                     }
-                    _ => ParseState::Error(ParseError::InvalidFormat(
+                    // The \\n has not arrived yet
+                    None if b == b'\r' && pos + 1 == self.buffer.len() => {
+                        ParseState::Error(ParseError::UnexpectedEof)
+                    }
+                    None if b == b'\r' => ParseState::Error(ParseError::InvalidFormat(
                         "Expected \\n after \\r".into(),
                     )),
+                    None => ParseState::Error(ParseError::InvalidFormat(
+                        "Invalid character in length".into(),
+                    )),
                 },
                 _ => ParseState::Error(ParseError::InvalidFormat(
                     "Invalid character in length".into(),
                 )),
             },
             None => ParseState::Error(ParseError::UnexpectedEof), // Changed from NotEnoughData
-        };
+        }
     }
 
     #[inline(always)]
-    fn handle_bulk_string(&mut self, start_pos: usize, remaining: usize) -> ParseState {
+    fn handle_bulk_string(
+        &mut self,
+        start_pos: usize,
+        remaining: usize,
+        type_char: u8,
+    ) -> ParseState {
         // Early returns for special cases
         if remaining == 0 {
             // This case should ideally not be reached if handle_length handles $0 correctly.
@@ -532,78 +1942,512 @@ impl Parser {
             ));
         }
 
-        if remaining >= self.max_length {
-            return ParseState::Error(ParseError::InvalidLength);
+        // `handle_length` already checked `remaining` against `max_length`
+        let required_len = start_pos + remaining + CRLF_LEN;
+        if self.buffer.len() < required_len {
+            return ParseState::Error(ParseError::NotEnoughData);
+        }
+
+        // Check terminator first to fail fast
+        if self.buffer[start_pos + remaining] != b'\r'
+            || self.buffer[start_pos + remaining + 1] != b'\n'
+        {
+            return ParseState::Error(ParseError::InvalidFormat("Missing CRLF terminator".into()));
+        }
+
+        if type_char == b'$' && self.config.shared_bulks {
+            return self.split_bulk(start_pos, remaining);
+        }
+
+        // Create string view
+        let string_slice = &self.buffer[start_pos..start_pos + remaining];
+
+        let text = match bulk_str(string_slice) {
+            Ok(_) if let Some(interned) = self.config.intern.get(string_slice) => {
+                Cow::Borrowed(interned)
+            }
+            Ok(s) => Cow::Owned(s.to_string()),
+            Err(_) => return ParseState::Error(ParseError::InvalidUtf8),
+        };
+        let result = match type_char {
+            b'$' => RespValue::BulkString(Some(text)),
+            b'=' => match Verbatim::parse(text) {
+                Some(verbatim) => RespValue::VerbatimString(Some(Box::new(verbatim))),
+                None => {
+                    return ParseState::Error(ParseError::InvalidFormat(
+                        "Invalid verbatim string format prefix".into(),
+                    ));
+                }
+            },
+            _ => RespValue::BulkError(Some(text)),
+        };
+
+        ParseState::Complete(Some((result, start_pos + remaining + CRLF_LEN)))
+    }
+
+    /// Completes a bulk string with its payload split off the buffer as `Bytes`; see
+    /// `ParserConfig::shared_bulks`.
+    fn split_bulk(&mut self, start_pos: usize, len: usize) -> ParseState {
+        let end = start_pos + len + CRLF_LEN;
+        if self.peeking {
+            // The buffer is restored after peeking, so leave it whole
+            let payload = Bytes::copy_from_slice(&self.buffer[start_pos..start_pos + len]);
+            return ParseState::Complete(Some((RespValue::BulkBytes(Box::new(payload)), end)));
+        }
+        // Like `make_room`, drop what the frame no longer needs
+        let consumed = self.buffer.split_to(end).freeze();
+        self.frame_offset += end;
+        let payload = consumed.slice(start_pos..start_pos + len);
+        ParseState::Complete(Some((RespValue::BulkBytes(Box::new(payload)), 0)))
+    }
+
+    /// Hands the buffered part of a bulk string body to the `stream_bulks` hook and
+    /// drops it, then completes the value once its CRLF has arrived.
+    fn handle_streamed_bulk_string(
+        &mut self,
+        pos: usize,
+        len: usize,
+        remaining: usize,
+    ) -> ParseState {
+        if remaining == 0 {
+            return match self.buffer.get(pos..pos + CRLF_LEN) {
+                Some(b"\r\n") => {
+                    ParseState::Complete(Some((RespValue::BulkStreamed { len }, pos + CRLF_LEN)))
+                }
+                Some(_) => {
+                    ParseState::Error(ParseError::InvalidFormat("Missing CRLF terminator".into()))
+                }
+                None => ParseState::Error(ParseError::NotEnoughData),
+            };
+        }
+
+        let available = remaining.min(self.buffer.len() - pos);
+        if available == 0 {
+            return ParseState::Error(ParseError::NotEnoughData);
+        }
+        let end = pos + available;
+        if self.peeking {
+            return ParseState::StreamingBulkString {
+                pos: end,
+                len,
+                remaining: remaining - available,
+            };
+        }
+
+        if let Some((_, hook)) = &self.hooks.bulk_chunks {
+            hook(&BulkChunk {
+                data: &self.buffer[pos..end],
+                offset: len - remaining,
+                len,
+            });
+        }
+        // Like `make_room`, drop what the frame no longer needs
+        self.buffer.advance(end);
+        self.frame_offset += end;
+        ParseState::StreamingBulkString {
+            pos: 0,
+            len,
+            remaining: remaining - available,
+        }
+    }
+
+    /// Checks the next chunk of a streamed string is complete, then moves past it. The
+    /// closing chunk assembles the payloads of all chunks into a bulk string.
+    fn handle_streamed_string(&mut self, start: usize, pos: usize, len: usize) -> ParseState {
+        match streamed_chunk(&self.buffer, pos, &self.config) {
+            Ok((_, 0, next)) => {
+                let mut payload = Vec::with_capacity(len);
+                let mut at = start;
+                while let Ok((data, n, next)) = streamed_chunk(&self.buffer, at, &self.config)
+                    && n > 0
+                {
+                    payload.extend_from_slice(&self.buffer[data..data + n]);
+                    at = next;
+                }
+                match String::from_utf8(payload) {
+                    Ok(s) => ParseState::Complete(Some((
+                        RespValue::BulkString(Some(Cow::Owned(s))),
+                        next,
+                    ))),
+                    Err(_) => ParseState::Error(ParseError::InvalidUtf8),
+                }
+            }
+            Ok((_, n, _)) if len + n >= self.config.max_length => {
+                ParseState::Error(ParseError::InvalidLength)
+            }
+            Ok((_, n, next)) => ParseState::ReadingStreamedString {
+                start,
+                pos: next,
+                len: len + n,
+            },
+            Err(error) => ParseState::Error(error),
+        }
+    }
+
+    #[inline(always)]
+    fn handle_array(
+        &mut self,
+        pos: usize,
+        total: usize,
+        current: usize,
+        elements: Vec<RespValue<'static>>,
+        original_type_char: u8, // Pass original_type_char
+    ) -> ParseState {
+        if current >= total {
+            // Check if all elements are read
+            // Completion logic moved to try_parse
+            // This state should only transition to Index or Error here
+            // If we reach here, it means we are ready to parse the next element
+            ParseState::Index { pos }
+        } else {
+            // The stack is pre-allocated for `max_depth` aggregates and never grows past it
+            if self.nested_stack.len() >= self.config.max_depth {
+                // Values still owed by this aggregate and every open ancestor
+                self.rejected_frame = Some((pos, self.owed(total - current)));
+                return ParseState::Error(ParseError::InvalidDepth);
+            }
+
+            // Store current array/map state
+            self.nested_stack.push(ParseState::ReadingArray {
+                pos, // Position *after* the element we just parsed
+                total,
+                current, // Number of elements *already* parsed
+                elements,
+                original_type_char,
+            });
+            if self.nested_stack.len() > self.stats.max_depth_seen {
+                self.stats.max_depth_seen = self.nested_stack.len();
+            }
+
+            // Start parsing next element from current position
+            ParseState::Index { pos }
+        }
+    }
+
+    /// Builds the value of a completed aggregate from its elements. `pos` is the end of
+    /// the aggregate, for warnings.
+    fn finish_aggregate(
+        &mut self,
+        type_char: u8,
+        completed_elements: Vec<RespValue<'static>>,
+        pos: usize,
+    ) -> Result<RespValue<'static>, ParseError> {
+        Ok(match type_char {
+            b'%' => {
+                // Map
+                if completed_elements.iter().step_by(2).any(contains_nan) {
+                    self.warn(ParseWarning::NanMapKey, pos)?;
+                }
+                if self.config.detect_duplicate_keys
+                    && has_duplicates(completed_elements.iter().step_by(2))
+                {
+                    self.warn(ParseWarning::DuplicateMapKey, pos)?;
+                }
+                let mut map_pairs = Vec::with_capacity(completed_elements.len() / 2);
+                let mut iter = completed_elements.into_iter();
+                while let (Some(key), Some(val)) = (iter.next(), iter.next()) {
+                    map_pairs.push((key, val));
+                }
+                RespValue::Map(Some(Box::from(map_pairs)))
+            }
+            b'~' => {
+                // Set
+                if completed_elements.iter().any(contains_nan) {
+                    self.warn(ParseWarning::NanSetMember, pos)?;
+                }
+                if self.config.detect_duplicate_members && has_duplicates(completed_elements.iter())
+                {
+                    self.warn(ParseWarning::DuplicateSetMember, pos)?;
+                }
+                RespValue::Set(Some(Box::from(completed_elements)))
+            }
+            b'>' => {
+                // Push
+                RespValue::Push(Some(Box::from(completed_elements)))
+            }
+            b'|' => {
+                // Attributes, then the value they annotate
+                let mut elements = completed_elements;
+                let value = elements.pop().unwrap_or_default();
+                let mut attributes = Vec::with_capacity(elements.len() / 2);
+                let mut iter = elements.into_iter();
+                while let (Some(key), Some(val)) = (iter.next(), iter.next()) {
+                    attributes.push((key, val));
+                }
+                RespValue::Attribute(Box::new(Attributed { attributes, value }))
+            }
+            _ => {
+                // Default to Array (*)
+                RespValue::Array(Some(Box::from(completed_elements)))
+            }
+        })
+    }
+
+    /// Opens a streamed aggregate; its elements are read until the `.` end marker.
+    fn handle_streamed_aggregate(
+        &mut self,
+        pos: usize,
+        elements: Vec<RespValue<'static>>,
+        original_type_char: u8,
+    ) -> ParseState {
+        if self.nested_stack.len() >= self.config.max_depth {
+            let mut discard = self.owed(0);
+            discard.streamed.push(std::mem::take(&mut discard.values));
+            self.rejected_frame = Some((pos, discard));
+            return ParseState::Error(ParseError::InvalidDepth);
+        }
+        self.nested_stack
+            .push(ParseState::ReadingStreamedAggregate {
+                pos,
+                elements,
+                original_type_char,
+            });
+        if self.nested_stack.len() > self.stats.max_depth_seen {
+            self.stats.max_depth_seen = self.nested_stack.len();
+        }
+        ParseState::Index { pos }
+    }
+
+    /// Completes the innermost streamed aggregate at its `.` end marker.
+    fn end_streamed_aggregate(&mut self, index: usize) -> ParseState {
+        let Some(ParseState::ReadingStreamedAggregate {
+            elements,
+            original_type_char,
+            ..
+        }) = self.nested_stack.last_mut()
+        else {
+            return ParseState::Error(ParseError::InvalidFormat("Invalid type marker".into()));
+        };
+        if *original_type_char == b'%' && elements.len() % 2 != 0 {
+            return ParseState::Error(ParseError::InvalidFormat(
+                "Streamed map with a key but no value".into(),
+            ));
+        }
+        let next_pos = match self.terminator_at(index + 1) {
+            Some(len) => index + 1 + len,
+            None if self.buffer.len() < index + 1 + CRLF_LEN => {
+                return ParseState::Error(ParseError::UnexpectedEof);
+            }
+            None => {
+                return ParseState::Error(ParseError::InvalidFormat("Invalid end marker".into()));
+            }
+        };
+
+        let Some(ParseState::ReadingStreamedAggregate {
+            elements,
+            original_type_char,
+            ..
+        }) = self.nested_stack.pop()
+        else {
+            unreachable!("checked above");
+        };
+        match self.finish_aggregate(original_type_char, elements, next_pos) {
+            Ok(value) => ParseState::Complete(Some((value, next_pos))),
+            Err(error) => ParseState::Error(error),
+        }
+    }
+
+    /// Returns what is left of the frame past the value being read, which owes
+    /// `values` more values itself.
+    fn owed(&self, values: usize) -> Discard {
+        let mut discard = Discard::default();
+        for state in self.nested_stack.iter() {
+            match state {
+                ParseState::ReadingArray { total, current, .. } => {
+                    discard.values += total - current - 1
+                }
+                ParseState::ReadingStreamedAggregate { .. } => {
+                    discard.streamed.push(std::mem::take(&mut discard.values))
+                }
+                _ => {}
+            }
+        }
+        discard.values += values;
+        discard
+    }
+
+    /// Records `warning` for the frame being parsed, or rejects the frame in strict mode.
+    ///
+    /// `pos` is the end of the value that triggered it.
+    fn warn(&mut self, warning: ParseWarning, pos: usize) -> Result<(), ParseError> {
+        if self.config.strict {
+            self.rejected_frame = Some((pos, self.owed(0)));
+            return Err(ParseError::InvalidFormat(warning.message().into()));
+        }
+        self.warnings.push(warning);
+        Ok(())
+    }
+
+    /// Finds the end of the current line, resuming the scan at `scanned`. Returns the
+    /// end of the line's content and the position after its terminator.
+    ///
+    /// If the line is still incomplete, the scan progress is stored in the current
+    /// line state so the next call only scans newly arrived bytes.
+    #[inline(always)]
+    fn find_line_end(&mut self, scanned: usize) -> Option<(usize, usize)> {
+        let end = find_line(
+            &self.buffer,
+            scanned,
+            self.config.scanner,
+            self.config.lenient_line_endings,
+        );
+        if end.is_none() {
+            // Rescan the last byte, it may be the \r of a split CRLF
+            let resume = self.buffer.len().saturating_sub(1).max(scanned);
+            if let ParseState::ReadingSimpleString { scanned, .. }
+            | ParseState::ReadingError { scanned, .. }
+            | ParseState::ReadingInteger { scanned, .. }
+            | ParseState::ReadingDouble { scanned, .. }
+            | ParseState::ReadingBigNumber { scanned, .. }
+            | ParseState::ReadingBulkError { scanned, .. }
+            | ParseState::ReadingVerbatimString { scanned, .. } = &mut self.state
+            {
+                *scanned = resume;
+            }
         }
+        end
+    }
 
-        let required_len = start_pos + remaining + CRLF_LEN;
-        if self.buffer.len() < required_len {
-            return ParseState::Error(ParseError::NotEnoughData);
+    /// Returns the length of the line terminator at `at`, or `None` if there is none
+    /// (yet).
+    #[inline(always)]
+    fn terminator_at(&self, at: usize) -> Option<usize> {
+        match self.buffer.get(at..at + CRLF_LEN) {
+            Some(b"\r\n") => Some(CRLF_LEN),
+            _ if self.config.lenient_line_endings && self.buffer.get(at) == Some(&b'\n') => Some(1),
+            _ => None,
         }
+    }
 
-        // Check terminator first to fail fast
-        if self.buffer[start_pos + remaining] != b'\r'
-            || self.buffer[start_pos + remaining + 1] != b'\n'
-        {
-            return ParseState::Error(ParseError::InvalidFormat("Missing CRLF terminator".into()));
+    #[inline(always)]
+    fn handle_double(&mut self, pos: usize, scanned: usize) -> ParseState {
+        match self.find_line_end(scanned) {
+            Some((end_pos, next_pos)) => match std::str::from_utf8(&self.buffer[pos..end_pos]) {
+                Ok(s) => match s.parse::<f64>() {
+                    Ok(value) => ParseState::Complete(Some((RespValue::Double(value), next_pos))),
+                    Err(_) => {
+                        ParseState::Error(ParseError::InvalidFormat("Invalid double value".into()))
+                    }
+                },
+                Err(_) => ParseState::Error(ParseError::InvalidUtf8),
+            },
+            None => ParseState::Error(ParseError::UnexpectedEof),
         }
+    }
 
-        // Create string view
-        let string_slice = &self.buffer[start_pos..start_pos + remaining];
+    #[inline(always)]
+    fn handle_big_number(&mut self, pos: usize, scanned: usize) -> ParseState {
+        match self.find_line_end(scanned) {
+            Some((end_pos, next_pos)) => {
+                let bytes = &self.buffer[pos..end_pos];
 
-        // Optimize ASCII check
-        let is_ascii = string_slice.iter().all(|&b| b < 128);
+                // Digits with an optional leading sign
+                let (sign, digits) = match bytes.first() {
+                    Some(b'-' | b'+') => bytes.split_at(1),
+                    _ => (&b""[..], bytes),
+                };
+                if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+                    return ParseState::Error(ParseError::InvalidFormat(
+                        "Invalid big number format".into(),
+                    ));
+                }
 
-        // Build result efficiently based on content type
-        let result = if is_ascii {
-            // Fast path for ASCII
-            let s = unsafe { std::str::from_utf8_unchecked(string_slice) }.to_string();
-            RespValue::BulkString(Some(Cow::Owned(s)))
-        } else {
-            // Only do UTF-8 validation for non-ASCII
-            match std::str::from_utf8(string_slice) {
-                Ok(s) => RespValue::BulkString(Some(Cow::Owned(s.to_string()))),
-                Err(_) => return ParseState::Error(ParseError::InvalidUtf8),
+                let canonical =
+                    (digits.len() == 1 || digits[0] != b'0') && !(sign == b"-" && digits == b"0");
+                let number = match self.config.big_numbers {
+                    BigNumberPolicy::Keep => bytes,
+                    BigNumberPolicy::Reject if !canonical => {
+                        return ParseState::Error(ParseError::InvalidFormat(
+                            "Big number not in canonical form".into(),
+                        ));
+                    }
+                    BigNumberPolicy::Normalize if !canonical || sign == b"+" => {
+                        let trimmed = match digits.iter().position(|&b| b != b'0') {
+                            Some(first) => &digits[first..],
+                            None => &b"0"[..],
+                        };
+                        let negative = sign == b"-" && trimmed != b"0";
+                        let mut number = String::with_capacity(trimmed.len() + 1);
+                        if negative {
+                            number.push('-');
+                        }
+                        // Only ASCII digits, checked above
+                        number.extend(trimmed.iter().map(|&b| b as char));
+                        return ParseState::Complete(Some((
+                            RespValue::BigNumber(Cow::Owned(number)),
+                            next_pos,
+                        )));
+                    }
+                    _ => bytes,
+                };
+
+                match std::str::from_utf8(number) {
+                    Ok(s) => ParseState::Complete(Some((
+                        RespValue::BigNumber(Cow::Owned(s.to_string())),
+                        next_pos,
+                    ))),
+                    Err(_) => ParseState::Error(ParseError::InvalidUtf8),
+                }
             }
-        };
+            None => ParseState::Error(ParseError::UnexpectedEof),
+        }
+    }
 
-        ParseState::Complete(Some((result, start_pos + remaining + CRLF_LEN)))
+    #[inline(always)]
+    fn handle_bulk_error(&mut self, pos: usize, scanned: usize) -> ParseState {
+        match self.find_line_end(scanned) {
+            Some((end_pos, next_pos)) => {
+                let bytes = &self.buffer[pos..end_pos];
+
+                // Check for null bulk error (-1)
+                if bytes == b"-1" {
+                    return ParseState::Complete(Some((RespValue::BulkError(None), next_pos)));
+                }
+
+                match std::str::from_utf8(bytes) {
+                    Ok(s) => ParseState::Complete(Some((
+                        RespValue::BulkError(Some(Cow::Owned(s.to_string()))),
+                        next_pos,
+                    ))),
+                    Err(_) => ParseState::Error(ParseError::InvalidUtf8),
+                }
+            }
+            None => ParseState::Error(ParseError::UnexpectedEof),
+        }
     }
 
     #[inline(always)]
-    fn handle_array(
-        &mut self,
-        pos: usize,
-        total: usize,
-        current: usize,
-        elements: Vec<RespValue<'static>>,
-        original_type_char: u8, // Pass original_type_char
-    ) -> ParseState {
-        if current >= total {
-            // Check if all elements are read
-            // Completion logic moved to try_parse
-            // This state should only transition to Index or Error here
-            // If we reach here, it means we are ready to parse the next element
-            ParseState::Index { pos }
-        } else {
-            // Store current array/map state
-            self.nested_stack.push(ParseState::ReadingArray {
-                pos, // Position *after* the element we just parsed
-                total,
-                current, // Number of elements *already* parsed
-                elements,
-                original_type_char,
-            });
+    fn handle_verbatim_string(&mut self, pos: usize, scanned: usize) -> ParseState {
+        match self.find_line_end(scanned) {
+            Some((end_pos, next_pos)) => {
+                let bytes = &self.buffer[pos..end_pos];
 
-            // Start parsing next element from current position
-            ParseState::Index { pos }
+                // Check for null verbatim string (-1)
+                if bytes == b"-1" {
+                    return ParseState::Complete(Some((RespValue::VerbatimString(None), next_pos)));
+                }
+
+                match std::str::from_utf8(bytes) {
+                    Ok(s) => match Verbatim::parse(Cow::Owned(s.to_string())) {
+                        Some(verbatim) => ParseState::Complete(Some((
+                            RespValue::VerbatimString(Some(Box::new(verbatim))),
+                            next_pos,
+                        ))),
+                        None => ParseState::Error(ParseError::InvalidFormat(
+                            "Invalid verbatim string format prefix".into(),
+                        )),
+                    },
+                    Err(_) => ParseState::Error(ParseError::InvalidUtf8),
+                }
+            }
+            None => ParseState::Error(ParseError::UnexpectedEof),
         }
     }
 
     #[inline(always)]
-    fn handle_simple_string(&mut self, pos: usize) -> ParseState {
-        match self.find_crlf(pos) {
-            Some(end_pos) => {
+    fn handle_simple_string(&mut self, pos: usize, scanned: usize) -> ParseState {
+        match self.find_line_end(scanned) {
+            Some((end_pos, next_pos)) => {
                 let bytes = &self.buffer[pos..end_pos];
 
                 // Validate no CR/LF in simple strings per RESP3 spec
@@ -613,41 +2457,39 @@ impl Parser {
                     ));
                 }
 
-                // Use from_utf8_lossy to directly create Cow<str>
-                let string = String::from_utf8_lossy(bytes).into_owned();
+                let string = match self.config.intern.get(bytes) {
+                    Some(interned) => Cow::Borrowed(interned),
+                    // Use from_utf8_lossy to directly create Cow<str>
+                    None => Cow::Owned(String::from_utf8_lossy(bytes).into_owned()),
+                };
 
-                ParseState::Complete(Some((
-                    RespValue::SimpleString(Cow::Owned(string)),
-                    end_pos + CRLF_LEN,
-                )))
+                ParseState::Complete(Some((RespValue::SimpleString(string), next_pos)))
             }
             None => ParseState::Error(ParseError::UnexpectedEof),
         }
     }
 
     #[inline(always)]
-    fn handle_error(&mut self, pos: usize) -> ParseState {
-        match self.find_crlf(pos) {
-            Some(end_pos) => {
+    fn handle_error(&mut self, pos: usize, scanned: usize) -> ParseState {
+        match self.find_line_end(scanned) {
+            Some((end_pos, next_pos)) => {
                 let bytes = &self.buffer[pos..end_pos];
 
                 // Use from_utf8_lossy to directly create Cow<str>
                 let error = String::from_utf8_lossy(bytes).into_owned();
 
-                ParseState::Complete(Some((
-                    RespValue::Error(Cow::Owned(error)),
-                    end_pos + CRLF_LEN,
-                )))
+                ParseState::Complete(Some((RespValue::Error(Cow::Owned(error)), next_pos)))
             }
             None => ParseState::Error(ParseError::UnexpectedEof),
         }
     }
 
     #[inline(always)]
-    fn handle_integer(&mut self, pos: usize) -> ParseState {
-        match self.find_crlf(pos) {
-            Some(end_pos) => {
-                let bytes = &self.buffer[pos..end_pos];
+    fn handle_integer(&mut self, pos: usize, scanned: usize) -> ParseState {
+        match self.find_line_end(scanned) {
+            Some((end_pos, next_pos)) => {
+                #[allow(unused_mut)]
+                let mut bytes = &self.buffer[pos..end_pos];
 
                 // Check for explicit plus sign
                 let explicit_plus = bytes.first() == Some(&b'+');
@@ -677,7 +2519,6 @@ impl Parser {
                 // Use the potentially modified 'bytes' slice
                 if bytes.len() <= 19 {
                     // Adjusted length check slightly for safety with i64
-                    let mut value: i64 = 0;
                     let mut start = 0;
                     let negative = bytes.first() == Some(&b'-');
 
@@ -698,19 +2539,21 @@ impl Parser {
                         ));
                     }
 
-                    for &byte in &bytes[start..] {
-                        if !(b'0'..=b'9').contains(&byte) {
-                            // Simplified check
-                            return ParseState::Error(ParseError::InvalidFormat(
-                                "Invalid character in integer".into(),
-                            ));
-                        }
-                        // Check for potential overflow before multiplication
-                        if value > (i64::MAX - (byte - b'0') as i64) / 10 {
-                            return ParseState::Error(ParseError::Overflow);
-                        }
-                        value = value * 10 + (byte - b'0') as i64;
+                    let digits = &bytes[start..];
+                    let scanner = self.config.scanner;
+                    if scanner.digit_run(digits) != digits.len() {
+                        return ParseState::Error(ParseError::InvalidFormat(
+                            "Invalid character in integer".into(),
+                        ));
                     }
+                    let mut value = match scanner
+                        .parse_digits(digits)
+                        .ok()
+                        .and_then(|digits| i64::try_from(digits).ok())
+                    {
+                        Some(value) => value,
+                        None => return ParseState::Error(ParseError::Overflow),
+                    };
 
                     // Apply sign if negative
                     if negative {
@@ -723,10 +2566,7 @@ impl Parser {
                         }
                     }
 
-                    return ParseState::Complete(Some((
-                        RespValue::Integer(value),
-                        end_pos + CRLF_LEN,
-                    )));
+                    return ParseState::Complete(Some((RespValue::Integer(value), next_pos)));
                 }
 
                 // Fallback to atoi for potentially larger strings (or if fast path logic needs refinement)
@@ -741,10 +2581,7 @@ impl Parser {
                         #[cfg(feature = "explicit-positive-sign")]
                         {
                             // If atoi parsed successfully, it should be the correct value
-                            ParseState::Complete(Some((
-                                RespValue::Integer(value),
-                                end_pos + CRLF_LEN,
-                            )))
+                            ParseState::Complete(Some((RespValue::Integer(value), next_pos)))
                         }
                         #[cfg(not(feature = "explicit-positive-sign"))]
                         {
@@ -756,10 +2593,7 @@ impl Parser {
                                     "Internal error: explicit '+' parsed unexpectedly".into(),
                                 ))
                             } else {
-                                ParseState::Complete(Some((
-                                    RespValue::Integer(value),
-                                    end_pos + CRLF_LEN,
-                                )))
+                                ParseState::Complete(Some((RespValue::Integer(value), next_pos)))
                             }
                         }
                     }
@@ -772,12 +2606,48 @@ impl Parser {
         }
     }
 
-    /// Clears the parser's internal buffer and resets the state.
+    /// Drops the first `pos` bytes of the buffer and resets the state.
+    ///
+    /// `pos` is the end position of the last consumed frame, so the next parse
+    /// attempt starts at the head of the buffer.
     pub fn clear_buffer(&mut self, pos: usize) {
-        self.state = ParseState::Index { pos };
+        self.buffer.advance(pos.min(self.buffer.len()));
+        self.frame_offset = 0;
+        self.frame_memory = 0;
+        self.state = ParseState::Index { pos: 0 };
         self.nested_stack.clear();
     }
 
+    /// Returns the parser to the state of a fresh one with the same configuration,
+    /// keeping its buffer and stack allocations for reuse; see `pool::ParserPool`.
+    ///
+    /// Buffered input, warnings, statistics and decode hooks are dropped.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.clear_buffer(0);
+        self.resyncing = false;
+        self.rejected_frame = None;
+        self.discard = None;
+        self.warnings.clear();
+        self.last_error_context = None;
+        self.next_frame = None;
+        self.stats = ParserStats::default();
+        self.hooks = DecodeHooks::default();
+    }
+
+    /// Asserts that a new frame starts exactly where the previous one ended.
+    ///
+    /// Consumed bytes are dropped after every frame, so between frames the parser
+    /// must always point at the head of the buffer.
+    #[cfg(debug_assertions)]
+    fn check_frame_boundary(&self) {
+        if let ParseState::Index { pos } = self.state
+            && self.nested_stack.is_empty()
+        {
+            debug_assert_eq!(pos, 0, "frame does not start at the head of the buffer");
+        }
+    }
+
     /// Attempts to parse the data in the buffer and returns a `ParseResult`.
     ///
     /// This method will iterate through the buffer, checking for maximum iterations and depth.
@@ -788,42 +2658,383 @@ impl Parser {
     ///
     /// # Errors
     ///
-    /// Returns `ParseError::InvalidFormat` if the maximum number of iterations is exceeded.
+    /// Returns `ParseError::IterationBudgetExceeded` if `ParserConfig::max_iterations`
+    /// runs out.
     /// Returns `ParseError::InvalidDepth` if the maximum nested depth is exceeded.
     pub fn try_parse(&mut self) -> ParseResult {
+        self.try_parse_with_len()
+            .map(|frame| frame.map(|(value, _)| value))
+    }
+
+    /// Same as `try_parse`, also returning how many input bytes the frame took up.
+    ///
+    /// The lengths of consecutive frames add up to the input consumed, so they can be
+    /// used to account for traffic or to slice the original byte stream per frame.
+    /// Bytes skipped while recovering from errors belong to no frame; see
+    /// `skipped_bytes`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::parser::Parser;
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let mut parser = Parser::new(10, 1024);
+    /// parser.read_buf(b"+OK\r\n:12\r\n");
+    /// assert_eq!(
+    ///     parser.try_parse_with_len(),
+    ///     Ok(Some((RespValue::SimpleString("OK".into()), 5)))
+    /// );
+    /// assert_eq!(
+    ///     parser.try_parse_with_len(),
+    ///     Ok(Some((RespValue::Integer(12), 5)))
+    /// );
+    /// ```
+    pub fn try_parse_with_len(
+        &mut self,
+    ) -> Result<Option<(RespValue<'static>, usize)>, ParseError> {
+        self.next_frame = None;
+        self.observe_buffer();
+        #[cfg(debug_assertions)]
+        self.check_frame_boundary();
+
+        loop {
+            if self.resyncing && !self.resync() {
+                return Err(ParseError::UnexpectedEof);
+            }
+            if self.discard.is_some() {
+                self.discard_rejected()?;
+            }
+
+            match self.parse_frame() {
+                Ok(Some((value, end))) => {
+                    let frame_len = self.frame_offset + end;
+                    self.stats.frames_parsed += 1;
+                    self.stats.bytes_consumed += frame_len as u64;
+                    self.observe_frame_size(frame_len);
+                    if !self.hooks.is_empty() {
+                        self.hooks.run(&value, frame_len);
+                    }
+                    self.clear_buffer(end);
+                    if self.config.speculate {
+                        self.next_frame = Some(self.prevalidate());
+                    }
+                    return Ok(Some((value, frame_len)));
+                }
+                Ok(None) => return Ok(None),
+                Err(error) if error.is_recoverable() => {
+                    self.stats.parse_errors += 1;
+                    self.stats.parse_errors_by_kind.record(&error);
+                    if self.config.detailed_errors {
+                        self.capture_error_context();
+                    }
+                    if let Some((pos, discard)) = self.rejected_frame.take() {
+                        // The frame's extent is known, so drop exactly its remainder
+                        self.clear_buffer(pos);
+                        self.discard = Some(discard);
+                        if !self.config.recover_errors {
+                            return Err(error);
+                        }
+                        continue;
+                    }
+                    if !self.config.recover_errors {
+                        return Err(error);
+                    }
+                    debug!("Recovering from parse error: {}", error);
+                    self.clear_buffer(0);
+                    self.resyncing = true;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Same as `try_parse`, but parses straight out of `buf` instead of the parser's own
+    /// buffer, advancing `buf` past every frame returned.
+    ///
+    /// For frameworks that already keep a read buffer per connection. Between calls,
+    /// only append to `buf`: a partially parsed frame refers to its bytes by position,
+    /// and elements of it may already have been advanced past. The parser's own buffer
+    /// must not hold a partial frame at the same time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use stream_resp::parser::{ParseError, Parser};
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let mut parser = Parser::new(10, 1024);
+    /// let mut buf = BytesMut::from(&b"+OK\r\n:1"[..]);
+    /// assert_eq!(
+    ///     parser.parse_in_place(&mut buf),
+    ///     Ok(Some(RespValue::SimpleString("OK".into())))
+    /// );
+    /// assert_eq!(parser.parse_in_place(&mut buf), Err(ParseError::UnexpectedEof));
+    /// buf.extend_from_slice(b"\r\n");
+    /// assert_eq!(parser.parse_in_place(&mut buf), Ok(Some(RespValue::Integer(1))));
+    /// assert!(buf.is_empty());
+    /// ```
+    pub fn parse_in_place(&mut self, buf: &mut BytesMut) -> ParseResult {
+        debug_assert!(
+            self.buffer.is_empty(),
+            "parse_in_place with input left in the parser's own buffer"
+        );
+        std::mem::swap(&mut self.buffer, buf);
+        let result = self.try_parse();
+        std::mem::swap(&mut self.buffer, buf);
+        result
+    }
+
+    /// Updates the peak buffer size; input only arrives between calls that consume it.
+    #[inline(always)]
+    fn observe_buffer(&mut self) {
+        let buffered = self.buffer.len() as u64;
+        if buffered > self.stats.peak_buffered_bytes {
+            self.stats.peak_buffered_bytes = buffered;
+        }
+    }
+
+    /// Records where the current state failed, for `ParseError::detailed`.
+    fn capture_error_context(&mut self) {
+        let (pos, state) = self.state.describe();
+        let pos = pos.min(self.buffer.len());
+        let snippet_end = (pos + ERROR_SNIPPET_LEN).min(self.buffer.len());
+        self.last_error_context = Some(ErrorContext {
+            offset: self.frame_offset + pos,
+            snippet: self.buffer[pos..snippet_end].to_vec(),
+            state,
+            depth: self.nested_stack.len(),
+        });
+    }
+
+    /// Returns what speculative validation found out about the next buffered frame.
+    ///
+    /// Only available with `ParserConfig::speculate`, right after `try_parse` returned
+    /// a frame and until the next `try_parse` call. Returns `None` otherwise, or once
+    /// new input may have changed an `Incomplete` result.
+    pub fn peek(&self) -> Option<&NextFrame> {
+        self.next_frame.as_ref()
+    }
+
+    /// Validates the framing of the next buffered frame without decoding it.
+    fn prevalidate(&self) -> NextFrame {
+        if self.resyncing || self.discard.is_some() {
+            return NextFrame::Incomplete;
+        }
+        match frame_end(&self.buffer, 0, &self.config) {
+            Ok(len) => NextFrame::Complete { len },
+            Err(ParseError::UnexpectedEof | ParseError::NotEnoughData) => NextFrame::Incomplete,
+            Err(error) => NextFrame::Invalid(error),
+        }
+    }
+
+    /// Drops bytes up to the next plausible frame boundary: a CRLF followed by a
+    /// valid type marker.
+    ///
+    /// Returns `false` if no boundary is buffered yet; the scan resumes on the next call.
+    fn resync(&mut self) -> bool {
+        let mut from = 0;
+        loop {
+            match find_crlf(&self.buffer, from) {
+                Some(crlf) if crlf + CRLF_LEN < self.buffer.len() => {
+                    if is_type_marker(self.buffer[crlf + CRLF_LEN]) {
+                        self.skip(crlf + CRLF_LEN);
+                        self.resyncing = false;
+                        return true;
+                    }
+                    from = crlf + 1;
+                }
+                Some(crlf) => {
+                    // The byte after this CRLF has not arrived yet
+                    self.skip(crlf);
+                    return false;
+                }
+                None => {
+                    // Keep a trailing \r that may start the next CRLF
+                    let keep = usize::from(self.buffer.last() == Some(&b'\r'));
+                    self.skip(self.buffer.len() - keep);
+                    return false;
+                }
+            }
+        }
+    }
+
+    /// Drops the remaining values of a rejected frame without decoding them.
+    ///
+    /// Only a count of owed values is kept, so arbitrarily deep remainders are dropped
+    /// without allocating, unless they hold streamed aggregates.
+    fn discard_rejected(&mut self) -> Result<(), ParseError> {
+        while let Some(discard) = self.discard.as_mut() {
+            if discard.payload > 0 {
+                let dropped = discard.payload.min(self.buffer.len());
+                self.buffer.advance(dropped);
+                discard.payload -= dropped;
+                if discard.payload > 0 {
+                    return Err(ParseError::NotEnoughData);
+                }
+                continue;
+            }
+            if discard.values == 0 && discard.streamed.is_empty() {
+                self.discard = None;
+                break;
+            }
+
+            let marker = *self.buffer.first().ok_or(ParseError::UnexpectedEof)?;
+            let line_end = find_crlf(&self.buffer, 1).ok_or(ParseError::UnexpectedEof)?;
+            let line = &self.buffer[1..line_end];
+            if discard.values > 0 {
+                discard.values -= 1;
+            } else if marker == b'.' {
+                discard.values = discard.streamed.pop().unwrap_or_default();
+            }
+            // Otherwise this is an element of the innermost streamed aggregate, which
+            // still owes its end marker
+            match marker {
+                // A streamed string owes its chunks, each non-empty one owing another
+                b'$' if line == b"?" => discard.values += 1,
+                b';' => {
+                    let len = parse_decimal(line)?;
+                    if len > 0 {
+                        discard.values += 1;
+                        discard.payload = len as usize + CRLF_LEN;
+                    }
+                }
+                b'=' if self.config.legacy_verbatim_strings => {}
+                b'!' if self.config.legacy_bulk_errors => {}
+                b'$' | b'=' | b'!' => {
+                    let len = parse_decimal(line)?;
+                    if len >= 0 {
+                        discard.payload = len as usize + CRLF_LEN;
+                    }
+                }
+                b'*' | b'%' | b'~' if line == b"?" => {
+                    discard.streamed.push(std::mem::take(&mut discard.values))
+                }
+                b'*' | b'~' | b'>' => discard.values += parse_decimal(line)?.max(0) as usize,
+                b'%' => discard.values += 2 * parse_decimal(line)?.max(0) as usize,
+                b'|' => discard.values += 2 * parse_decimal(line)?.max(0) as usize + 1,
+                _ => {}
+            }
+            self.buffer.advance(line_end + CRLF_LEN);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn skip(&mut self, len: usize) {
+        self.buffer.advance(len);
+        self.stats.skipped_bytes += len as u64;
+    }
+
+    /// Parses every complete frame in the buffer, handing each one to `handler`.
+    ///
+    /// Frames are delivered in order as soon as they are complete; a trailing partial
+    /// frame stays buffered for the next call. This avoids the per-frame `try_parse`
+    /// call and return in tight server loops.
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of frames delivered to `handler`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error other than running out of data. Frames delivered before the
+    /// error are not affected.
+    pub fn parse_with<F>(&mut self, mut handler: F) -> Result<usize, ParseError>
+    where
+        F: FnMut(RespValue<'static>),
+    {
+        let mut frames = 0;
+        loop {
+            match self.try_parse() {
+                Ok(Some(value)) => {
+                    handler(value);
+                    frames += 1;
+                }
+                Ok(None) | Err(ParseError::UnexpectedEof) | Err(ParseError::NotEnoughData) => {
+                    return Ok(frames);
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Adds `bytes` to the estimated heap size of the current frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::MemoryBudgetExceeded` once the frame goes over
+    /// `ParserConfig::max_frame_memory`.
+    #[inline(always)]
+    fn charge(&mut self, bytes: usize) -> Result<(), ParseError> {
+        Self::charge_frame(&mut self.frame_memory, &self.config, bytes)
+    }
+
+    // Split from `charge` for callers holding a borrow of the nested stack
+    #[inline(always)]
+    fn charge_frame(
+        frame_memory: &mut usize,
+        config: &ParserConfig,
+        bytes: usize,
+    ) -> Result<(), ParseError> {
+        if let Some(budget) = config.max_frame_memory {
+            *frame_memory = frame_memory.saturating_add(bytes);
+            if *frame_memory > budget {
+                return Err(ParseError::MemoryBudgetExceeded);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the state machine until a frame completes, returning it with its end position.
+    ///
+    /// The buffer is left untouched; consuming the frame is up to the caller.
+    fn parse_frame(&mut self) -> Result<Option<(RespValue<'static>, usize)>, ParseError> {
         let mut iterations = 0;
+        let max_iterations = self.config.max_iterations.unwrap_or(usize::MAX);
 
         loop {
             iterations += 1;
-            if iterations > MAX_ITERATIONS {
-                return Err(ParseError::InvalidFormat(
-                    "Maximum parsing iterations exceeded".into(),
-                ));
+            if iterations > max_iterations {
+                // The state is kept, so the next call resumes here
+                return Err(ParseError::IterationBudgetExceeded);
             }
 
-            // Check max Depth
-            if self.nested_stack.len() > self.max_depth {
-                return Err(ParseError::InvalidDepth);
-            }
+            debug_assert!(self.nested_stack.len() <= self.config.max_depth);
 
             debug!(
-                "{:?} | state={:?} | buffer={:?} | nested_len:{:?}",
+                "{:?} | state={:?} | buffered={} | nested_len:{:?}",
                 iterations,
                 self.state,
-                String::from_utf8_lossy(&self.buffer),
+                self.buffer.len(),
                 self.nested_stack.len()
             );
 
-            let current_state = self.state.clone();
-            let next_state = match current_state {
+            // States owning values are moved out rather than cloned; the others are
+            // copied so an incomplete value keeps its state for the next call
+            let next_state = match self.state {
                 ParseState::Index { pos } => self.handle_index(pos),
-                ParseState::ReadingArray {
-                    pos,
-                    total,
-                    current,
-                    elements,
-                    original_type_char, // Pass to handler
-                } => self.handle_array(pos, total, current, elements, original_type_char),
+                ParseState::ReadingArray { .. }
+                | ParseState::ReadingStreamedAggregate { .. }
+                | ParseState::Error(_)
+                | ParseState::Complete(_) => {
+                    match std::mem::replace(&mut self.state, ParseState::Index { pos: 0 }) {
+                        ParseState::ReadingArray {
+                            pos,
+                            total,
+                            current,
+                            elements,
+                            original_type_char, // Pass to handler
+                        } => self.handle_array(pos, total, current, elements, original_type_char),
+                        ParseState::ReadingStreamedAggregate {
+                            pos,
+                            elements,
+                            original_type_char,
+                        } => self.handle_streamed_aggregate(pos, elements, original_type_char),
+                        owned => owned,
+                    }
+                }
                 ParseState::ReadingLength {
                     pos,
                     value,
@@ -833,16 +3044,58 @@ impl Parser {
                 ParseState::ReadingBulkString {
                     start_pos,
                     remaining,
-                } => self.handle_bulk_string(start_pos, remaining),
-                ParseState::ReadingSimpleString { pos } => self.handle_simple_string(pos),
-                ParseState::ReadingError { pos } => self.handle_error(pos),
-                ParseState::ReadingInteger { pos } => self.handle_integer(pos),
-                ParseState::Error(error) => ParseState::Error(error),
-                ParseState::Complete(value) => ParseState::Complete(value),
+                    type_char,
+                } => self.handle_bulk_string(start_pos, remaining, type_char),
+                ParseState::StreamingBulkString {
+                    pos,
+                    len,
+                    remaining,
+                } => self.handle_streamed_bulk_string(pos, len, remaining),
+                ParseState::ReadingStreamedString { start, pos, len } => {
+                    self.handle_streamed_string(start, pos, len)
+                }
+                ParseState::ReadingSimpleString { pos, scanned } => {
+                    self.handle_simple_string(pos, scanned)
+                }
+                ParseState::ReadingError { pos, scanned } => self.handle_error(pos, scanned),
+                ParseState::ReadingInteger { pos, scanned } => self.handle_integer(pos, scanned),
+                ParseState::ReadingDouble { pos, scanned } => self.handle_double(pos, scanned),
+                ParseState::ReadingBigNumber { pos, scanned } => {
+                    self.handle_big_number(pos, scanned)
+                }
+                ParseState::ReadingBulkError { pos, scanned } => {
+                    self.handle_bulk_error(pos, scanned)
+                }
+                ParseState::ReadingVerbatimString { pos, scanned } => {
+                    self.handle_verbatim_string(pos, scanned)
+                }
             };
 
             match next_state {
                 ParseState::Complete(Some((value, pos))) => {
+                    if let Err(error) = self.charge(payload_size(&value)) {
+                        self.rejected_frame = Some((pos, self.owed(0)));
+                        return Err(error);
+                    }
+                    if let Some(ParseState::ReadingStreamedAggregate { elements, .. }) =
+                        self.nested_stack.last_mut()
+                    {
+                        if elements.len() == elements.capacity() {
+                            let additional = elements.len().max(1);
+                            elements.reserve_exact(additional);
+                            if let Err(error) = Self::charge_frame(
+                                &mut self.frame_memory,
+                                &self.config,
+                                additional * std::mem::size_of::<RespValue>(),
+                            ) {
+                                self.rejected_frame = Some((pos, self.owed(0)));
+                                return Err(error);
+                            }
+                        }
+                        elements.push(value);
+                        self.state = ParseState::Index { pos };
+                        continue;
+                    }
                     // Check if we are inside a nested structure (Array or Map)
                     if let Some(ParseState::ReadingArray {
                         total,
@@ -851,6 +3104,20 @@ impl Parser {
                         ..
                     }) = self.nested_stack.last_mut()
                     {
+                        if elements.len() == elements.capacity() {
+                            // Double, but never reserve past the declared length
+                            let remaining = *total - elements.len();
+                            let additional = remaining.min(elements.len().max(1));
+                            elements.reserve_exact(additional);
+                            if let Err(error) = Self::charge_frame(
+                                &mut self.frame_memory,
+                                &self.config,
+                                additional * std::mem::size_of::<RespValue>(),
+                            ) {
+                                self.rejected_frame = Some((pos, self.owed(0)));
+                                return Err(error);
+                            }
+                        }
                         elements.push(value);
                         *current += 1;
 
@@ -860,7 +3127,7 @@ impl Parser {
                             continue;
                         } else {
                             // Array/Map/Set/Push is complete, pop it from the stack
-                            let mut completed_elements = Vec::new();
+                            let completed_elements: Vec<RespValue<'static>>;
                             let finished_type_char: u8;
 
                             // Pop the completed ReadingArray state
@@ -879,36 +3146,12 @@ impl Parser {
                                 ));
                             }
 
-                            // Construct the final value (Array, Map, Set, or Push)
-                            let completed_result = match finished_type_char {
-                                b'%' => {
-                                    // Map
-                                    let mut map_pairs =
-                                        Vec::with_capacity(completed_elements.len() / 2);
-                                    let mut iter = completed_elements.into_iter();
-                                    while let (Some(key), Some(val)) = (iter.next(), iter.next()) {
-                                        map_pairs.push((key, val));
-                                    }
-                                    RespValue::Map(Some(map_pairs))
-                                }
-                                b'~' => {
-                                    // Set
-                                    RespValue::Set(Some(completed_elements))
-                                }
-                                b'>' => {
-                                    // Push
-                                    RespValue::Push(Some(completed_elements))
-                                }
-                                _ => {
-                                    // Default to Array (*)
-                                    RespValue::Array(Some(completed_elements))
-                                }
-                            };
+                            let completed_result =
+                                self.finish_aggregate(finished_type_char, completed_elements, pos)?;
 
                             // If the stack is now empty, this is the final result
                             if self.nested_stack.is_empty() {
-                                self.clear_buffer(pos);
-                                return Ok(Some(completed_result));
+                                return Ok(Some((completed_result, pos)));
                             } else {
                                 // Otherwise, this completed structure is an element of the parent structure
                                 // Push it back onto the parent's state (which is now on top of the stack)
@@ -920,8 +3163,7 @@ impl Parser {
                     } else {
                         // Not in a nested structure, this is the final result
                         if self.nested_stack.is_empty() {
-                            self.clear_buffer(pos);
-                            return Ok(Some(value));
+                            return Ok(Some((value, pos)));
                         } else {
                             // This case might indicate an issue, e.g., completing a value when stack isn't empty but top isn't ReadingArray
                             return Err(ParseError::InvalidFormat(
@@ -955,6 +3197,107 @@ impl Parser {
             }
         }
     }
+
+    /// Splits off exactly one complete frame as raw, undecoded bytes.
+    ///
+    /// Framing is validated (type markers, lengths and CRLF terminators) but no
+    /// `RespValue` is built, which makes this much cheaper for pass-through proxies
+    /// that only need frame boundaries. The returned `Bytes` shares the parser's
+    /// allocation and the frame is consumed from the buffer. The rest of a frame
+    /// `try_parse` rejected, and input skipped while recovering from an error, is
+    /// dropped first, as `try_parse` would.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::UnexpectedEof` or `ParseError::NotEnoughData` if the frame
+    /// is incomplete, and `ParseError::InvalidFormat` if a value is already partially
+    /// parsed through `try_parse`.
+    pub fn split_frame(&mut self) -> Result<Bytes, ParseError> {
+        self.next_frame = None;
+        self.observe_buffer();
+        // Bytes pending error recovery are never part of a frame
+        if self.resyncing && !self.resync() {
+            return Err(ParseError::UnexpectedEof);
+        }
+        if self.discard.is_some() {
+            self.discard_rejected()?;
+        }
+        let start = match self.state {
+            ParseState::Index { pos } if self.nested_stack.is_empty() => pos,
+            _ => {
+                return Err(ParseError::InvalidFormat(
+                    "Cannot split a frame while a value is partially parsed".into(),
+                ));
+            }
+        };
+
+        let end = self.frame_end(start)?;
+
+        // Drop the already consumed prefix, then hand out the frame itself
+        let _ = self.buffer.split_to(start);
+        let frame = self.buffer.split_to(end - start).freeze();
+        self.state = ParseState::Index { pos: 0 };
+        self.stats.frames_parsed += 1;
+        self.stats.bytes_consumed += frame.len() as u64;
+        self.observe_frame_size(frame.len());
+        Ok(frame)
+    }
+
+    /// Finds the end position of the frame starting at `start` without decoding it.
+    fn frame_end(&self, start: usize) -> Result<usize, ParseError> {
+        frame_end(&self.buffer, start, &self.config)
+    }
+
+    /// Returns `true` if the buffered input ends inside the frame in progress, so
+    /// parsing can't complete it before more is read.
+    ///
+    /// Returns `false` when that can't be told from the buffer, e.g. once the head
+    /// of the frame has already been dropped from it.
+    #[cfg(feature = "codec")]
+    pub(crate) fn needs_input(&self) -> bool {
+        self.frame_offset == 0
+            && !self.resyncing
+            && self.discard.is_none()
+            && matches!(
+                self.frame_end(0),
+                Err(ParseError::UnexpectedEof | ParseError::NotEnoughData)
+            )
+    }
+
+    /// Parses the next complete frame without consuming it.
+    ///
+    /// The buffer, the parse state and any partially built aggregates are restored
+    /// afterwards, so the following `try_parse` call returns the same frame again.
+    /// This lets a dispatcher inspect a command before deciding who should consume it.
+    ///
+    /// # Returns
+    ///
+    /// Returns the same `ParseResult` that `try_parse` would return at this point.
+    pub fn peek_frame(&mut self) -> ParseResult {
+        // Bytes pending error recovery are never part of a frame
+        if self.resyncing && !self.resync() {
+            return Err(ParseError::UnexpectedEof);
+        }
+        if self.discard.is_some() {
+            self.discard_rejected()?;
+        }
+
+        let saved_state = self.state.clone();
+        let saved_stack = self.nested_stack.clone();
+        let saved_warnings = self.warnings.len();
+        let saved_memory = self.frame_memory;
+
+        self.peeking = true;
+        let result = self.parse_frame();
+        self.peeking = false;
+
+        self.state = saved_state;
+        self.nested_stack = saved_stack;
+        self.warnings.truncate(saved_warnings);
+        self.frame_memory = saved_memory;
+        self.rejected_frame = None;
+        result.map(|frame| frame.map(|(value, _)| value))
+    }
 }
 
 //EOF
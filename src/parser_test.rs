@@ -1,10 +1,15 @@
-use crate::parser::{ParseError, Parser};
-use crate::resp::RespValue;
+use crate::parser::{
+    BigNumberPolicy, BulkChunk, ErrorContext, FixedDepthParser, NextFrame, ParseError,
+    ParseWarning, Parser, ParserConfig, Protocol,
+};
+use crate::resp::{Attributed, RespKind, RespValue, Verbatim};
+use bytes::{Buf, Bytes, BytesMut};
 use std::borrow::Cow;
 use tracing::Level;
 use tracing_subscriber::FmtSubscriber;
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
     use super::*;
 
@@ -237,7 +242,7 @@ mod tests {
             Ok(None) => panic!("Expected complete value"),
             Err(e) => panic!("Parse error: {:?}", e),
         };
-        // The parser currently keeps leading zeros based on implementation
+        // Kept as sent by default, see `test_big_number_policies`
         assert_eq!(result, RespValue::BigNumber(Cow::Borrowed("00123")));
 
         // Invalid format (non-digit)
@@ -251,7 +256,7 @@ mod tests {
         let mut parser = Parser::new(100, 1000);
 
         // With error message
-        parser.read_buf(b"!Error details\r\n");
+        parser.read_buf(b"!13\r\nError details\r\n");
         let result = match parser.try_parse() {
             Ok(Some(val)) => val,
             Ok(None) => panic!("Expected complete value"),
@@ -276,7 +281,7 @@ mod tests {
     fn test_verbatim_string() {
         let mut parser = Parser::new(100, 1000);
 
-        parser.read_buf(b"=txt:Some verbatim text\r\n");
+        parser.read_buf(b"=22\r\ntxt:Some verbatim text\r\n");
         let result = match parser.try_parse() {
             Ok(Some(val)) => val,
             Ok(None) => panic!("Expected complete value"),
@@ -284,7 +289,7 @@ mod tests {
         };
         assert_eq!(
             result,
-            RespValue::VerbatimString(Some(Cow::Borrowed("txt:Some verbatim text")))
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt("Some verbatim text"))))
         );
 
         // Null verbatim string
@@ -297,7 +302,7 @@ mod tests {
         assert_eq!(result, RespValue::VerbatimString(None));
 
         // Empty content (valid)
-        parser.read_buf(b"=txt:\r\n");
+        parser.read_buf(b"=4\r\ntxt:\r\n");
         let result = match parser.try_parse() {
             Ok(Some(val)) => val,
             Ok(None) => panic!("Expected complete value"),
@@ -305,7 +310,7 @@ mod tests {
         };
         assert_eq!(
             result,
-            RespValue::VerbatimString(Some(Cow::Borrowed("txt:")))
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt(""))))
         );
     }
 
@@ -321,7 +326,7 @@ mod tests {
         };
         assert_eq!(
             result,
-            RespValue::Map(Some(vec![
+            RespValue::Map(Some(Box::from(vec![
                 (
                     RespValue::SimpleString(Cow::Borrowed("key1")),
                     RespValue::Integer(123)
@@ -330,7 +335,7 @@ mod tests {
                     RespValue::SimpleString(Cow::Borrowed("key2")),
                     RespValue::BulkString(Some(Cow::Borrowed("value")))
                 )
-            ]))
+            ])))
         );
 
         // Map with odd number of elements (should fail)
@@ -347,7 +352,7 @@ mod tests {
         // This input represents a valid map with 3 pairs.
         assert_eq!(
             result,
-            Ok(Some(RespValue::Map(Some(vec![
+            Ok(Some(RespValue::Map(Some(Box::from(vec![
                 (
                     RespValue::SimpleString(Cow::Borrowed("key1")),
                     RespValue::Integer(1)
@@ -360,7 +365,7 @@ mod tests {
                     RespValue::SimpleString(Cow::Borrowed("key3")),
                     RespValue::Integer(3)
                 ),
-            ])))),
+            ]))))),
             "Failed to parse valid map with 3 pairs, got {:?}",
             result
         );
@@ -368,7 +373,7 @@ mod tests {
         // Empty Map
         parser.read_buf(b"%0\r\n");
         let result = parser.try_parse();
-        assert_eq!(result, Ok(Some(RespValue::Map(Some(vec![])))));
+        assert_eq!(result, Ok(Some(RespValue::Map(Some(Box::default())))));
 
         // Null Map
         parser.read_buf(b"%-1\r\n");
@@ -380,7 +385,7 @@ mod tests {
         let result = parser.try_parse();
         assert_eq!(
             result,
-            Ok(Some(RespValue::Map(Some(vec![
+            Ok(Some(RespValue::Map(Some(Box::from(vec![
                 (
                     RespValue::SimpleString(Cow::Borrowed("key1")),
                     RespValue::Null
@@ -389,7 +394,7 @@ mod tests {
                     RespValue::SimpleString(Cow::Borrowed("key2")),
                     RespValue::BulkString(Some(Cow::Borrowed("")))
                 )
-            ]))))
+            ])))))
         );
     }
 
@@ -405,17 +410,20 @@ mod tests {
         };
         assert_eq!(
             result,
-            RespValue::Set(Some(vec![
+            RespValue::Set(Some(Box::from(vec![
                 RespValue::Integer(1),
                 RespValue::SimpleString(Cow::Borrowed("two")),
                 RespValue::BulkString(Some(Cow::Borrowed("three")))
-            ]))
+            ])))
         );
 
         // Test Empty Set ~0\r\n
         parser = Parser::new(100, 1000);
         parser.read_buf(b"~0\r\n");
-        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Set(Some(vec![])))));
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Set(Some(Box::default()))))
+        );
 
         // Test Null Set ~-1\r\n
         parser = Parser::new(100, 1000);
@@ -435,16 +443,19 @@ mod tests {
         };
         assert_eq!(
             result,
-            RespValue::Push(Some(vec![
+            RespValue::Push(Some(Box::from(vec![
                 RespValue::SimpleString(Cow::Borrowed("message")),
                 RespValue::Integer(42)
-            ]))
+            ])))
         );
 
         // Test Empty Push >0\r\n
         parser = Parser::new(100, 1000);
         parser.read_buf(b">0\r\n");
-        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Push(Some(vec![])))));
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Push(Some(Box::default()))))
+        );
 
         // Test Null Push >-1\r\n
         parser = Parser::new(100, 1000);
@@ -728,14 +739,33 @@ mod tests {
             other => panic!("Expected None for incomplete data, got {:?}", other),
         }
 
+        // The rest of the rejected frame is dropped, leaving the parser usable
         shallow_parser.read_buf(b"+OK\r\n");
         match shallow_parser.try_parse() {
-            Err(ParseError::InvalidDepth) => (), // Expected error
+            Err(ParseError::UnexpectedEof) => (),
             other => panic!(
-                "Expected InvalidFormat error for exceeding maximum depth, got {:?}",
+                "Expected the rejected frame to be discarded, got {:?}",
                 other
             ),
         }
+
+        shallow_parser.read_buf(b":1\r\n");
+        assert_eq!(shallow_parser.try_parse(), Ok(Some(RespValue::Integer(1))));
+    }
+
+    #[test]
+    fn test_depth_overflow_discards_frame() {
+        let mut parser = Parser::new(2, 1000);
+
+        // Nested far deeper than allowed, with bulk payloads left to skip
+        parser.read_buf(b"*2\r\n*1\r\n*1\r\n%1\r\n$3\r\n*1\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::InvalidDepth));
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+        parser.read_buf(b"\r\n:2\r\n:3\r\n+OK\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
     }
 
     #[test]
@@ -829,7 +859,7 @@ mod tests {
         parser.read_buf(b"\r\n");
         match parser.try_parse() {
             Ok(Some(RespValue::BulkString(Some(msg)))) => {
-                assert_eq!(msg, large_string);
+                assert_eq!(*msg, large_string);
             }
             other => panic!("Expected BulkString, got {:?}", other),
         }
@@ -1103,10 +1133,10 @@ mod tests {
             let result = parser.try_parse();
             assert_eq!(
                 result,
-                Ok(Some(RespValue::Array(Some(vec![
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
                     RespValue::Integer(1),
                     RespValue::Integer(2)
-                ]))))
+                ])))))
             );
         }
 
@@ -1118,7 +1148,7 @@ mod tests {
             // RESP3 Empty Array should be Array(Some(vec![]))
             assert_eq!(
                 result,
-                Ok(Some(RespValue::Array(Some(vec![])))),
+                Ok(Some(RespValue::Array(Some(Box::default())))),
                 "Failed on Empty Array *0"
             );
         }
@@ -1153,11 +1183,11 @@ mod tests {
             let result = parser.try_parse();
             assert_eq!(
                 result,
-                Ok(Some(RespValue::Array(Some(vec![
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
                     RespValue::Integer(123),
                     RespValue::SimpleString("hello".into()),
                     RespValue::BulkString(Some("world".into()))
-                ]))))
+                ])))))
             );
         }
 
@@ -1180,16 +1210,16 @@ mod tests {
             let result = parser.try_parse();
             assert_eq!(
                 result,
-                Ok(Some(RespValue::Array(Some(vec![
-                    RespValue::Array(Some(vec![
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
+                    RespValue::Array(Some(Box::from(vec![
                         RespValue::SimpleString(Cow::Borrowed("a")),
                         RespValue::SimpleString(Cow::Borrowed("b"))
-                    ])),
-                    RespValue::Array(Some(vec![
+                    ]))),
+                    RespValue::Array(Some(Box::from(vec![
                         RespValue::SimpleString(Cow::Borrowed("c")),
                         RespValue::SimpleString(Cow::Borrowed("d"))
-                    ]))
-                ]))))
+                    ])))
+                ])))))
             );
         }
 
@@ -1226,11 +1256,11 @@ mod tests {
             let result = parser.try_parse();
             assert_eq!(
                 result,
-                Ok(Some(RespValue::Array(Some(vec![
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
                     RespValue::BulkString(Some(Cow::Borrowed("hello"))),
                     RespValue::BulkString(None), // Null bulk string
                     RespValue::BulkString(Some(Cow::Borrowed("")))  // Empty bulk string
-                ])))),
+                ]))))),
                 "Failed on array with null/empty bulk strings"
             );
         }
@@ -1242,11 +1272,13 @@ mod tests {
             let result = parser.try_parse();
             assert_eq!(
                 result,
-                Ok(Some(RespValue::Array(Some(vec![
-                    RespValue::Array(Some(vec![])), // Empty array
-                    RespValue::Array(None),         // Null array
-                    RespValue::Array(Some(vec![RespValue::SimpleString(Cow::Borrowed("OK"))]))
-                ])))),
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
+                    RespValue::Array(Some(Box::default())), // Empty array
+                    RespValue::Array(None),                 // Null array
+                    RespValue::Array(Some(Box::from(vec![RespValue::SimpleString(
+                        Cow::Borrowed("OK")
+                    )])))
+                ]))))),
                 "Failed on nested null/empty arrays"
             );
         }
@@ -1313,7 +1345,7 @@ mod tests {
         parser.read_buf(b"(34928903");
         assert!(matches!(parser.try_parse(), Err(ParseError::UnexpectedEof)));
         // Chunk 2: Rest of value
-        parser.read_buf(&big_num[8..].as_bytes());
+        parser.read_buf(&big_num.as_bytes()[8..]);
         assert!(matches!(parser.try_parse(), Err(ParseError::UnexpectedEof)));
         // Chunk 3: Terminator
         parser.read_buf(b"\r\n");
@@ -1328,12 +1360,12 @@ mod tests {
         let mut parser = Parser::new(100, 1000);
 
         // Non-null
-        // Chunk 1: Type marker + partial value
-        parser.read_buf(b"!Error");
-        assert!(matches!(parser.try_parse(), Err(ParseError::UnexpectedEof)));
+        // Chunk 1: Type marker + length + partial value
+        parser.read_buf(b"!13\r\nError");
+        assert!(matches!(parser.try_parse(), Err(ParseError::NotEnoughData)));
         // Chunk 2: Rest of value
         parser.read_buf(b" details");
-        assert!(matches!(parser.try_parse(), Err(ParseError::UnexpectedEof)));
+        assert!(matches!(parser.try_parse(), Err(ParseError::NotEnoughData)));
         // Chunk 3: Terminator
         parser.read_buf(b"\r\n");
         assert_eq!(
@@ -1359,18 +1391,21 @@ mod tests {
     fn test_verbatim_string_chunks() {
         let mut parser = Parser::new(100, 1000);
 
-        // Chunk 1: Type marker + partial value
-        parser.read_buf(b"=txt:Some");
+        // Chunk 1: Type marker + partial length
+        parser.read_buf(b"=2");
         assert!(matches!(parser.try_parse(), Err(ParseError::UnexpectedEof)));
-        // Chunk 2: Rest of value
+        // Chunk 2: Rest of length + partial value
+        parser.read_buf(b"2\r\ntxt:Some");
+        assert!(matches!(parser.try_parse(), Err(ParseError::NotEnoughData)));
+        // Chunk 3: Rest of value
         parser.read_buf(b" verbatim text");
-        assert!(matches!(parser.try_parse(), Err(ParseError::UnexpectedEof)));
-        // Chunk 3: Terminator
+        assert!(matches!(parser.try_parse(), Err(ParseError::NotEnoughData)));
+        // Chunk 4: Terminator
         parser.read_buf(b"\r\n");
         assert_eq!(
             parser.try_parse(),
-            Ok(Some(RespValue::VerbatimString(Some(Cow::Borrowed(
-                "txt:Some verbatim text"
+            Ok(Some(RespValue::VerbatimString(Some(Box::new(
+                Verbatim::txt("Some verbatim text")
             )))))
         );
     }
@@ -1398,7 +1433,7 @@ mod tests {
         parser.read_buf(b"value\r\n");
         assert_eq!(
             parser.try_parse(),
-            Ok(Some(RespValue::Map(Some(vec![
+            Ok(Some(RespValue::Map(Some(Box::from(vec![
                 (
                     RespValue::SimpleString(Cow::Borrowed("key1")),
                     RespValue::Integer(123)
@@ -1407,7 +1442,7 @@ mod tests {
                     RespValue::SimpleString(Cow::Borrowed("key2")),
                     RespValue::BulkString(Some(Cow::Borrowed("value")))
                 )
-            ]))))
+            ])))))
         );
 
         // Test Empty Map %0\r\n
@@ -1415,7 +1450,10 @@ mod tests {
         parser.read_buf(b"%0");
         assert!(matches!(parser.try_parse(), Err(ParseError::UnexpectedEof)));
         parser.read_buf(b"\r\n");
-        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Map(Some(vec![])))));
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(Box::default()))))
+        );
 
         // Test Null Map %-1\r\n
         parser = Parser::new(100, 1000);
@@ -1442,17 +1480,20 @@ mod tests {
         parser.read_buf(b"$5\r\nthree\r\n");
         assert_eq!(
             parser.try_parse(),
-            Ok(Some(RespValue::Set(Some(vec![
+            Ok(Some(RespValue::Set(Some(Box::from(vec![
                 RespValue::Integer(1),
                 RespValue::SimpleString(Cow::Borrowed("two")),
                 RespValue::BulkString(Some(Cow::Borrowed("three")))
-            ]))))
+            ])))))
         );
 
         // Test Empty Set ~0\r\n
         parser = Parser::new(100, 1000);
         parser.read_buf(b"~0\r\n");
-        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Set(Some(vec![])))));
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Set(Some(Box::default()))))
+        );
 
         // Test Null Set ~-1\r\n
         parser = Parser::new(100, 1000);
@@ -1474,16 +1515,19 @@ mod tests {
         parser.read_buf(b":42\r\n");
         assert_eq!(
             parser.try_parse(),
-            Ok(Some(RespValue::Push(Some(vec![
+            Ok(Some(RespValue::Push(Some(Box::from(vec![
                 RespValue::SimpleString(Cow::Borrowed("message")),
                 RespValue::Integer(42)
-            ]))))
+            ])))))
         );
 
         // Test Empty Push >0\r\n
         parser = Parser::new(100, 1000);
         parser.read_buf(b">0\r\n");
-        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Push(Some(vec![])))));
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Push(Some(Box::default()))))
+        );
 
         // Test Null Push >-1\r\n
         parser = Parser::new(100, 1000);
@@ -1550,4 +1594,1770 @@ mod tests {
         // No more commands
         assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
     }
+
+    #[test]
+    fn test_peek_frame() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n+OK\r\n");
+
+        let expected = RespValue::Array(Some(Box::from(vec![
+            RespValue::BulkString(Some("GET".into())),
+            RespValue::BulkString(Some("key".into())),
+        ])));
+
+        // Peeking repeatedly yields the same frame without consuming it
+        assert_eq!(parser.peek_frame(), Ok(Some(expected.clone())));
+        assert_eq!(parser.peek_frame(), Ok(Some(expected.clone())));
+        assert_eq!(parser.try_parse(), Ok(Some(expected)));
+
+        assert_eq!(
+            parser.peek_frame(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+    }
+
+    #[test]
+    fn test_peek_frame_incomplete() {
+        let mut parser = Parser::new(10, 1024);
+
+        // A partial aggregate must survive a peek untouched
+        parser.read_buf(b"*2\r\n:1\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        assert_eq!(parser.peek_frame(), Err(ParseError::UnexpectedEof));
+
+        parser.read_buf(b":2\r\n");
+        let expected = RespValue::Array(Some(Box::from(vec![
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+        ])));
+        assert_eq!(parser.peek_frame(), Ok(Some(expected.clone())));
+        assert_eq!(parser.try_parse(), Ok(Some(expected)));
+    }
+
+    #[test]
+    fn test_split_frame() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n+OK\r\n%1\r\n+a\r\n*-1\r\n_\r\n");
+
+        assert_eq!(
+            parser.split_frame().unwrap().as_ref(),
+            b"*2\r\n$3\r\nGET\r\n$3\r\nkey\r\n"
+        );
+        assert_eq!(parser.split_frame().unwrap().as_ref(), b"+OK\r\n");
+        assert_eq!(
+            parser.split_frame().unwrap().as_ref(),
+            b"%1\r\n+a\r\n*-1\r\n"
+        );
+        assert_eq!(parser.split_frame().unwrap().as_ref(), b"_\r\n");
+        assert_eq!(parser.split_frame(), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_split_frame_chunks() {
+        let mut parser = Parser::new(10, 1024);
+
+        parser.read_buf(b"*2\r\n$5\r\nhel");
+        assert_eq!(parser.split_frame(), Err(ParseError::NotEnoughData));
+
+        parser.read_buf(b"lo\r\n:4");
+        assert_eq!(parser.split_frame(), Err(ParseError::UnexpectedEof));
+
+        parser.read_buf(b"2\r\n");
+        assert_eq!(
+            parser.split_frame().unwrap().as_ref(),
+            b"*2\r\n$5\r\nhello\r\n:42\r\n"
+        );
+
+        // Splitting and decoding can be mixed between frames
+        parser.read_buf(b"+OK\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+        parser.read_buf(b"#t\r\n");
+        assert_eq!(parser.split_frame().unwrap().as_ref(), b"#t\r\n");
+    }
+
+    #[test]
+    fn test_split_frame_invalid() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"$3\r\nfoo\n\n");
+        assert!(matches!(
+            parser.split_frame(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*x\r\n");
+        assert!(matches!(
+            parser.split_frame(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+
+        let mut parser = Parser::new(1, 1024);
+        parser.read_buf(b"*1\r\n*1\r\n:1\r\n");
+        assert_eq!(parser.split_frame(), Err(ParseError::InvalidDepth));
+    }
+
+    // Small deterministic LCG so the fuzz cases are reproducible without extra dependencies
+    fn next_rand(seed: &mut u64) -> u64 {
+        *seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        *seed >> 33
+    }
+
+    fn random_value(seed: &mut u64, depth: usize) -> RespValue<'static> {
+        let choice = next_rand(seed) % if depth == 0 { 8 } else { 12 };
+        let text = "x".repeat((next_rand(seed) % 20) as usize);
+        match choice {
+            0 => RespValue::SimpleString(Cow::Owned(format!("s{}", text))),
+            1 => RespValue::Error(Cow::Owned(format!("ERR {}", text))),
+            2 => RespValue::Integer(next_rand(seed) as i64 - (1 << 30)),
+            3 => RespValue::BulkString(Some(Cow::Owned(text))),
+            4 => RespValue::BulkString(None),
+            5 => RespValue::Null,
+            6 => RespValue::Boolean(next_rand(seed).is_multiple_of(2)),
+            7 => RespValue::BigNumber(Cow::Owned(format!("{}", next_rand(seed)))),
+            8 => RespValue::Array(Some(
+                (0..next_rand(seed) % 4)
+                    .map(|_| random_value(seed, depth - 1))
+                    .collect(),
+            )),
+            9 => RespValue::Set(Some(
+                (0..next_rand(seed) % 4)
+                    .map(|_| random_value(seed, depth - 1))
+                    .collect(),
+            )),
+            10 => RespValue::Push(Some(
+                (0..1 + next_rand(seed) % 3)
+                    .map(|_| random_value(seed, depth - 1))
+                    .collect(),
+            )),
+            _ => RespValue::Map(Some(
+                (0..next_rand(seed) % 3)
+                    .map(|_| (random_value(seed, depth - 1), random_value(seed, depth - 1)))
+                    .collect(),
+            )),
+        }
+    }
+
+    #[test]
+    fn test_back_to_back_frame_boundaries() {
+        for round in 0..50u64 {
+            let mut seed = round;
+            let frames: Vec<RespValue> = (0..40).map(|_| random_value(&mut seed, 3)).collect();
+            let wire: Vec<u8> = frames.iter().flat_map(|frame| frame.as_bytes()).collect();
+            let largest = frames.iter().map(|f| f.as_bytes().len()).max().unwrap();
+
+            let mut parser = Parser::new(10, 1024);
+            let mut parsed = Vec::new();
+            let mut offset = 0;
+            while offset < wire.len() {
+                let chunk = 1 + (next_rand(&mut seed) % 17) as usize;
+                let end = (offset + chunk).min(wire.len());
+                parser.read_buf(&wire[offset..end]);
+                offset = end;
+
+                loop {
+                    match parser.try_parse() {
+                        Ok(Some(value)) => parsed.push(value),
+                        Err(ParseError::UnexpectedEof) | Err(ParseError::NotEnoughData) => break,
+                        other => panic!("round {}: unexpected result {:?}", round, other),
+                    }
+                }
+
+                // Consumed frames must not accumulate in the buffer
+                assert!(
+                    parser.buffer().len() <= largest + 17,
+                    "round {}: buffer grew to {} bytes",
+                    round,
+                    parser.buffer().len()
+                );
+            }
+
+            assert_eq!(parsed, frames, "round {}", round);
+            assert!(parser.buffer().is_empty(), "round {}", round);
+        }
+    }
+
+    #[test]
+    fn test_clear_buffer_drops_consumed_bytes() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"+OK\r\n:1\r\n");
+
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+        assert_eq!(parser.buffer().as_ref(), b":1\r\n");
+
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(1))));
+        assert!(parser.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_read_buf_large_chunk_keeps_partial_frame() {
+        let mut parser = Parser::new(10, 100_000);
+        parser.read_buf(b"*2\r\n$5\r\nhel");
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+
+        // A chunk larger than the current capacity must not discard buffered bytes
+        let mut chunk = b"lo\r\n$10000\r\n".to_vec();
+        chunk.extend(std::iter::repeat_n(b'a', 10_000));
+        chunk.extend_from_slice(b"\r\n");
+        parser.read_buf(&chunk);
+
+        match parser.try_parse() {
+            Ok(Some(RespValue::Array(Some(items)))) => {
+                assert_eq!(items[0], RespValue::BulkString(Some("hello".into())));
+                assert_eq!(
+                    items[1],
+                    RespValue::BulkString(Some(Cow::Owned("a".repeat(10_000))))
+                );
+            }
+            other => panic!("Expected Array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let mut parser = Parser::new(10, 1024);
+        let mut frames = Vec::new();
+
+        parser.read_buf(b"+OK\r\n:1\r\n*2\r\n:2");
+        assert_eq!(parser.parse_with(|value| frames.push(value)), Ok(2));
+        assert_eq!(
+            frames,
+            vec![RespValue::SimpleString("OK".into()), RespValue::Integer(1)]
+        );
+
+        parser.read_buf(b"\r\n:3\r\n");
+        assert_eq!(parser.parse_with(|value| frames.push(value)), Ok(1));
+        assert_eq!(
+            frames[2],
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::Integer(2),
+                RespValue::Integer(3)
+            ])))
+        );
+
+        assert_eq!(parser.parse_with(|value| frames.push(value)), Ok(0));
+
+        parser.read_buf(b":4\r\n?\r\n");
+        assert!(matches!(
+            parser.parse_with(|value| frames.push(value)),
+            Err(ParseError::InvalidFormat(_))
+        ));
+        assert_eq!(frames.last(), Some(&RespValue::Integer(4)));
+    }
+
+    fn recovering_parser() -> Parser {
+        Parser::with_config(ParserConfig {
+            max_depth: 10,
+            max_length: 1024,
+            recover_errors: true,
+            ..ParserConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_recover_errors_disabled_by_default() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"?garbage\r\n+OK\r\n");
+        assert!(matches!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+        assert_eq!(parser.skipped_bytes(), 0);
+    }
+
+    #[test]
+    fn test_recover_errors_resync() {
+        let mut parser = recovering_parser();
+        parser.read_buf(b"?garbage\r\n+OK\r\n#x\r\n:1\r\n");
+
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+        assert_eq!(parser.skipped_bytes(), 10);
+
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(1))));
+        assert_eq!(parser.skipped_bytes(), 14);
+    }
+
+    #[test]
+    fn test_recover_errors_across_chunks() {
+        let mut parser = recovering_parser();
+
+        parser.read_buf(b"*1\r\n$3\r\n\xff\xfe\xfd\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+
+        // Invalid UTF-8; no boundary buffered yet, so garbage is dropped while waiting
+        parser.read_buf(b"\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        assert_eq!(parser.buffer().as_ref(), b"\r\n");
+
+        parser.read_buf(b":7\r\n");
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(7))));
+        assert_eq!(parser.skipped_bytes(), 13);
+        assert!(parser.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_fragmented_line_chunks() {
+        let mut parser = Parser::new(10, 1024);
+        let line = "a".repeat(5000);
+
+        parser.read_buf(b"+");
+        for chunk in line.as_bytes().chunks(7) {
+            parser.read_buf(chunk);
+            assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        }
+
+        // CRLF split across chunks
+        parser.read_buf(b"\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString(Cow::Owned(line))))
+        );
+
+        // Lone \r bytes inside an error line do not end it
+        parser.read_buf(b"-ERR a\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"b\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Error("ERR a\rb\r".into())))
+        );
+
+        parser.read_buf(b":12");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"34\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"\n");
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(1234))));
+    }
+
+    #[test]
+    fn test_many_lone_carriage_returns() {
+        let mut parser = Parser::new(10, 4 * 1024 * 1024);
+        let mut input = b"+".to_vec();
+        input.extend(std::iter::repeat_n(b'\r', 1024 * 1024));
+        parser.read_buf(&input);
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+
+        parser.read_buf(b"x\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(
+                "Simple string cannot contain CR or LF".into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_nan_map_keys_and_set_members() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"%1\r\n,nan\r\n:1\r\n~2\r\n:1\r\n*1\r\n,nan\r\n%1\r\n:1\r\n,nan\r\n");
+
+        assert!(matches!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(_))))
+        ));
+        assert!(matches!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Set(Some(_))))
+        ));
+        // NaN map values are fine
+        assert!(matches!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(_))))
+        ));
+        assert_eq!(
+            parser.take_warnings(),
+            vec![ParseWarning::NanMapKey, ParseWarning::NanSetMember]
+        );
+        assert!(parser.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_strict_rejects_nan_map_keys() {
+        let mut parser = Parser::with_config(ParserConfig {
+            strict: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*2\r\n~1\r\n,nan\r\n%1\r\n,nan\r\n:1\r\n:7\r\n");
+
+        assert_eq!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat("NaN set member".into()))
+        );
+        // The rest of the rejected frame is dropped
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(7))));
+        assert!(parser.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_detailed_errors() {
+        let mut parser = Parser::with_config(ParserConfig {
+            detailed_errors: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*2\r\n:1\r\n:x\r\n");
+        let error = parser.try_parse().unwrap_err();
+        assert!(matches!(error, ParseError::InvalidFormat(_)));
+
+        let detailed = error.clone().detailed(&parser);
+        assert_eq!(
+            detailed.context,
+            Some(ErrorContext {
+                offset: 9,
+                snippet: b"x\r\n".to_vec(),
+                state: "reading an integer",
+                depth: 1,
+            })
+        );
+        assert_eq!(
+            detailed.to_string(),
+            "Failed to parse RESP frame while reading an integer at offset 9 (depth 1), near \"x\\r\\n\""
+        );
+
+        // The original error stays reachable through the source chain
+        let source = std::error::Error::source(&detailed).unwrap();
+        assert_eq!(source.to_string(), error.to_string());
+    }
+
+    #[test]
+    fn test_detailed_errors_disabled_by_default() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b":x\r\n");
+        let detailed = parser.try_parse().unwrap_err().detailed(&parser);
+        assert_eq!(detailed.context, None);
+        assert_eq!(detailed.to_string(), "Failed to parse RESP frame");
+    }
+
+    #[test]
+    fn test_fragmented_resp3_lines() {
+        let mut parser = Parser::new(10, 1024);
+        let input = b",3.5\r\n(12345678901234567890\r\n!-1\r\n=6\r\ntxt:hi\r\n";
+        let mut values = Vec::new();
+        for byte in input {
+            parser.read_buf(&[*byte]);
+            if let Ok(Some(value)) = parser.try_parse() {
+                values.push(value);
+            }
+        }
+        assert_eq!(
+            values,
+            vec![
+                RespValue::Double(3.5),
+                RespValue::BigNumber("12345678901234567890".into()),
+                RespValue::BulkError(None),
+                RespValue::VerbatimString(Some(Box::new(Verbatim::txt("hi")))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_feed_bytes_takes_over_allocation() {
+        let mut parser = Parser::new(10, 1024);
+        let bytes = Bytes::from(b"*2\r\n+a\r\n".to_vec());
+        let ptr = bytes.as_ptr();
+        parser.feed_bytes(bytes);
+        assert_eq!(parser.buffer().as_ptr(), ptr);
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+
+        // Input is copied while a frame is partially buffered
+        let shared = Bytes::from_static(b"+b\r\n:1\r\n");
+        parser.feed_bytes(shared.slice(..4));
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::SimpleString("a".into()),
+                RespValue::SimpleString("b".into()),
+            ])))))
+        );
+
+        // Static or shared bytes cannot be taken over and are copied
+        parser.feed_bytes(shared.slice(4..));
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(1))));
+    }
+
+    #[test]
+    fn test_feed_buf() {
+        let mut parser = Parser::new(10, 1024);
+        let first = BytesMut::from(&b"$5\r\nhel"[..]);
+        let ptr = first.as_ptr();
+        parser.feed(first);
+        assert_eq!(parser.buffer().as_ptr(), ptr);
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+
+        parser.feed(Buf::chain(&b"lo\r\n:"[..], &b"7\r\n"[..]));
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkString(Some("hello".into()))))
+        );
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(7))));
+    }
+
+    #[test]
+    fn test_from_and_into_buffer() {
+        let buffer = BytesMut::from(&b"+OK\r\n:12"[..]);
+        let ptr = buffer.as_ptr();
+        let mut parser = Parser::from_buffer(buffer);
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+
+        let buffer = parser.into_buffer();
+        assert_eq!(&buffer[..], b":12");
+        assert_eq!(buffer.as_ptr(), ptr.wrapping_add(5));
+
+        let mut parser = Parser::from_buffer(buffer);
+        parser.read_buf(b"\r\n");
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(12))));
+        assert!(parser.into_buffer().is_empty());
+    }
+
+    #[test]
+    fn test_read_from() {
+        let mut parser = Parser::new(10, 1024 * 1024);
+        let mut input = b"*2\r\n$5\r\nhello\r\n:1\r\n".repeat(1000);
+        input.extend_from_slice(b"+tail");
+        let mut reader = &input[..];
+
+        let mut frames = 0;
+        loop {
+            let read = parser.read_from(&mut reader).unwrap();
+            while let Ok(Some(_)) = parser.try_parse() {
+                frames += 1;
+            }
+            if read == 0 {
+                break;
+            }
+        }
+        assert_eq!(frames, 1000);
+        assert_eq!(&parser.buffer()[..], b"+tail");
+    }
+
+    #[test]
+    fn test_read_from_error_keeps_buffer() {
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::Interrupted.into())
+            }
+        }
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"+O");
+        let error = parser.read_from(&mut Failing).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Interrupted);
+        assert_eq!(&parser.buffer()[..], b"+O");
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_read_from_async() {
+        let (mut client, mut server) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            for _ in 0..100 {
+                client.write_all(b"*1\r\n$5\r\nhello\r\n").await.unwrap();
+            }
+        });
+
+        let mut parser = Parser::new(10, 1024);
+        let mut frames = 0;
+        while parser.read_from_async(&mut server).await.unwrap() > 0 {
+            while let Ok(Some(_)) = parser.try_parse() {
+                frames += 1;
+            }
+        }
+        writer.await.unwrap();
+        assert_eq!(frames, 100);
+        assert!(parser.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_speculative_prevalidation() {
+        let mut parser = Parser::with_config(ParserConfig {
+            speculate: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"+OK\r\n*2\r\n:1\r\n:2\r\n$3\r\nab");
+        assert_eq!(parser.peek(), None);
+
+        assert!(parser.try_parse().unwrap().is_some());
+        assert_eq!(parser.peek(), Some(&NextFrame::Complete { len: 12 }));
+        assert!(parser.try_parse().unwrap().is_some());
+        assert_eq!(parser.peek(), Some(&NextFrame::Incomplete));
+
+        // New input invalidates an incomplete result
+        parser.read_buf(b"c\r\n?\r\n");
+        assert_eq!(parser.peek(), None);
+        assert!(parser.try_parse().unwrap().is_some());
+        assert_eq!(
+            parser.peek(),
+            Some(&NextFrame::Invalid(ParseError::InvalidFormat(
+                "Invalid type marker".into()
+            )))
+        );
+    }
+
+    #[test]
+    fn test_speculation_disabled_by_default() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"+OK\r\n+OK\r\n");
+        parser.try_parse().unwrap();
+        assert_eq!(parser.peek(), None);
+    }
+
+    #[test]
+    fn test_hostile_aggregate_header_is_not_preallocated() {
+        let mut parser = Parser::new(10, 1024);
+        // Reserving this many elements up front would abort the process
+        parser.read_buf(b"*1000000000000\r\n:1\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_aggregate_grows_past_prealloc_limit() {
+        let mut parser = Parser::with_config(ParserConfig {
+            prealloc_limit: 2,
+            ..ParserConfig::default()
+        });
+        let mut frame = b"%5\r\n".to_vec();
+        for i in 0..5 {
+            frame.extend_from_slice(format!(":{}\r\n:{}\r\n", i, i * 10).as_bytes());
+        }
+        parser.read_buf(&frame);
+        let expected = (0..5)
+            .map(|i| (RespValue::Integer(i), RespValue::Integer(i * 10)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(Box::from(expected)))))
+        );
+    }
+
+    #[test]
+    fn test_decode_hooks_by_kind() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = Parser::new(10, 1024);
+        for kind in [RespKind::Error, RespKind::Push] {
+            let seen = seen.clone();
+            parser.on_kind(kind, move |value| seen.lock().unwrap().push(value.clone()));
+        }
+
+        parser.read_buf(b"+OK\r\n-ERR a\r\n*1\r\n-ERR nested\r\n>1\r\n:1\r\n");
+        let mut frames = 0;
+        while let Ok(Some(_)) = parser.try_parse() {
+            frames += 1;
+        }
+        assert_eq!(frames, 4);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                RespValue::Error("ERR a".into()),
+                RespValue::Push(Some(Box::from(vec![RespValue::Integer(1)]))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_on_frame_hook() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = Parser::new(10, 1024);
+        let hook_seen = seen.clone();
+        parser.on_frame(move |value, len| hook_seen.lock().unwrap().push((value.kind(), len)));
+
+        // Frame lengths include bytes dropped from the buffer mid-aggregate
+        parser.read_buf(b"+OK\r\n*2\r\n:1\r\n");
+        assert!(parser.try_parse().unwrap().is_some());
+        assert!(parser.try_parse().is_err());
+        parser.read_buf(b"$2\r\nab\r\n");
+        assert!(parser.try_parse().unwrap().is_some());
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![(RespKind::SimpleString, 5), (RespKind::Array, 16)]
+        );
+    }
+
+    #[test]
+    fn test_large_flat_array_needs_no_budget() {
+        let mut frame = b"*10000\r\n".to_vec();
+        for i in 0..10000 {
+            frame.extend_from_slice(format!(":{}\r\n", i).as_bytes());
+        }
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(&frame);
+        match parser.try_parse() {
+            Ok(Some(RespValue::Array(Some(elements)))) => assert_eq!(elements.len(), 10000),
+            other => panic!("Expected array, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_iteration_budget_resumes() {
+        let mut frame = b"*1000\r\n".to_vec();
+        for _ in 0..1000 {
+            frame.extend_from_slice(b"$1\r\nx\r\n");
+        }
+        let mut parser = Parser::with_config(ParserConfig {
+            max_iterations: Some(100),
+            ..ParserConfig::default()
+        });
+        parser.read_buf(&frame);
+
+        let mut calls = 1;
+        let value = loop {
+            match parser.try_parse() {
+                Ok(Some(value)) => break value,
+                Err(ParseError::IterationBudgetExceeded) => calls += 1,
+                other => panic!("Unexpected result {:?}", other),
+            }
+        };
+        assert!(calls > 10, "{} calls", calls);
+        assert_eq!(
+            value,
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("x".into()));
+                1000
+            ])))
+        );
+        assert_eq!(parser.stats().parse_errors, 0);
+        assert!(parser.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_lenient_line_endings() {
+        let mut parser = Parser::with_config(ParserConfig {
+            lenient_line_endings: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*6\n+OK\n:-12\r\n$3\nfoo\r\n_\n#t\n,1.5\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::SimpleString("OK".into()),
+                RespValue::Integer(-12),
+                RespValue::BulkString(Some("foo".into())),
+                RespValue::Null,
+                RespValue::Boolean(true),
+                RespValue::Double(1.5),
+            ])))))
+        );
+
+        // A line split right after its \r still ends at the \n
+        parser.read_buf(b"-ERR x\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Error("ERR x".into())))
+        );
+        assert!(parser.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_bare_newline_rejected_by_default() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*1\n:1\n");
+        assert!(matches!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"+OK\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_fixed_depth_parser() {
+        let mut parser = FixedDepthParser::<2>::with_fixed_depth(ParserConfig {
+            max_depth: 10,
+            ..ParserConfig::default()
+        });
+        assert_eq!(parser.config().max_depth, 2);
+
+        // Split across reads so the inline stack is kept between calls
+        parser.read_buf(b"*2\r\n*1\r\n:1\r\n%1\r\n+a");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"\r\n:2\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)]))),
+                RespValue::Map(Some(Box::from(vec![(
+                    RespValue::SimpleString("a".into()),
+                    RespValue::Integer(2)
+                )]))),
+            ])))))
+        );
+
+        parser.read_buf(b"*1\r\n*1\r\n*1\r\n:1\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::InvalidDepth));
+    }
+
+    #[test]
+    fn test_fixed_depth_parser_peek_and_reset() {
+        let mut parser = FixedDepthParser::<4>::with_fixed_depth(ParserConfig::default());
+        parser.read_buf(b"*1\r\n*1\r\n:1\r\n");
+        let peeked = parser.peek_frame().unwrap();
+        assert_eq!(parser.try_parse().unwrap(), peeked);
+
+        parser.read_buf(b"*2\r\n*1\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.reset();
+        parser.read_buf(b":7\r\n");
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(7))));
+    }
+
+    #[test]
+    fn test_try_parse_with_len() {
+        let input: &[u8] = b"*2\r\n$3\r\nfoo\r\n:1\r\n+OK\r\n%1\r\n+a\r\n_\r\n";
+        let mut parser = Parser::new(10, 1024);
+        let mut frames = Vec::new();
+        // Byte by byte, so elements are dropped from the buffer mid-frame
+        for byte in input {
+            parser.read_buf(&[*byte]);
+            if let Ok(Some((_, len))) = parser.try_parse_with_len() {
+                frames.push(len);
+            }
+        }
+        assert_eq!(frames, vec![17, 5, 11]);
+        assert_eq!(frames.iter().sum::<usize>(), input.len());
+
+        // Skipped garbage is not part of the next frame
+        let mut parser = Parser::with_config(ParserConfig {
+            recover_errors: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"?junk\r\n:7\r\n");
+        assert_eq!(
+            parser.try_parse_with_len(),
+            Ok(Some((RespValue::Integer(7), 4)))
+        );
+        assert_eq!(parser.skipped_bytes(), 7);
+    }
+
+    #[test]
+    fn test_parse_in_place() {
+        let mut parser = Parser::new(10, 1024);
+        let mut buf = BytesMut::new();
+        let mut values = Vec::new();
+        // Dropping elements of the nested frame mid-way advances the caller's buffer
+        for chunk in [
+            &b"*2\r\n$3\r\nfo"[..],
+            b"o\r\n:1",
+            b"\r\n+OK\r\n",
+            b"%1\r\n",
+        ] {
+            buf.extend_from_slice(chunk);
+            while let Ok(Some(value)) = parser.parse_in_place(&mut buf) {
+                values.push(value);
+            }
+        }
+        assert_eq!(
+            values,
+            vec![
+                RespValue::Array(Some(Box::from(vec![
+                    RespValue::BulkString(Some("foo".into())),
+                    RespValue::Integer(1),
+                ]))),
+                RespValue::SimpleString("OK".into()),
+            ]
+        );
+        assert_eq!(&buf[..], b"%1\r\n");
+        assert!(parser.buffer().is_empty());
+        assert_eq!(parser.stats().frames_parsed, 2);
+    }
+
+    #[test]
+    fn test_stream_bulks() {
+        let chunks = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut parser = Parser::new(10, 1024);
+        let seen = chunks.clone();
+        parser.stream_bulks(8, move |chunk: &BulkChunk<'_>| {
+            seen.lock()
+                .unwrap()
+                .push((chunk.data.to_vec(), chunk.offset, chunk.is_last()));
+        });
+
+        // Bodies up to the threshold are decoded as usual
+        parser.read_buf(b"$8\r\n01234567\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkString(Some("01234567".into()))))
+        );
+
+        parser.read_buf(b"*2\r\n$12\r\nhello");
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+        // Delivered bytes no longer take up buffer space
+        assert!(parser.buffer().is_empty());
+        parser.read_buf(b", worl");
+        assert_eq!(parser.peek_frame(), Err(ParseError::NotEnoughData));
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+        parser.read_buf(b"d\r");
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+        parser.read_buf(b"\n:1\r\n");
+        assert_eq!(
+            parser.try_parse_with_len(),
+            Ok(Some((
+                RespValue::Array(Some(Box::from(vec![
+                    RespValue::BulkStreamed { len: 12 },
+                    RespValue::Integer(1),
+                ]))),
+                27
+            )))
+        );
+        assert_eq!(
+            *chunks.lock().unwrap(),
+            vec![
+                (b"hello".to_vec(), 0, false),
+                (b", worl".to_vec(), 5, false),
+                (b"d".to_vec(), 11, true),
+            ]
+        );
+        assert_eq!(RespValue::BulkStreamed { len: 12 }.as_bytes(), b"$12\r\n");
+
+        // Peeking reports the value without streaming the body away
+        chunks.lock().unwrap().clear();
+        parser.read_buf(b"$9\r\n123456789\r\n");
+        assert_eq!(
+            parser.peek_frame(),
+            Ok(Some(RespValue::BulkStreamed { len: 9 }))
+        );
+        assert!(chunks.lock().unwrap().is_empty());
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkStreamed { len: 9 }))
+        );
+        assert_eq!(chunks.lock().unwrap().len(), 1);
+
+        parser.read_buf(b"$9\r\n123456789xx");
+        assert!(matches!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_max_frame_memory() {
+        let value_size = std::mem::size_of::<RespValue>();
+        let fan_out = b"*4\r\n*4\r\n:1\r\n:2\r\n:3\r\n:4\r\n*1\r\n:1\r\n*1\r\n:1\r\n*1\r\n:1\r\n";
+        let parser_with_budget = |budget| {
+            Parser::with_config(ParserConfig {
+                max_frame_memory: Some(budget),
+                ..ParserConfig::default()
+            })
+        };
+
+        // Element vectors of 4 + 4 + 1 + 1 + 1 values
+        let mut parser = parser_with_budget(11 * value_size);
+        parser.read_buf(fan_out);
+        assert!(parser.try_parse().unwrap().is_some());
+        let mut parser = parser_with_budget(11 * value_size - 1);
+        parser.read_buf(fan_out);
+        assert_eq!(parser.try_parse(), Err(ParseError::MemoryBudgetExceeded));
+        assert_eq!(parser.stats().parse_errors_by_kind.memory_budget, 1);
+
+        // String payloads count too, and the budget applies per frame
+        let mut parser = parser_with_budget(2 * value_size + 9);
+        parser.read_buf(b"*2\r\n$5\r\nhello\r\n+world\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::MemoryBudgetExceeded));
+        let mut parser = parser_with_budget(2 * value_size + 10);
+        for _ in 0..3 {
+            parser.read_buf(b"*2\r\n$5\r\nhello\r\n+world\r\n");
+            assert!(parser.try_parse().unwrap().is_some());
+        }
+    }
+
+    #[test]
+    fn test_oversized_bulk_rejected_at_header() {
+        let mut parser = Parser::new(10, 1024);
+        // Nothing of the payload is buffered yet
+        parser.read_buf(b"$1073741824\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::InvalidLength));
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*2\r\n:1\r\n$1024\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::InvalidLength));
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"$1023\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::NotEnoughData));
+    }
+
+    #[test]
+    fn test_verbatim_string_length_prefixed() {
+        let mut parser = Parser::new(10, 1024);
+        // The payload is binary safe
+        parser.read_buf(b"=15\r\nmkd:line\r\nline2\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::VerbatimString(Some(Box::new(
+                Verbatim::mkd("line\r\nline2")
+            )))))
+        );
+
+        for input in [&b"=3\r\ntxt\r\n"[..], b"=5\r\ntxt-x\r\n", b"=0\r\n\r\n"] {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(input);
+            assert!(matches!(
+                parser.try_parse(),
+                Err(ParseError::InvalidFormat(_))
+            ));
+        }
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"=2000\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::InvalidLength));
+
+        // Framing agrees with decoding
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*1\r\n=6\r\ntxt:hi\r\n+OK\r\n");
+        assert_eq!(parser.split_frame().unwrap().len(), 16);
+    }
+
+    #[test]
+    fn test_legacy_verbatim_strings() {
+        let mut parser = Parser::with_config(ParserConfig {
+            legacy_verbatim_strings: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"=txt:hi\r\n=-1\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::VerbatimString(Some(Box::new(
+                Verbatim::txt("hi")
+            )))))
+        );
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::VerbatimString(None)))
+        );
+
+        parser.read_buf(b"*1\r\n=txt:hi\r\n");
+        assert_eq!(parser.split_frame().unwrap().len(), 13);
+    }
+
+    #[test]
+    fn test_bulk_error_length_prefixed() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"!21\r\nSYNTAX invalid syntax\r\n!0\r\n\r\n!4\r\na\r\nb\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkError(Some(
+                "SYNTAX invalid syntax".into()
+            ))))
+        );
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkError(Some("".into()))))
+        );
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkError(Some("a\r\nb".into()))))
+        );
+
+        // Round trip through the encoder and the framing
+        let value = RespValue::BulkError(Some("ERR x".into()));
+        parser.read_buf(&value.as_bytes());
+        assert_eq!(parser.peek_frame(), Ok(Some(value.clone())));
+        assert_eq!(parser.split_frame().unwrap().len(), 11);
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"!5\r\nERR xyz\r\n");
+        assert!(matches!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_legacy_bulk_errors() {
+        let mut parser = Parser::with_config(ParserConfig {
+            legacy_bulk_errors: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"!ERR x\r\n!-1\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkError(Some("ERR x".into()))))
+        );
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::BulkError(None))));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let popularity = || {
+            vec![(
+                RespValue::SimpleString("key-popularity".into()),
+                RespValue::Map(Some(Box::from(vec![(
+                    RespValue::BulkString(Some("a".into())),
+                    RespValue::Double(0.1923),
+                )]))),
+            )]
+        };
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"|1\r\n+key-popularity\r\n%1\r\n$1\r\na\r\n,0.1923\r\n*2\r\n:2039123\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b":9543892\r\n");
+        let value = parser.try_parse().unwrap().unwrap();
+        assert_eq!(
+            value,
+            RespValue::Attribute(Box::new(Attributed {
+                attributes: popularity(),
+                value: RespValue::Array(Some(Box::from(vec![
+                    RespValue::Integer(2039123),
+                    RespValue::Integer(9543892),
+                ]))),
+            }))
+        );
+        assert_eq!(value.kind(), RespKind::Attribute);
+
+        // An attributed element still counts as one element
+        parser.read_buf(b"*2\r\n|0\r\n:1\r\n:2\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::Attribute(Box::new(Attributed {
+                    attributes: vec![],
+                    value: RespValue::Integer(1),
+                })),
+                RespValue::Integer(2),
+            ])))))
+        );
+
+        // Encoding round trips, framing included
+        let bytes = value.as_bytes();
+        parser.read_buf(&bytes);
+        parser.read_buf(&bytes);
+        assert_eq!(parser.split_frame().unwrap().len(), bytes.len());
+        assert_eq!(parser.try_parse(), Ok(Some(value)));
+
+        parser.read_buf(b"|-1\r\n");
+        assert!(matches!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_streamed_string() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"$?\r\n;4\r\ntest\r\n;0\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::BulkString(Some("test".into()))))
+        );
+
+        // Chunks arrive across reads and are assembled in order
+        let input = b"*2\r\n$?\r\n;5\r\nhello\r\n;6\r\n world\r\n;0\r\n:1\r\n";
+        for split in 1..input.len() {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(&input[..split]);
+            let first = parser.try_parse();
+            assert!(matches!(
+                first,
+                Err(ParseError::UnexpectedEof | ParseError::NotEnoughData)
+            ));
+            parser.read_buf(&input[split..]);
+            assert_eq!(
+                parser.try_parse(),
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
+                    RespValue::BulkString(Some("hello world".into())),
+                    RespValue::Integer(1),
+                ])))))
+            );
+        }
+
+        // Framing agrees with decoding
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(input);
+        parser.read_buf(b"+OK\r\n");
+        assert_eq!(parser.split_frame().unwrap().len(), input.len());
+    }
+
+    #[test]
+    fn test_streamed_string_invalid() {
+        for input in [
+            &b"$?\r\n:4\r\n"[..],
+            b"$?\r\n;-1\r\n",
+            b"$?\r\n;2\r\nabc\r\n",
+        ] {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(input);
+            assert!(matches!(
+                parser.try_parse(),
+                Err(ParseError::InvalidFormat(_))
+            ));
+        }
+
+        // The assembled length is bounded like any bulk string
+        let mut parser = Parser::new(10, 8);
+        parser.read_buf(b"$?\r\n;4\r\ntest\r\n;4\r\ntest\r\n;0\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::InvalidLength));
+
+        // Recovery skips the whole streamed string
+        let mut parser = Parser::with_config(ParserConfig {
+            recover_errors: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"$?\r\n;2\r\n\xff\xfe\r\n;0\r\n+OK\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+    }
+
+    #[test]
+    fn test_streamed_aggregates() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*?\r\n:1\r\n*?\r\n.\r\n~?\r\n+a\r\n.\r\n.\r\n%?\r\n+k\r\n:1\r\n.\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::Integer(1),
+                RespValue::Array(Some(Box::default())),
+                RespValue::Set(Some(Box::from(vec![RespValue::SimpleString("a".into())]))),
+            ])))))
+        );
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(Box::from(vec![(
+                RespValue::SimpleString("k".into()),
+                RespValue::Integer(1)
+            )])))))
+        );
+
+        // Elements arrive across reads, inside fixed length aggregates too
+        let input = b"*2\r\n*?\r\n$3\r\nfoo\r\n*1\r\n:2\r\n.\r\n:3\r\n";
+        let expected = RespValue::Array(Some(Box::from(vec![
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("foo".into())),
+                RespValue::Array(Some(Box::from(vec![RespValue::Integer(2)]))),
+            ]))),
+            RespValue::Integer(3),
+        ])));
+        for split in 1..input.len() {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(&input[..split]);
+            assert!(matches!(
+                parser.try_parse(),
+                Err(ParseError::UnexpectedEof | ParseError::NotEnoughData)
+            ));
+            parser.read_buf(&input[split..]);
+            assert_eq!(parser.try_parse(), Ok(Some(expected.clone())));
+        }
+
+        // Framing agrees with decoding
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(input);
+        parser.read_buf(b"+OK\r\n");
+        assert_eq!(parser.split_frame().unwrap().len(), input.len());
+    }
+
+    #[test]
+    fn test_streamed_aggregates_invalid() {
+        for input in [
+            &b".\r\n"[..],
+            b"*1\r\n.\r\n",
+            b"%?\r\n:1\r\n.\r\n",
+            b"*?\r\n.x\r\n",
+        ] {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(input);
+            assert!(matches!(
+                parser.try_parse(),
+                Err(ParseError::InvalidFormat(_))
+            ));
+        }
+
+        // The remainder of a rejected streamed aggregate is dropped up to its end marker
+        let mut parser = Parser::with_config(ParserConfig {
+            max_depth: 2,
+            recover_errors: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*?\r\n*1\r\n*1\r\n:1\r\n:2\r\n*?\r\n.\r\n.\r\n+OK\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::SimpleString("OK".into())))
+        );
+        assert_eq!(parser.stats().parse_errors_by_kind.invalid_depth, 1);
+    }
+
+    #[test]
+    fn test_double_integer_form() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b",10\r\n,-3\r\n,0\r\n,1e4\r\n,1E-2\r\n,2.5e+3\r\n");
+        for expected in [10.0, -3.0, 0.0, 1e4, 0.01, 2500.0] {
+            assert_eq!(parser.try_parse(), Ok(Some(RespValue::Double(expected))));
+        }
+
+        parser.read_buf(b",1e\r\n");
+        assert!(matches!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_big_number_policies() {
+        let input = b"(+123\r\n(007\r\n(-0\r\n(-00\r\n(+0\r\n(-42\r\n";
+        let parse_all = |big_numbers| {
+            let mut parser = Parser::with_config(ParserConfig {
+                big_numbers,
+                ..ParserConfig::default()
+            });
+            parser.read_buf(input);
+            (0..6)
+                .map(|_| match parser.try_parse() {
+                    Ok(Some(RespValue::BigNumber(n))) => Ok(n.into_owned()),
+                    other => Err(other),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            parse_all(BigNumberPolicy::Keep),
+            ["+123", "007", "-0", "-00", "+0", "-42"].map(|n| Ok(n.to_string()))
+        );
+        assert_eq!(
+            parse_all(BigNumberPolicy::Normalize),
+            ["123", "7", "0", "0", "0", "-42"].map(|n| Ok(n.to_string()))
+        );
+
+        for (input, big_numbers) in [
+            (&b"(007\r\n"[..], BigNumberPolicy::Reject),
+            (b"(-0\r\n", BigNumberPolicy::Reject),
+            (b"(-00\r\n", BigNumberPolicy::Reject),
+            (b"(\r\n", BigNumberPolicy::Keep),
+            (b"(-\r\n", BigNumberPolicy::Keep),
+            (b"(+\r\n", BigNumberPolicy::Normalize),
+            (b"(1-2\r\n", BigNumberPolicy::Keep),
+            (b"(++1\r\n", BigNumberPolicy::Keep),
+        ] {
+            let mut parser = Parser::with_config(ParserConfig {
+                big_numbers,
+                ..ParserConfig::default()
+            });
+            parser.read_buf(input);
+            assert!(matches!(
+                parser.try_parse(),
+                Err(ParseError::InvalidFormat(_))
+            ));
+        }
+
+        let mut parser = Parser::with_config(ParserConfig {
+            big_numbers: BigNumberPolicy::Reject,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"(+123\r\n(0\r\n(-42\r\n");
+        for expected in ["+123", "0", "-42"] {
+            assert_eq!(
+                parser.try_parse(),
+                Ok(Some(RespValue::BigNumber(expected.into())))
+            );
+        }
+    }
+
+    #[test]
+    fn test_set_protocol() {
+        let resp2 = ParserConfig {
+            protocol: Protocol::Resp2,
+            ..ParserConfig::default()
+        };
+        for input in [
+            &b"%1\r\n+a\r\n:1\r\n"[..],
+            b"_\r\n",
+            b"*1\r\n,1.5\r\n",
+            b"$?\r\n;1\r\na\r\n;0\r\n",
+        ] {
+            let mut parser = Parser::with_config(resp2.clone());
+            parser.read_buf(input);
+            assert!(matches!(
+                parser.try_parse(),
+                Err(ParseError::InvalidFormat(_))
+            ));
+            parser.read_buf(b"\r\n");
+            assert!(matches!(
+                parser.split_frame(),
+                Err(ParseError::InvalidFormat(_))
+            ));
+        }
+
+        // Only lengths can be streamed; `?` is plain text elsewhere
+        for (input, value) in [
+            (&b"+?\r\n"[..], RespValue::SimpleString("?".into())),
+            (b"-?\r\n", RespValue::Error("?".into())),
+        ] {
+            let mut parser = Parser::with_config(ParserConfig {
+                speculate: true,
+                ..resp2.clone()
+            });
+            parser.read_buf(input);
+            assert_eq!(parser.split_frame().as_deref(), Ok(input));
+            parser.read_buf(input);
+            parser.read_buf(input);
+            assert_eq!(parser.peek_frame(), Ok(Some(value.clone())));
+            assert_eq!(parser.try_parse(), Ok(Some(value.clone())));
+            assert_eq!(parser.try_parse(), Ok(Some(value.clone())));
+            let span = crate::span::Spans::with_config(input, resp2.clone()).next();
+            assert_eq!(span.unwrap().unwrap().value, value);
+        }
+
+        // A connection upgraded by `HELLO 3` keeps its buffered input
+        let mut parser = Parser::with_config(resp2);
+        parser.read_buf(b"*2\r\n$5\r\nHELLO\r\n$1\r\n3\r\n%1\r\n+proto\r\n:3\r\n*1\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("HELLO".into())),
+                RespValue::BulkString(Some("3".into())),
+            ])))))
+        );
+        parser.set_protocol(Protocol::Resp3);
+        assert_eq!(parser.config().protocol, Protocol::Resp3);
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(Box::from(vec![(
+                RespValue::SimpleString("proto".into()),
+                RespValue::Integer(3)
+            )])))))
+        );
+
+        // A frame partially parsed before a downgrade is finished under the new rules
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.set_protocol(Protocol::Resp2);
+        parser.read_buf(b"#t\r\n");
+        assert!(matches!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_map_keys() {
+        let input = b"%2\r\n+a\r\n:1\r\n+a\r\n:2\r\n%2\r\n+a\r\n:1\r\n$1\r\na\r\n:2\r\n";
+
+        // Off by default
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(input);
+        assert!(parser.try_parse().unwrap().is_some());
+        assert!(parser.take_warnings().is_empty());
+
+        let mut parser = Parser::with_config(ParserConfig {
+            detect_duplicate_keys: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(input);
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Map(Some(Box::from(vec![
+                (RespValue::SimpleString("a".into()), RespValue::Integer(1)),
+                (RespValue::SimpleString("a".into()), RespValue::Integer(2)),
+            ])))))
+        );
+        // Keys of different types are different keys
+        assert!(parser.try_parse().unwrap().is_some());
+        assert_eq!(parser.take_warnings(), vec![ParseWarning::DuplicateMapKey]);
+
+        let mut parser = Parser::with_config(ParserConfig {
+            detect_duplicate_keys: true,
+            strict: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*2\r\n%2\r\n:1\r\n:1\r\n:1\r\n:2\r\n:3\r\n:7\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat("Duplicate map key".into()))
+        );
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(7))));
+    }
+
+    #[test]
+    fn test_duplicate_set_members() {
+        let mut parser = Parser::with_config(ParserConfig {
+            detect_duplicate_members: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"~3\r\n:1\r\n$1\r\n1\r\n:1\r\n~2\r\n*1\r\n:1\r\n*1\r\n:2\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Set(Some(Box::from(vec![
+                RespValue::Integer(1),
+                RespValue::BulkString(Some("1".into())),
+                RespValue::Integer(1),
+            ])))))
+        );
+        assert!(parser.try_parse().unwrap().is_some());
+        assert_eq!(
+            parser.take_warnings(),
+            vec![ParseWarning::DuplicateSetMember]
+        );
+
+        let mut parser = Parser::with_config(ParserConfig {
+            detect_duplicate_members: true,
+            strict: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"%1\r\n:0\r\n~2\r\n*1\r\n:1\r\n*1\r\n:1\r\n:7\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Err(ParseError::InvalidFormat("Duplicate set member".into()))
+        );
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(7))));
+    }
+
+    #[test]
+    fn test_requests_only() {
+        let config = ParserConfig {
+            requests_only: true,
+            ..ParserConfig::default()
+        };
+        let mut parser = Parser::with_config(config.clone());
+        parser.read_buf(b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("GET".into())),
+                RespValue::BulkString(Some("k".into())),
+            ])))))
+        );
+
+        for (input, got) in [
+            (&b"*1\r\n:1\r\n"[..], "expected '$', got ':'"),
+            (b"*1\r\n*1\r\n$1\r\na\r\n", "expected '$', got '*'"),
+            (b"+PING\r\n", "expected '*', got '+'"),
+            (b"PING\r\n", "expected '*', got 'P'"),
+        ] {
+            let error = ParseError::InvalidFormat(format!("Protocol error: {got}").into());
+            let mut parser = Parser::with_config(config.clone());
+            parser.read_buf(input);
+            assert_eq!(parser.split_frame(), Err(error.clone()));
+            assert_eq!(parser.try_parse(), Err(error));
+        }
+
+        for input in [
+            &b"*?\r\n$1\r\na\r\n.\r\n"[..],
+            b"*1\r\n$?\r\n;1\r\na\r\n;0\r\n",
+        ] {
+            let mut parser = Parser::with_config(config.clone());
+            parser.read_buf(input);
+            assert!(parser.split_frame().is_err());
+            assert!(parser.try_parse().is_err());
+        }
+
+        // Framing alone accepts the same requests
+        let mut parser = Parser::with_config(config.clone());
+        parser.read_buf(b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n");
+        assert_eq!(
+            parser.split_frame().as_deref(),
+            Ok(&b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n"[..])
+        );
+    }
+
+    #[test]
+    fn test_inline_commands() {
+        let mut parser = Parser::with_config(ParserConfig {
+            requests_only: true,
+            inline_commands: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"SET k  v\r\n\r\n*1\r\n$4\r\nPING\r\nPI");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("SET".into())),
+                RespValue::BulkString(Some("k".into())),
+                RespValue::BulkString(Some("v".into())),
+            ])))))
+        );
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("PING".into()))
+            ])))))
+        );
+        assert!(parser.try_parse().is_err());
+        parser.read_buf(b"NG\r\n");
+        assert_eq!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some("PING".into()))
+            ])))))
+        );
+        // Framing alone splits the same commands; a blank line goes with the frame
+        // after it
+        let mut parser = Parser::with_config(ParserConfig {
+            requests_only: true,
+            inline_commands: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"SET k  v\r\n\r\n*1\r\n$4\r\nPING\r\nPI");
+        assert_eq!(parser.split_frame().as_deref(), Ok(&b"SET k  v\r\n"[..]));
+        assert_eq!(
+            parser.split_frame().as_deref(),
+            Ok(&b"\r\n*1\r\n$4\r\nPING\r\n"[..])
+        );
+        assert_eq!(parser.split_frame(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"NG\r\n");
+        assert_eq!(parser.split_frame().as_deref(), Ok(&b"PING\r\n"[..]));
+        parser.read_buf(b"\xff\r\n");
+        assert_eq!(parser.split_frame(), Err(ParseError::InvalidUtf8));
+    }
+
+    #[test]
+    fn test_shared_bulks() {
+        let mut parser = Parser::with_config(ParserConfig {
+            shared_bulks: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*3\r\n$3\r\nGET\r\n$2\r\n\xff\x00\r\n$0\r\n\r\n$5\r\nhel");
+        assert_eq!(
+            parser.try_parse_with_len(),
+            Ok(Some((
+                RespValue::Array(Some(Box::from(vec![
+                    RespValue::BulkBytes(Box::new(Bytes::from_static(b"GET"))),
+                    RespValue::BulkBytes(Box::new(Bytes::from_static(b"\xff\x00"))),
+                    RespValue::BulkBytes(Box::new(Bytes::new())),
+                ]))),
+                27
+            )))
+        );
+        assert_eq!(parser.peek_frame(), Err(ParseError::NotEnoughData));
+        parser.read_buf(b"lo\r\n");
+        assert_eq!(
+            parser.peek_frame(),
+            Ok(Some(RespValue::BulkBytes(Box::new(Bytes::from_static(
+                b"hello"
+            )))))
+        );
+        let value = parser.try_parse().unwrap().unwrap();
+        assert_eq!(value.as_bytes(), b"$5\r\nhello\r\n");
+        assert_eq!(value.kind(), RespKind::BulkString);
+        assert!(parser.buffer().is_empty());
+    }
+
+    #[test]
+    fn test_max_frame_memory_resumes_at_next_frame() {
+        let mut parser = Parser::with_config(ParserConfig {
+            max_frame_memory: Some(16),
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"$20\r\n01234567890123456789\r\n+OK\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::MemoryBudgetExceeded));
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::OK)));
+
+        // The rest of an aggregate rejected mid-way is skipped, even when it has yet
+        // to arrive
+        let value_size = std::mem::size_of::<RespValue>();
+        let mut parser = Parser::with_config(ParserConfig {
+            max_frame_memory: Some(3 * value_size + 10),
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*3\r\n$5\r\nhello\r\n$6\r\nworld!\r\n*2\r\n:1\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::MemoryBudgetExceeded));
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b":2\r\n:3\r\n");
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(3))));
+
+        // So is every element of an aggregate too large to allocate for
+        let mut parser = Parser::with_config(ParserConfig {
+            max_frame_memory: Some(value_size),
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*2\r\n*2\r\n:1\r\n:2\r\n:3\r\n+OK\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::MemoryBudgetExceeded));
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::OK)));
+    }
+
+    #[test]
+    fn test_split_frame_skips_rejected_input() {
+        // The rest of a frame rejected by `try_parse` is never handed out
+        let mut parser = Parser::new(2, 1024);
+        parser.read_buf(b"*1\r\n*1\r\n*1\r\n:1\r\n+OK\r\n");
+        assert_eq!(parser.try_parse(), Err(ParseError::InvalidDepth));
+        assert_eq!(parser.split_frame().unwrap().as_ref(), b"+OK\r\n");
+
+        // Neither is input skipped while recovering from an error
+        let mut parser = Parser::with_config(ParserConfig {
+            recover_errors: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"?garbage");
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        parser.read_buf(b"\r\n:1\r\n");
+        assert_eq!(parser.split_frame().unwrap().as_ref(), b":1\r\n");
+    }
+
+    #[test]
+    fn test_split_frame_clears_peek() {
+        let mut parser = Parser::with_config(ParserConfig {
+            speculate: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"+OK\r\n:1\r\n");
+        assert!(parser.try_parse().unwrap().is_some());
+        assert_eq!(parser.peek(), Some(&NextFrame::Complete { len: 4 }));
+
+        assert_eq!(parser.split_frame().unwrap().as_ref(), b":1\r\n");
+        assert_eq!(parser.peek(), None);
+    }
+
+    #[test]
+    fn test_requests_only_negative_lengths() {
+        let config = ParserConfig {
+            requests_only: true,
+            ..ParserConfig::default()
+        };
+        let error = ParseError::InvalidFormat(crate::error_text::INVALID_BULK_LENGTH.into());
+        let mut parser = Parser::with_config(config.clone());
+        parser.read_buf(b"*2\r\n$3\r\nGET\r\n$-1\r\n");
+        assert_eq!(parser.split_frame(), Err(error.clone()));
+        assert_eq!(parser.try_parse(), Err(error));
+
+        // Empty and null commands are skipped
+        let ping = b"*1\r\n$4\r\nPING\r\n";
+        for prefix in [&b"*0\r\n"[..], b"*-1\r\n", b"*0\r\n*-1\r\n"] {
+            let input = [prefix, &ping[..]].concat();
+            let mut parser = Parser::with_config(config.clone());
+            parser.read_buf(&input);
+            assert_eq!(parser.split_frame(), Ok(Bytes::from(input)));
+
+            let mut parser = Parser::with_config(config.clone());
+            parser.read_buf(prefix);
+            assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+            parser.read_buf(ping);
+            assert_eq!(
+                parser.try_parse(),
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
+                    RespValue::BulkString(Some("PING".into())),
+                ])))))
+            );
+            assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        }
+    }
 }
@@ -0,0 +1,96 @@
+use crate::parser::{Parser, ParserConfig};
+use std::sync::Mutex;
+
+// Parsers whose buffer grew past this are dropped instead of pooled
+const DEFAULT_MAX_BUFFER: usize = 64 * 1024;
+
+/// A pool of reset parsers, for servers handling many short-lived connections.
+///
+/// Released parsers keep their read buffer and aggregate stack, so acquiring one
+/// allocates nothing once the pool is warm. The pool can be shared between threads,
+/// e.g. behind an `Arc`.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::parser::ParserConfig;
+/// use stream_resp::pool::ParserPool;
+///
+/// let pool = ParserPool::new(ParserConfig::default(), 1024);
+/// let mut parser = pool.acquire();
+/// parser.read_buf(b"+OK\r\n");
+/// assert!(parser.try_parse().unwrap().is_some());
+/// pool.release(parser);
+/// assert_eq!(pool.idle(), 1);
+/// ```
+#[derive(Debug)]
+pub struct ParserPool {
+    config: ParserConfig,
+    max_idle: usize,
+    max_buffer: usize,
+    idle: Mutex<Vec<Parser>>,
+}
+
+impl ParserPool {
+    /// Creates an empty pool of parsers using `config`, keeping at most `max_idle`
+    /// released parsers.
+    pub fn new(config: ParserConfig, max_idle: usize) -> Self {
+        ParserPool {
+            config,
+            max_idle,
+            max_buffer: DEFAULT_MAX_BUFFER,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Sets the buffer capacity above which released parsers are dropped rather than
+    /// kept, so a single huge frame does not pin its memory in the pool. 64 KiB by
+    /// default.
+    pub fn with_max_buffer(mut self, max_buffer: usize) -> Self {
+        self.max_buffer = max_buffer;
+        self
+    }
+
+    /// Creates `count` parsers up front, up to `max_idle`.
+    pub fn prefill(&self, count: usize) {
+        let mut idle = self.lock();
+        let count = count.min(self.max_idle.saturating_sub(idle.len()));
+        idle.extend((0..count).map(|_| Parser::with_config(self.config.clone())));
+    }
+
+    /// Returns a fresh parser, reusing an idle one if available.
+    pub fn acquire(&self) -> Parser {
+        self.lock()
+            .pop()
+            .unwrap_or_else(|| Parser::with_config(self.config.clone()))
+    }
+
+    /// Resets `parser` and keeps it for a later `acquire`.
+    ///
+    /// The parser is dropped instead if the pool is full or its buffer grew past the
+    /// pool's maximum. It should come from this pool, as its configuration is kept.
+    pub fn release(&self, mut parser: Parser) {
+        if parser.buffer().capacity() > self.max_buffer {
+            return;
+        }
+        parser.reset();
+        let mut idle = self.lock();
+        if idle.len() < self.max_idle {
+            idle.push(parser);
+        }
+    }
+
+    /// Returns the number of idle parsers in the pool.
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Parser>> {
+        // A panic while holding the lock cannot leave the list inconsistent
+        self.idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+//EOF
@@ -0,0 +1,47 @@
+use crate::parser::{ParseError, ParserConfig};
+use crate::pool::ParserPool;
+use crate::resp::RespValue;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_released_parsers_are_reset_and_reused() {
+        let pool = ParserPool::new(ParserConfig::default(), 4);
+        let mut parser = pool.acquire();
+        parser.read_buf(b"+OK\r\n*2\r\n:1\r\n");
+        assert!(parser.try_parse().unwrap().is_some());
+        assert_eq!(parser.try_parse(), Err(ParseError::UnexpectedEof));
+        let capacity = parser.buffer().capacity();
+        pool.release(parser);
+        assert_eq!(pool.idle(), 1);
+
+        let mut parser = pool.acquire();
+        assert_eq!(pool.idle(), 0);
+        assert!(parser.buffer().is_empty());
+        assert!(parser.buffer().capacity() >= capacity);
+        assert_eq!(parser.stats().frames_parsed, 0);
+        parser.read_buf(b":7\r\n");
+        assert_eq!(parser.try_parse(), Ok(Some(RespValue::Integer(7))));
+    }
+
+    #[test]
+    fn test_pool_limits() {
+        let pool = ParserPool::new(ParserConfig::default(), 2).with_max_buffer(8 * 1024);
+        pool.prefill(5);
+        assert_eq!(pool.idle(), 2);
+
+        let mut big = pool.acquire();
+        big.read_buf(&vec![b'x'; 16 * 1024]);
+        pool.release(big);
+        assert_eq!(pool.idle(), 1);
+
+        for _ in 0..3 {
+            pool.release(pool.acquire());
+        }
+        pool.release(ParserPool::new(ParserConfig::default(), 0).acquire());
+        pool.release(ParserPool::new(ParserConfig::default(), 0).acquire());
+        assert_eq!(pool.idle(), 2);
+    }
+}
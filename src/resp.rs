@@ -1,33 +1,248 @@
+use crate::error_text::ErrorReply;
+use crate::parser::is_verbatim_prefix;
+use bytes::{BufMut, Bytes};
 use std::borrow::Cow;
-use std::convert::TryFrom; // Add TryFrom import
-use std::fmt; // Add fmt import for error display
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
 
-#[derive(Debug, Clone)]
+/// The size of a `RespValue` in bytes on 64-bit targets: a tag and a payload of up
+/// to three words, the size of an inline `Cow<str>`. Aggregates, verbatim strings
+/// and the `Bytes` handle of `BulkBytes` are boxed, so none of them adds to it.
+///
+/// Two words would take boxing every string too, costing an extra allocation per
+/// parsed string, so strings stay inline and the size stays that of the original
+/// enum instead.
+#[cfg(target_pointer_width = "64")]
+pub const RESP_VALUE_SIZE: usize = 32;
+
+#[cfg(target_pointer_width = "64")]
+const _: () = assert!(std::mem::size_of::<RespValue>() == RESP_VALUE_SIZE);
+
+/// A RESP value.
+///
+/// Strings are stored inline, so parsing one allocates at most its text, and
+/// interned or borrowed strings not even that. Aggregates are boxed, so that
+/// arrays of values stay cheap to move and clone.
+#[derive(Clone, Default)]
 #[repr(C, align(8))]
 pub enum RespValue<'a> {
-    // Largest variants first (16 bytes or more)
-    Array(Option<Vec<RespValue<'a>>>),
-    Map(Option<Vec<(RespValue<'a>, RespValue<'a>)>>),
-    Set(Option<Vec<RespValue<'a>>>),
-    Push(Option<Vec<RespValue<'a>>>),
+    // Boxed aggregates (16 bytes)
+    Array(Option<Box<[RespValue<'a>]>>),
+    Map(Option<Box<[(RespValue<'a>, RespValue<'a>)]>>),
+    Set(Option<Box<[RespValue<'a>]>>),
+    Push(Option<Box<[RespValue<'a>]>>),
+    /// A value preceded by RESP3 attributes (`|`).
+    Attribute(Box<Attributed<'a>>),
 
-    // Variants with Cow (16 bytes)
+    // Inline strings (up to 24 bytes)
     SimpleString(Cow<'a, str>),
     Error(Cow<'a, str>),
     BulkString(Option<Cow<'a, str>>),
     BulkError(Option<Cow<'a, str>>),
-    VerbatimString(Option<Cow<'a, str>>),
+    VerbatimString(Option<Box<Verbatim<'a>>>),
     BigNumber(Cow<'a, str>),
+    /// A bulk string whose payload shares the buffer it was parsed from; see
+    /// `ParserConfig::shared_bulks`. Encodes exactly like `BulkString`.
+    BulkBytes(Box<Bytes>),
 
     // 8-byte variants
     Integer(i64),
     Double(f64),
+    /// A bulk string of `len` bytes whose body was handed to
+    /// `Parser::stream_bulks` instead of being buffered.
+    BulkStreamed {
+        len: usize,
+    },
 
     // Small variants (1 byte)
     Boolean(bool),
+    /// The RESP3 null, `_`.
+    ///
+    /// The RESP2 nulls `$-1` and `*-1` are read as `BulkString(None)` and
+    /// `Array(None)` instead, so every null encodes back to the form it was read in.
+    #[default]
+    Null,
+}
+
+/// The type of a `RespValue`, without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RespKind {
+    SimpleString,
+    Error,
+    Integer,
+    BulkString,
+    Array,
     Null,
+    Boolean,
+    Double,
+    BigNumber,
+    BulkError,
+    VerbatimString,
+    Map,
+    Set,
+    Push,
+    Attribute,
+}
+
+impl RespKind {
+    /// Returns the type marker starting values of this kind on the wire, such as
+    /// `b'+'` for `SimpleString`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::{RespKind, RespValue};
+    ///
+    /// assert_eq!(RespValue::Integer(1).kind().type_byte(), b':');
+    /// assert_eq!(RespKind::from_type_byte(b'%'), Some(RespKind::Map));
+    /// ```
+    pub const fn type_byte(self) -> u8 {
+        match self {
+            RespKind::SimpleString => b'+',
+            RespKind::Error => b'-',
+            RespKind::Integer => b':',
+            RespKind::BulkString => b'$',
+            RespKind::Array => b'*',
+            RespKind::Null => b'_',
+            RespKind::Boolean => b'#',
+            RespKind::Double => b',',
+            RespKind::BigNumber => b'(',
+            RespKind::BulkError => b'!',
+            RespKind::VerbatimString => b'=',
+            RespKind::Map => b'%',
+            RespKind::Set => b'~',
+            RespKind::Push => b'>',
+            RespKind::Attribute => b'|',
+        }
+    }
+
+    /// Returns the kind of values starting with the type marker `byte`, or `None` if
+    /// it is no type marker.
+    pub const fn from_type_byte(byte: u8) -> Option<RespKind> {
+        Some(match byte {
+            b'+' => RespKind::SimpleString,
+            b'-' => RespKind::Error,
+            b':' => RespKind::Integer,
+            b'$' => RespKind::BulkString,
+            b'*' => RespKind::Array,
+            b'_' => RespKind::Null,
+            b'#' => RespKind::Boolean,
+            b',' => RespKind::Double,
+            b'(' => RespKind::BigNumber,
+            b'!' => RespKind::BulkError,
+            b'=' => RespKind::VerbatimString,
+            b'%' => RespKind::Map,
+            b'~' => RespKind::Set,
+            b'>' => RespKind::Push,
+            b'|' => RespKind::Attribute,
+            _ => return None,
+        })
+    }
+}
+
+/// A value together with the attributes sent ahead of it, such as the
+/// `key-popularity` hints of client-side caching.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Attributed<'a> {
+    pub attributes: Vec<(RespValue<'a>, RespValue<'a>)>,
+    pub value: RespValue<'a>,
+}
+
+/// The payload of a verbatim string: a three character format such as `txt` or `mkd`,
+/// and the text in that format.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::Verbatim;
+///
+/// let verbatim = Verbatim::parse("mkd:# Title".into()).unwrap();
+/// assert_eq!(verbatim.format(), "mkd");
+/// assert_eq!(verbatim.text(), "# Title");
+/// assert!(Verbatim::new("markdown", "# Title").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Verbatim<'a> {
+    format: [u8; 3],
+    text: Cow<'a, str>,
+}
+
+impl<'a> Verbatim<'a> {
+    /// Creates a verbatim string, or returns `None` if `format` isn't three ASCII
+    /// letters or digits.
+    pub fn new(format: &str, text: impl Into<Cow<'a, str>>) -> Option<Self> {
+        let format: [u8; 3] = format.as_bytes().try_into().ok()?;
+        if !format.iter().all(u8::is_ascii_alphanumeric) {
+            return None;
+        }
+        Some(Verbatim {
+            format,
+            text: text.into(),
+        })
+    }
+
+    /// Creates a plain text verbatim string.
+    pub fn txt(text: impl Into<Cow<'a, str>>) -> Self {
+        Verbatim {
+            format: *b"txt",
+            text: text.into(),
+        }
+    }
+
+    /// Creates a markdown verbatim string.
+    pub fn mkd(text: impl Into<Cow<'a, str>>) -> Self {
+        Verbatim {
+            format: *b"mkd",
+            text: text.into(),
+        }
+    }
+
+    /// Splits a payload as sent on the wire, `<format>:<text>`. Returns `None` if it
+    /// doesn't start with a valid format and `:`.
+    pub fn parse(payload: Cow<'a, str>) -> Option<Self> {
+        if !is_verbatim_prefix(payload.as_bytes()) {
+            return None;
+        }
+        let format = payload.as_bytes()[..3].try_into().ok()?;
+        let text = match payload {
+            Cow::Borrowed(payload) => Cow::Borrowed(&payload[4..]),
+            Cow::Owned(mut payload) => {
+                payload.drain(..4);
+                Cow::Owned(payload)
+            }
+        };
+        Some(Verbatim { format, text })
+    }
+
+    /// Returns the format, such as `txt`.
+    pub fn format(&self) -> &str {
+        std::str::from_utf8(&self.format).expect("formats are ASCII")
+    }
+
+    /// Returns the text, without the format.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the length of the payload on the wire, format and `:` included.
+    pub fn payload_len(&self) -> usize {
+        self.format.len() + 1 + self.text.len()
+    }
+
+    pub fn into_owned(self) -> Verbatim<'static> {
+        Verbatim {
+            format: self.format,
+            text: Cow::Owned(self.text.into_owned()),
+        }
+    }
 }
 
+/// Values are equal if they are of the same variant with equal payloads.
+///
+/// Doubles compare by value, except that all NaNs are equal to each other, so
+/// equality is a proper equivalence and `RespValue` can implement `Eq` and `Hash`.
+/// `0.0` and `-0.0` stay equal, as they are for `f64`.
 impl PartialEq for RespValue<'_> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
@@ -35,21 +250,546 @@ impl PartialEq for RespValue<'_> {
             (RespValue::Error(a), RespValue::Error(b)) => *a == *b,
             (RespValue::Integer(a), RespValue::Integer(b)) => a == b,
             (RespValue::BulkString(a), RespValue::BulkString(b)) => *a == *b,
+            (RespValue::BulkBytes(a), RespValue::BulkBytes(b)) => a == b,
+            (RespValue::BulkStreamed { len: a }, RespValue::BulkStreamed { len: b }) => a == b,
             (RespValue::Array(a), RespValue::Array(b)) => *a == *b,
             (RespValue::Null, RespValue::Null) => true,
             (RespValue::Boolean(a), RespValue::Boolean(b)) => a == b,
-            (RespValue::Double(a), RespValue::Double(b)) => a == b,
+            (RespValue::Double(a), RespValue::Double(b)) => double_bits(*a) == double_bits(*b),
             (RespValue::BigNumber(a), RespValue::BigNumber(b)) => *a == *b,
             (RespValue::BulkError(a), RespValue::BulkError(b)) => *a == *b,
             (RespValue::VerbatimString(a), RespValue::VerbatimString(b)) => *a == *b,
             (RespValue::Map(a), RespValue::Map(b)) => *a == *b,
             (RespValue::Set(a), RespValue::Set(b)) => *a == *b,
             (RespValue::Push(a), RespValue::Push(b)) => *a == *b,
+            (RespValue::Attribute(a), RespValue::Attribute(b)) => *a == *b,
             _ => false,
         }
     }
 }
 
+impl Eq for RespValue<'_> {}
+
+/// Consistent with `PartialEq`: doubles hash their value with all NaNs and both
+/// zeros hashing alike.
+impl Hash for RespValue<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            RespValue::Array(values) | RespValue::Set(values) | RespValue::Push(values) => {
+                values.hash(state)
+            }
+            RespValue::Map(entries) => entries.hash(state),
+            RespValue::Attribute(attributed) => attributed.hash(state),
+            RespValue::SimpleString(s) | RespValue::Error(s) | RespValue::BigNumber(s) => {
+                s.hash(state)
+            }
+            RespValue::BulkString(s) | RespValue::BulkError(s) => s.hash(state),
+            RespValue::VerbatimString(v) => v.hash(state),
+            RespValue::BulkBytes(b) => b.hash(state),
+            RespValue::Integer(i) => i.hash(state),
+            RespValue::Double(d) => double_bits(*d).hash(state),
+            RespValue::BulkStreamed { len } => len.hash(state),
+            RespValue::Boolean(b) => b.hash(state),
+            RespValue::Null => {}
+        }
+    }
+}
+
+/// A total order across all values, for sorting, deduplicating and `BTreeMap` keys.
+///
+/// Like Redis sorts numbers before strings, values are ordered by type first:
+/// `Null`, `Boolean`, `Integer`, `Double`, `BigNumber`, `SimpleString`,
+/// `BulkString`, `BulkBytes`, `BulkStreamed`, `VerbatimString`, `Error`,
+/// `BulkError`, `Array`, `Set`, `Push`, `Map` and `Attribute`. Values of the same
+/// type compare by payload: null strings and aggregates before all others, strings
+/// bytewise, aggregates element by element. Doubles follow `f64::total_cmp`, with
+/// NaNs after `inf` and both zeros equal, consistent with `PartialEq`.
+impl Ord for RespValue<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use RespValue::*;
+        match (self, other) {
+            (Null, Null) => Ordering::Equal,
+            (Boolean(a), Boolean(b)) => a.cmp(b),
+            (Integer(a), Integer(b)) => a.cmp(b),
+            (Double(a), Double(b)) => canonical_double(*a).total_cmp(&canonical_double(*b)),
+            (BigNumber(a), BigNumber(b))
+            | (SimpleString(a), SimpleString(b))
+            | (Error(a), Error(b)) => a.cmp(b),
+            (BulkString(a), BulkString(b)) | (BulkError(a), BulkError(b)) => a.cmp(b),
+            (BulkBytes(a), BulkBytes(b)) => a.cmp(b),
+            (BulkStreamed { len: a }, BulkStreamed { len: b }) => a.cmp(b),
+            (VerbatimString(a), VerbatimString(b)) => a.cmp(b),
+            (Array(a), Array(b)) | (Set(a), Set(b)) | (Push(a), Push(b)) => a.cmp(b),
+            (Map(a), Map(b)) => a.cmp(b),
+            (Attribute(a), Attribute(b)) => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+}
+
+impl PartialOrd for RespValue<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl RespValue<'_> {
+    /// The position of the value's type in the order documented on `Ord`.
+    fn type_rank(&self) -> u8 {
+        match self {
+            RespValue::Null => 0,
+            RespValue::Boolean(_) => 1,
+            RespValue::Integer(_) => 2,
+            RespValue::Double(_) => 3,
+            RespValue::BigNumber(_) => 4,
+            RespValue::SimpleString(_) => 5,
+            RespValue::BulkString(_) => 6,
+            RespValue::BulkBytes(_) => 7,
+            RespValue::BulkStreamed { .. } => 8,
+            RespValue::VerbatimString(_) => 9,
+            RespValue::Error(_) => 10,
+            RespValue::BulkError(_) => 11,
+            RespValue::Array(_) => 12,
+            RespValue::Set(_) => 13,
+            RespValue::Push(_) => 14,
+            RespValue::Map(_) => 15,
+            RespValue::Attribute(_) => 16,
+        }
+    }
+}
+
+/// Returns the number of decimal digits of `n`.
+pub(crate) fn decimal_len(n: u64) -> usize {
+    n.checked_ilog10().map_or(1, |digits| digits as usize + 1)
+}
+
+/// Writes the line announcing a bulk payload or aggregate of `len`, such as `*3\r\n`.
+pub(crate) fn put_header<B: BufMut>(out: &mut B, marker: u8, len: usize) {
+    out.put_u8(marker);
+    out.put_slice(itoa::Buffer::new().format(len).as_bytes());
+    out.put_slice(b"\r\n");
+}
+
+/// Passes the digits of a finite double to `emit`, in pieces, as `options` ask for.
+/// By default these are the shortest digits that parse back to the same value, as
+/// ryu finds them, without the `.0` ryu keeps on whole numbers. Unless
+/// `double_exponents` is set, ryu's exponent notation is spelled out in plain
+/// digits, the way `Display` writes doubles.
+fn double_digits(d: f64, options: EncodeOptions, emit: &mut impl FnMut(&[u8])) {
+    if let Some(precision) = options.double_precision {
+        return precise_double_digits(d, precision, options.double_exponents, emit);
+    }
+    let mut buffer = ryu::Buffer::new();
+    let formatted = buffer.format_finite(d);
+    let formatted = formatted.strip_suffix(".0").unwrap_or(formatted);
+    let Some((mantissa, exponent)) = formatted
+        .split_once('e')
+        .filter(|_| !options.double_exponents)
+    else {
+        emit(formatted.as_bytes());
+        return;
+    };
+    let mantissa = strip_minus(mantissa, emit);
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let exponent = exponent.parse().unwrap_or(0);
+    plain_digits(int.as_bytes(), frac.as_bytes(), exponent, emit);
+}
+
+/// Passes the digits of a finite double to `emit` the way C's `%.{precision}g`
+/// writes them: rounded to `precision` significant digits, without trailing zeros,
+/// and with an exponent such as `e+21` or `e-07` when it is below -4 or not below
+/// `precision`. Unless `exponents` is set, such an exponent is spelled out instead.
+fn precise_double_digits(d: f64, precision: u8, exponents: bool, emit: &mut impl FnMut(&[u8])) {
+    /// Room for a sign, 255 digits, the point and the longest exponent, `e-324`.
+    struct Formatted {
+        bytes: [u8; 264],
+        len: usize,
+    }
+
+    impl std::fmt::Write for Formatted {
+        fn write_str(&mut self, s: &str) -> std::fmt::Result {
+            let end = self.len + s.len();
+            let bytes = self.bytes.get_mut(self.len..end).ok_or(std::fmt::Error)?;
+            bytes.copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    // As in C, a precision of 0 means 1
+    let precision = precision.max(1);
+    let mut formatted = Formatted {
+        bytes: [0; 264],
+        len: 0,
+    };
+    let _ = std::fmt::Write::write_fmt(
+        &mut formatted,
+        format_args!("{:.*e}", usize::from(precision) - 1, d),
+    );
+    let formatted = std::str::from_utf8(&formatted.bytes[..formatted.len]).unwrap_or("0e0");
+    let (mantissa, exponent) = formatted.split_once('e').unwrap_or((formatted, "0"));
+    let mantissa = strip_minus(mantissa, emit);
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let frac = frac.trim_end_matches('0');
+    let exponent: isize = exponent.parse().unwrap_or(0);
+    if !exponents || (-4..isize::from(precision)).contains(&exponent) {
+        plain_digits(int.as_bytes(), frac.as_bytes(), exponent, emit);
+        return;
+    }
+    emit(int.as_bytes());
+    if !frac.is_empty() {
+        emit(b".");
+        emit(frac.as_bytes());
+    }
+    emit(if exponent < 0 { b"e-" } else { b"e+" });
+    // C writes at least two digits of exponent
+    if exponent.unsigned_abs() < 10 {
+        emit(b"0");
+    }
+    emit(
+        itoa::Buffer::new()
+            .format(exponent.unsigned_abs())
+            .as_bytes(),
+    );
+}
+
+/// Emits the minus sign of `digits`, if it has one, and returns the rest.
+fn strip_minus<'d>(digits: &'d str, emit: &mut impl FnMut(&[u8])) -> &'d str {
+    match digits.strip_prefix('-') {
+        Some(digits) => {
+            emit(b"-");
+            digits
+        }
+        None => digits,
+    }
+}
+
+/// Passes the digits `int.frac` times `10^exponent` to `emit`, without an exponent.
+/// `int` is a single digit, as both ryu and `{:e}` write it.
+fn plain_digits(int: &[u8], frac: &[u8], exponent: isize, emit: &mut impl FnMut(&[u8])) {
+    fn zeros(mut n: usize, emit: &mut impl FnMut(&[u8])) {
+        const ZEROS: [u8; 64] = [b'0'; 64];
+        while n > 0 {
+            let len = n.min(ZEROS.len());
+            emit(&ZEROS[..len]);
+            n -= len;
+        }
+    }
+
+    // Where the point goes, counted in digits from the first one
+    let point = int.len() as isize + exponent;
+    let digits = int.len() + frac.len();
+    if point <= 0 {
+        emit(b"0.");
+        zeros(point.unsigned_abs(), emit);
+        emit(int);
+        emit(frac);
+    } else if point as usize >= digits {
+        emit(int);
+        emit(frac);
+        zeros(point as usize - digits, emit);
+    } else {
+        let (before, after) = frac.split_at(point as usize - int.len());
+        emit(int);
+        emit(before);
+        emit(b".");
+        emit(after);
+    }
+}
+
+/// Returns the length of a double as `as_bytes_with` writes it, without the type
+/// marker and CRLF.
+fn double_len(d: f64, options: EncodeOptions) -> usize {
+    if d.is_nan() {
+        3
+    } else if d.is_infinite() {
+        if d > 0.0 { 3 } else { 4 }
+    } else {
+        let mut len = 0;
+        double_digits(d, options, &mut |piece| len += piece.len());
+        len
+    }
+}
+
+/// Summarizes `values` until they encode to more than `max_bytes`, then counts the
+/// rest; see `RespValue::summarize`.
+fn summarize_elements<'a>(values: &[RespValue<'a>], max_bytes: usize) -> Vec<RespValue<'a>> {
+    let mut summary = Vec::new();
+    let mut used = 0;
+    for (i, value) in values.iter().enumerate() {
+        if used > max_bytes {
+            let rest = format!("<{} more elements>", values.len() - i);
+            summary.push(RespValue::SimpleString(rest.into()));
+            break;
+        }
+        let value = value.summarize(max_bytes);
+        used += value.encoded_len();
+        summary.push(value);
+    }
+    summary
+}
+
+/// Like `summarize_elements`, for map entries.
+fn summarize_entries<'a>(
+    entries: &[(RespValue<'a>, RespValue<'a>)],
+    max_bytes: usize,
+) -> Vec<(RespValue<'a>, RespValue<'a>)> {
+    let mut summary = Vec::new();
+    let mut used = 0;
+    for (i, (key, value)) in entries.iter().enumerate() {
+        if used > max_bytes {
+            let rest = format!("<{} more entries>", entries.len() - i);
+            summary.push((RespValue::SimpleString(rest.into()), RespValue::Null));
+            break;
+        }
+        let (key, value) = (key.summarize(max_bytes), value.summarize(max_bytes));
+        used += key.encoded_len() + value.encoded_len();
+        summary.push((key, value));
+    }
+    summary
+}
+
+/// Small values are gathered until about this many bytes are ready to be written;
+/// bulk payloads longer than this bypass the scratch buffer.
+const WRITE_CHUNK: usize = 8 * 1024;
+
+/// Splits the encoding of a value into the writes of `RespValue::write_to_io` and
+/// `write_to_async`: small values gathered into chunks, and long bulk payloads on
+/// their own, borrowed from the value. Walks with a stack rather than recursion so
+/// async writers can await between chunks.
+struct WriteChunks<'v, 'a> {
+    stack: Vec<Pending<'v, 'a>>,
+    scratch: Vec<u8>,
+    /// A long payload to return after the scratch buffer ending in its header.
+    payload: Option<&'v [u8]>,
+    /// Whether the scratch buffer has been returned and is to be cleared.
+    returned: bool,
+}
+
+/// Values still to be written by `WriteChunks`.
+enum Pending<'v, 'a> {
+    Value(&'v RespValue<'a>),
+    Values(std::slice::Iter<'v, RespValue<'a>>),
+    /// Map or attribute entries, and the value of the entry whose key was written.
+    Entries(
+        std::slice::Iter<'v, (RespValue<'a>, RespValue<'a>)>,
+        Option<&'v RespValue<'a>>,
+    ),
+}
+
+impl<'v, 'a> WriteChunks<'v, 'a> {
+    fn new(value: &'v RespValue<'a>) -> Self {
+        WriteChunks {
+            stack: vec![Pending::Value(value)],
+            scratch: Vec::new(),
+            payload: None,
+            returned: false,
+        }
+    }
+
+    /// Returns the next bytes to write, or `None` once the value is written.
+    fn next_chunk(&mut self) -> Option<&[u8]> {
+        if self.returned {
+            self.scratch.clear();
+            self.returned = false;
+        }
+        if let Some(payload) = self.payload.take() {
+            self.scratch.put_slice(b"\r\n");
+            return Some(payload);
+        }
+        while self.scratch.len() < WRITE_CHUNK {
+            let Some(value) = self.next_value() else {
+                break;
+            };
+            let marker = value.kind().type_byte();
+            match value {
+                RespValue::BulkString(Some(s)) | RespValue::BulkError(Some(s))
+                    if s.len() > WRITE_CHUNK =>
+                {
+                    put_header(&mut self.scratch, marker, s.len());
+                    self.payload = Some(s.as_bytes());
+                    break;
+                }
+                RespValue::BulkBytes(b) if b.len() > WRITE_CHUNK => {
+                    put_header(&mut self.scratch, marker, b.len());
+                    self.payload = Some(b);
+                    break;
+                }
+                RespValue::VerbatimString(Some(v)) if v.text().len() > WRITE_CHUNK => {
+                    put_header(&mut self.scratch, marker, v.payload_len());
+                    self.scratch.put_slice(v.format().as_bytes());
+                    self.scratch.put_u8(b':');
+                    self.payload = Some(v.text().as_bytes());
+                    break;
+                }
+                RespValue::Array(Some(values))
+                | RespValue::Set(Some(values))
+                | RespValue::Push(Some(values)) => {
+                    put_header(&mut self.scratch, marker, values.len());
+                    self.stack.push(Pending::Values(values.iter()));
+                }
+                RespValue::Map(Some(entries)) => {
+                    put_header(&mut self.scratch, marker, entries.len());
+                    self.stack.push(Pending::Entries(entries.iter(), None));
+                }
+                RespValue::Attribute(attributed) => {
+                    put_header(&mut self.scratch, marker, attributed.attributes.len());
+                    self.stack.push(Pending::Value(&attributed.value));
+                    self.stack
+                        .push(Pending::Entries(attributed.attributes.iter(), None));
+                }
+                _ => value.write_to(&mut self.scratch),
+            }
+        }
+        if self.scratch.is_empty() {
+            return None;
+        }
+        self.returned = true;
+        Some(&self.scratch)
+    }
+
+    fn next_value(&mut self) -> Option<&'v RespValue<'a>> {
+        loop {
+            let next = match self.stack.last_mut()? {
+                Pending::Value(value) => {
+                    let value = *value;
+                    self.stack.pop();
+                    return Some(value);
+                }
+                Pending::Values(values) => values.next(),
+                Pending::Entries(entries, next_value) => match next_value.take() {
+                    Some(value) => Some(value),
+                    None => entries.next().map(|(k, v)| {
+                        *next_value = Some(v);
+                        k
+                    }),
+                },
+            };
+            match next {
+                Some(value) => return Some(value),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Returns `true` if every element of `a` can be paired with a distinct, equal
+/// element of `b`.
+fn unordered_eq<T>(a: &[T], b: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut matched = vec![false; b.len()];
+    a.iter().all(
+        |x| match (0..b.len()).find(|&i| !matched[i] && eq(x, &b[i])) {
+            Some(i) => {
+                matched[i] = true;
+                true
+            }
+            None => false,
+        },
+    )
+}
+
+/// Returns `d` with every NaN and both zeros mapped to one value each.
+fn canonical_double(d: f64) -> f64 {
+    if d.is_nan() {
+        f64::NAN
+    } else if d == 0.0 {
+        0.0
+    } else {
+        d
+    }
+}
+
+/// Returns the bits of `d` with every NaN and both zeros mapped to one pattern each.
+fn double_bits(d: f64) -> u64 {
+    canonical_double(d).to_bits()
+}
+
+/// A map key decoded into its RESP3 type.
+///
+/// RESP3 allows any value as a map key, so keys are not assumed to be strings.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapKey<'a> {
+    /// Simple, bulk or verbatim string keys.
+    Str(&'a str),
+    Int(i64),
+    Double(f64),
+    /// Any other key, including null strings and aggregates.
+    Other(&'a RespValue<'a>),
+}
+
+impl<'a> From<&'a RespValue<'a>> for MapKey<'a> {
+    fn from(value: &'a RespValue<'a>) -> Self {
+        match value {
+            RespValue::SimpleString(s) => MapKey::Str(s),
+            RespValue::BulkString(Some(s)) => MapKey::Str(s),
+            RespValue::BulkBytes(b) if let Ok(s) = std::str::from_utf8(b) => MapKey::Str(s),
+            RespValue::VerbatimString(Some(v)) => MapKey::Str(v.text()),
+            RespValue::Integer(i) => MapKey::Int(*i),
+            RespValue::Double(d) => MapKey::Double(*d),
+            other => MapKey::Other(other),
+        }
+    }
+}
+
+/// A `RespValue` that owns all of its data, for storing values without carrying a
+/// lifetime around.
+///
+/// Dereferences to `RespValue<'static>`, which is what the parser returns, and
+/// converts losslessly to and from `RespValue` of any lifetime.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::{OwnedRespValue, RespValue};
+///
+/// let name = String::from("stream");
+/// let owned = OwnedRespValue::from(RespValue::SimpleString(name.as_str().into()));
+/// drop(name);
+/// assert_eq!(*owned, RespValue::SimpleString("stream".into()));
+/// let value: RespValue = owned.into();
+/// assert_eq!(value.as_bytes(), b"+stream\r\n");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OwnedRespValue(RespValue<'static>);
+
+impl OwnedRespValue {
+    pub fn into_inner(self) -> RespValue<'static> {
+        self.0
+    }
+}
+
+impl std::ops::Deref for OwnedRespValue {
+    type Target = RespValue<'static>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for OwnedRespValue {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<RespValue<'_>> for OwnedRespValue {
+    fn from(value: RespValue<'_>) -> Self {
+        OwnedRespValue(value.into_owned())
+    }
+}
+
+impl<'a> From<OwnedRespValue> for RespValue<'a> {
+    fn from(value: OwnedRespValue) -> Self {
+        value.0
+    }
+}
+
 // Implement From and Into traits for RespValue
 impl From<String> for RespValue<'_> {
     fn from(value: String) -> Self {
@@ -69,6 +809,55 @@ impl From<i64> for RespValue<'_> {
     }
 }
 
+impl From<i32> for RespValue<'_> {
+    fn from(value: i32) -> Self {
+        RespValue::Integer(value.into())
+    }
+}
+
+impl From<u32> for RespValue<'_> {
+    fn from(value: u32) -> Self {
+        RespValue::Integer(value.into())
+    }
+}
+
+/// Values past `i64::MAX` become a `BigNumber`.
+impl From<u64> for RespValue<'_> {
+    fn from(value: u64) -> Self {
+        match i64::try_from(value) {
+            Ok(value) => RespValue::Integer(value),
+            Err(_) => RespValue::BigNumber(Cow::Owned(value.to_string())),
+        }
+    }
+}
+
+/// Values past `i64::MAX` become a `BigNumber`.
+impl From<usize> for RespValue<'_> {
+    fn from(value: usize) -> Self {
+        RespValue::from(value as u64)
+    }
+}
+
+/// A binary-safe bulk string, copying `value`.
+impl From<&[u8]> for RespValue<'_> {
+    fn from(value: &[u8]) -> Self {
+        RespValue::BulkBytes(Box::new(Bytes::copy_from_slice(value)))
+    }
+}
+
+/// A binary-safe bulk string, taking over the allocation of `value`.
+impl From<Vec<u8>> for RespValue<'_> {
+    fn from(value: Vec<u8>) -> Self {
+        RespValue::BulkBytes(Box::new(Bytes::from(value)))
+    }
+}
+
+impl From<Bytes> for RespValue<'_> {
+    fn from(value: Bytes) -> Self {
+        RespValue::BulkBytes(Box::new(value))
+    }
+}
+
 impl From<Option<String>> for RespValue<'_> {
     fn from(value: Option<String>) -> Self {
         RespValue::BulkString(value.map(Cow::Owned))
@@ -77,7 +866,7 @@ impl From<Option<String>> for RespValue<'_> {
 
 impl<'a> From<Vec<RespValue<'a>>> for RespValue<'a> {
     fn from(value: Vec<RespValue<'a>>) -> Self {
-        RespValue::Array(Some(value))
+        RespValue::Array(Some(Box::from(value)))
     }
 }
 
@@ -95,164 +884,1005 @@ impl From<f64> for RespValue<'_> {
 
 impl<'a> From<(RespValue<'a>, RespValue<'a>)> for RespValue<'a> {
     fn from(value: (RespValue<'a>, RespValue<'a>)) -> Self {
-        RespValue::Map(Some(vec![value]))
+        RespValue::Map(Some(Box::from(vec![value])))
     }
 }
 
 impl<'a> From<Vec<(RespValue<'a>, RespValue<'a>)>> for RespValue<'a> {
     fn from(value: Vec<(RespValue<'a>, RespValue<'a>)>) -> Self {
-        RespValue::Map(Some(value))
+        RespValue::Map(Some(Box::from(value)))
     }
 }
 
-impl Into<String> for RespValue<'_> {
-    fn into(self) -> String {
+/// Formats the value structurally, with `Some` and boxes left out: strings as
+/// `BulkString("foo")`, nulls as `BulkString(None)`, arrays as lists and maps as
+/// maps, so `{:#?}` prints nested replies one element per line.
+///
+/// A precision truncates string and bulk payloads to that many bytes, followed by
+/// their full length.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::RespValue;
+///
+/// let value = RespValue::Array(Some(Box::from(vec![
+///     RespValue::BulkString(Some("a long payload".into())),
+///     RespValue::Integer(1),
+/// ])));
+/// assert_eq!(format!("{:?}", value), r#"Array([BulkString("a long payload"), Integer(1)])"#);
+/// assert_eq!(
+///     format!("{:.6?}", value),
+///     r#"Array([BulkString("a long"... (14 bytes)), Integer(1)])"#
+/// );
+/// ```
+impl std::fmt::Debug for RespValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RespValue::Array(_) => "Array",
+            RespValue::Map(_) => "Map",
+            RespValue::Set(_) => "Set",
+            RespValue::Push(_) => "Push",
+            RespValue::Attribute(_) => "Attribute",
+            RespValue::SimpleString(_) => "SimpleString",
+            RespValue::Error(_) => "Error",
+            RespValue::BulkString(_) => "BulkString",
+            RespValue::BulkError(_) => "BulkError",
+            RespValue::VerbatimString(_) => "VerbatimString",
+            RespValue::BigNumber(_) => "BigNumber",
+            RespValue::BulkBytes(_) => "BulkBytes",
+            RespValue::Integer(_) => "Integer",
+            RespValue::Double(_) => "Double",
+            RespValue::BulkStreamed { .. } => "BulkStreamed",
+            RespValue::Boolean(_) => "Boolean",
+            RespValue::Null => "Null",
+        };
+        // Leaves stay on one line and aggregates open directly after their name, so
+        // `{:#?}` only breaks lines between elements.
         match self {
-            RespValue::SimpleString(value) => value.into_owned(),
-            _ => panic!("Cannot convert {:?} to String", self),
+            RespValue::SimpleString(s)
+            | RespValue::Error(s)
+            | RespValue::BigNumber(s)
+            | RespValue::BulkString(Some(s))
+            | RespValue::BulkError(Some(s)) => {
+                write!(f, "{}(", name)?;
+                DebugText(s).fmt(f)?;
+                f.write_str(")")
+            }
+            RespValue::BulkBytes(b) => {
+                write!(f, "{}(", name)?;
+                DebugBytes(b).fmt(f)?;
+                f.write_str(")")
+            }
+            RespValue::VerbatimString(Some(v)) => {
+                write!(f, "{} {{ format: {:?}, text: ", name, v.format())?;
+                DebugText(v.text()).fmt(f)?;
+                f.write_str(" }")
+            }
+            RespValue::Integer(i) => write!(f, "{}({})", name, i),
+            RespValue::Double(d) => write!(f, "{}({:?})", name, d),
+            RespValue::Boolean(b) => write!(f, "{}({})", name, b),
+            RespValue::BulkStreamed { len } => write!(f, "{} {{ len: {} }}", name, len),
+            RespValue::Array(Some(values))
+            | RespValue::Set(Some(values))
+            | RespValue::Push(Some(values)) => {
+                write!(f, "{}(", name)?;
+                f.debug_list().entries(values.iter()).finish()?;
+                f.write_str(")")
+            }
+            RespValue::Map(Some(entries)) => {
+                write!(f, "{}(", name)?;
+                DebugEntries(entries).fmt(f)?;
+                f.write_str(")")
+            }
+            RespValue::Attribute(attributed) => f
+                .debug_struct(name)
+                .field("attributes", &DebugEntries(&attributed.attributes))
+                .field("value", &attributed.value)
+                .finish(),
+            RespValue::Null => f.write_str(name),
+            RespValue::BulkString(None)
+            | RespValue::BulkError(None)
+            | RespValue::VerbatimString(None)
+            | RespValue::Array(None)
+            | RespValue::Set(None)
+            | RespValue::Push(None)
+            | RespValue::Map(None) => write!(f, "{}(None)", name),
         }
     }
 }
 
-impl Into<i64> for RespValue<'_> {
-    fn into(self) -> i64 {
-        match self {
-            RespValue::Integer(value) => value,
-            _ => panic!("Cannot convert {:?} to i64", self),
+/// Debug-formats map entries as a map.
+struct DebugEntries<'v, 'a>(&'v [(RespValue<'a>, RespValue<'a>)]);
+
+impl std::fmt::Debug for DebugEntries<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+/// Debug-formats a string payload, truncated to the formatter's precision.
+struct DebugText<'v>(&'v str);
+
+impl std::fmt::Debug for DebugText<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match f.precision() {
+            Some(limit) if self.0.len() > limit => {
+                let end = (0..=limit)
+                    .rev()
+                    .find(|&i| self.0.is_char_boundary(i))
+                    .unwrap_or(0);
+                write!(f, "{:?}... ({} bytes)", &self.0[..end], self.0.len())
+            }
+            _ => write!(f, "{:?}", self.0),
         }
     }
 }
 
-impl Into<Option<String>> for RespValue<'_> {
-    fn into(self) -> Option<String> {
-        match self {
-            RespValue::BulkString(value) => value.map(|v| v.into_owned()),
-            _ => panic!("Cannot convert {:?} to Option<String>", self),
+/// Debug-formats a bulk payload as a byte string, truncated to the formatter's
+/// precision.
+struct DebugBytes<'v>(&'v [u8]);
+
+impl std::fmt::Debug for DebugBytes<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match f.precision() {
+            Some(limit) if self.0.len() > limit => write!(
+                f,
+                "b\"{}\"... ({} bytes)",
+                self.0[..limit].escape_ascii(),
+                self.0.len()
+            ),
+            _ => write!(f, "b\"{}\"", self.0.escape_ascii()),
+        }
+    }
+}
+
+/// Renders the value the way `redis-cli` prints replies: bulk strings quoted and
+/// escaped, typed scalars such as `(integer) 5`, nulls as `(nil)`, and aggregates as
+/// numbered items with nested aggregates indented under their number. Attributes
+/// are not shown.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::parser::Parser;
+///
+/// let mut parser = Parser::new(10, 1024);
+/// parser.read_buf(b"*3\r\n:1\r\n*2\r\n$3\r\nfoo\r\n_\r\n+OK\r\n");
+/// let reply = parser.try_parse().unwrap().unwrap();
+/// assert_eq!(
+///     reply.to_string(),
+///     "1) (integer) 1\n2) 1) \"foo\"\n   2) (nil)\n3) OK"
+/// );
+/// ```
+impl std::fmt::Display for RespValue<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut out = String::new();
+        format_cli(self, "", &mut out);
+        f.write_str(out.strip_suffix('\n').unwrap_or(&out))
+    }
+}
+
+/// Appends `value` as `redis-cli` prints it, ending in a newline. `prefix` indents
+/// every line of an aggregate after its first.
+fn format_cli(value: &RespValue<'_>, prefix: &str, out: &mut String) {
+    use std::fmt::Write;
+
+    match value {
+        RespValue::SimpleString(s) => out.push_str(s),
+        RespValue::Error(s) | RespValue::BulkError(Some(s)) => {
+            let _ = write!(out, "(error) {}", s);
+        }
+        RespValue::BulkString(Some(s)) => quote_cli(s.as_bytes(), out),
+        RespValue::BulkBytes(b) => quote_cli(b, out),
+        RespValue::VerbatimString(Some(v)) => out.push_str(v.text()),
+        RespValue::BigNumber(s) => {
+            let _ = write!(out, "(big number) {}", s);
+        }
+        RespValue::Integer(i) => {
+            let _ = write!(out, "(integer) {}", i);
+        }
+        RespValue::Double(d) if d.is_nan() => out.push_str("(double) nan"),
+        RespValue::Double(d) if d.is_infinite() => out.push_str(if *d > 0.0 {
+            "(double) inf"
+        } else {
+            "(double) -inf"
+        }),
+        RespValue::Double(d) => {
+            let _ = write!(out, "(double) {}", d);
+        }
+        RespValue::Boolean(b) => out.push_str(if *b { "(true)" } else { "(false)" }),
+        RespValue::BulkStreamed { len } => {
+            let _ = write!(out, "(streamed) {} bytes", len);
         }
+        RespValue::Attribute(attributed) => return format_cli(&attributed.value, prefix, out),
+        RespValue::Array(Some(values)) => {
+            return format_cli_aggregate(values.len(), ')', "array", prefix, out, |i, p, out| {
+                format_cli(&values[i], p, out)
+            });
+        }
+        RespValue::Set(Some(values)) => {
+            return format_cli_aggregate(values.len(), '~', "set", prefix, out, |i, p, out| {
+                format_cli(&values[i], p, out)
+            });
+        }
+        RespValue::Push(Some(values)) => {
+            return format_cli_aggregate(values.len(), ')', "push", prefix, out, |i, p, out| {
+                format_cli(&values[i], p, out)
+            });
+        }
+        RespValue::Map(Some(entries)) => {
+            return format_cli_aggregate(entries.len(), '#', "hash", prefix, out, |i, p, out| {
+                format_cli(&entries[i].0, p, out);
+                out.pop();
+                out.push_str(" => ");
+                format_cli(&entries[i].1, p, out);
+            });
+        }
+        RespValue::Null
+        | RespValue::BulkString(None)
+        | RespValue::BulkError(None)
+        | RespValue::VerbatimString(None)
+        | RespValue::Array(None)
+        | RespValue::Set(None)
+        | RespValue::Push(None)
+        | RespValue::Map(None) => out.push_str("(nil)"),
+    }
+    out.push('\n');
+}
+
+/// Appends `len` items numbered from 1 and right-aligned, `1) ` or `1~ ` and so on,
+/// with `item` appending each one.
+fn format_cli_aggregate(
+    len: usize,
+    separator: char,
+    name: &str,
+    prefix: &str,
+    out: &mut String,
+    mut item: impl FnMut(usize, &str, &mut String),
+) {
+    use std::fmt::Write;
+
+    if len == 0 {
+        let _ = writeln!(out, "(empty {})", name);
+        return;
+    }
+    let width = decimal_len(len as u64);
+    let item_prefix = format!("{}{:2$}", prefix, "", width + 2);
+    for i in 0..len {
+        let line_prefix = if i == 0 { "" } else { prefix };
+        let _ = write!(out, "{}{:>3$}{} ", line_prefix, i + 1, separator, width);
+        item(i, &item_prefix, out);
+    }
+}
+
+/// Appends `bytes` quoted, escaping them the way `redis-cli` does.
+fn quote_cli(bytes: &[u8], out: &mut String) {
+    use std::fmt::Write;
+
+    out.push('"');
+    for &b in bytes {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            b' '..=b'~' => out.push(b as char),
+            _ => {
+                let _ = write!(out, "\\x{:02x}", b);
+            }
+        }
+    }
+    out.push('"');
+}
+
+/// A type that can index into a `RespValue`: `usize` for aggregate elements and
+/// strings for map keys.
+pub trait RespIndex: sealed::Sealed {
+    #[doc(hidden)]
+    fn index_into<'v, 'a>(&self, value: &'v RespValue<'a>) -> Option<&'v RespValue<'a>>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: Sealed + ?Sized> Sealed for &T {}
+}
+
+impl RespIndex for usize {
+    fn index_into<'v, 'a>(&self, value: &'v RespValue<'a>) -> Option<&'v RespValue<'a>> {
+        value.as_array()?.get(*self)
+    }
+}
+
+impl RespIndex for str {
+    fn index_into<'v, 'a>(&self, value: &'v RespValue<'a>) -> Option<&'v RespValue<'a>> {
+        value
+            .as_map()?
+            .iter()
+            .find(|(key, _)| key.as_str() == Some(self))
+            .map(|(_, value)| value)
+    }
+}
+
+impl RespIndex for String {
+    fn index_into<'v, 'a>(&self, value: &'v RespValue<'a>) -> Option<&'v RespValue<'a>> {
+        self.as_str().index_into(value)
     }
 }
 
-impl<'a> Into<Vec<RespValue<'a>>> for RespValue<'a> {
-    fn into(self) -> Vec<RespValue<'a>> {
+impl<T: RespIndex + ?Sized> RespIndex for &T {
+    fn index_into<'v, 'a>(&self, value: &'v RespValue<'a>) -> Option<&'v RespValue<'a>> {
+        (**self).index_into(value)
+    }
+}
+
+static NULL: RespValue<'static> = RespValue::Null;
+
+/// Indexes like `RespValue::get`, yielding `Null` when the element is absent so
+/// lookups can be chained.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::RespValue;
+///
+/// let reply = RespValue::Map(Some(Box::from(vec![(
+///     RespValue::BulkString(Some("ids".into())),
+///     RespValue::Array(Some(Box::from(vec![RespValue::Integer(7)]))),
+/// )])));
+/// assert_eq!(reply["ids"][0], RespValue::Integer(7));
+/// assert_eq!(reply["missing"][3], RespValue::Null);
+/// ```
+impl<'a, I: RespIndex> std::ops::Index<I> for RespValue<'a> {
+    type Output = RespValue<'a>;
+
+    fn index(&self, index: I) -> &RespValue<'a> {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+/// Iterates over the elements of an array, set or push. Null aggregates and other
+/// values, maps included, have none; see `RespValue::into_entries` for maps.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::RespValue;
+///
+/// let reply = RespValue::Array(Some(Box::from(vec![RespValue::Integer(1), RespValue::Integer(2)])));
+/// let sum: i64 = reply.iter().filter_map(RespValue::as_int).sum();
+/// assert_eq!(sum, 3);
+/// assert_eq!(reply.into_iter().count(), 2);
+/// assert_eq!(RespValue::Array(None).into_iter().count(), 0);
+/// ```
+impl<'a> IntoIterator for RespValue<'a> {
+    type Item = RespValue<'a>;
+    type IntoIter = std::vec::IntoIter<RespValue<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
         match self {
-            RespValue::Array(value) => value.unwrap().clone(),
-            RespValue::Set(value) => value.unwrap().clone(),
-            RespValue::Push(value) => value.unwrap().clone(),
-            _ => panic!("Cannot convert {:?} to Vec<RespValue>", self),
+            RespValue::Array(Some(values))
+            | RespValue::Set(Some(values))
+            | RespValue::Push(Some(values)) => values.into_iter(),
+            _ => Vec::new().into_iter(),
         }
     }
 }
 
-impl<'a> From<RespValue<'a>> for Vec<u8> {
-    fn from(value: RespValue<'a>) -> Vec<u8> {
+impl<'b, 'a> IntoIterator for &'b RespValue<'a> {
+    type Item = &'b RespValue<'a>;
+    type IntoIter = std::slice::Iter<'b, RespValue<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Collects values into an `Array`.
+///
+/// Sets and pushes are collected into a `Vec` first, or for a set without duplicates
+/// into a `builder::SetBuilder`.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::RespValue;
+///
+/// let array: RespValue = (1..=3).map(RespValue::Integer).collect();
+/// assert_eq!(array.as_bytes(), b"*3\r\n:1\r\n:2\r\n:3\r\n");
+/// let map: RespValue = [("a", 1)]
+///     .into_iter()
+///     .map(|(k, v)| (RespValue::from(k), RespValue::from(v)))
+///     .collect();
+/// assert_eq!(map.as_bytes(), b"%1\r\n+a\r\n:1\r\n");
+/// ```
+impl<'a> FromIterator<RespValue<'a>> for RespValue<'a> {
+    fn from_iter<I: IntoIterator<Item = RespValue<'a>>>(iter: I) -> Self {
+        RespValue::Array(Some(iter.into_iter().collect()))
+    }
+}
+
+/// Collects entries into a `Map`.
+impl<'a> FromIterator<(RespValue<'a>, RespValue<'a>)> for RespValue<'a> {
+    fn from_iter<I: IntoIterator<Item = (RespValue<'a>, RespValue<'a>)>>(iter: I) -> Self {
+        RespValue::Map(Some(iter.into_iter().collect()))
+    }
+}
+
+/// A map, with its entries in the map's iteration order.
+impl<'a, K, V, S> From<HashMap<K, V, S>> for RespValue<'a>
+where
+    K: Into<RespValue<'a>>,
+    V: Into<RespValue<'a>>,
+{
+    fn from(value: HashMap<K, V, S>) -> Self {
+        RespValue::Map(Some(
+            value
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        ))
+    }
+}
+
+/// A map, with its entries sorted by key.
+impl<'a, K, V> From<BTreeMap<K, V>> for RespValue<'a>
+where
+    K: Into<RespValue<'a>>,
+    V: Into<RespValue<'a>>,
+{
+    fn from(value: BTreeMap<K, V>) -> Self {
+        RespValue::Map(Some(
+            value
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        ))
+    }
+}
+
+/// A set, with its members in the set's iteration order.
+impl<'a, T, S> From<HashSet<T, S>> for RespValue<'a>
+where
+    T: Into<RespValue<'a>>,
+{
+    fn from(value: HashSet<T, S>) -> Self {
+        RespValue::Set(Some(value.into_iter().map(Into::into).collect()))
+    }
+}
+
+/// A set, with its members sorted.
+impl<'a, T> From<BTreeSet<T>> for RespValue<'a>
+where
+    T: Into<RespValue<'a>>,
+{
+    fn from(value: BTreeSet<T>) -> Self {
+        RespValue::Set(Some(value.into_iter().map(Into::into).collect()))
+    }
+}
+
+/// The error returned when converting a `RespValue` of the wrong type with
+/// `TryFrom`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FromRespError {
+    /// The type converted to.
+    pub expected: &'static str,
+    /// The type of the value converted from.
+    pub found: RespKind,
+}
+
+impl std::fmt::Display for FromRespError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cannot convert {:?} to {}", self.found, self.expected)
+    }
+}
+
+impl std::error::Error for FromRespError {}
+
+impl FromRespError {
+    fn new(expected: &'static str, value: &RespValue<'_>) -> Self {
+        FromRespError {
+            expected,
+            found: value.kind(),
+        }
+    }
+}
+
+/// Converts simple, bulk and verbatim strings.
+impl TryFrom<RespValue<'_>> for String {
+    type Error = FromRespError;
+
+    fn try_from(value: RespValue<'_>) -> Result<String, FromRespError> {
         match value {
-            RespValue::SimpleString(s) => format!("+{}\r\n", s.to_owned()).into_bytes(),
-            RespValue::Error(msg) => format!("-{}\r\n", msg.to_owned()).into_bytes(),
-            RespValue::Integer(i) => format!(":{}\r\n", i).into_bytes(),
-            RespValue::BulkString(s) => match s {
-                Some(s) => format!("${}\r\n{}\r\n", s.len(), s.to_owned()).into_bytes(),
-                None => "$-1\r\n".as_bytes().to_vec(),
-            },
-            RespValue::Null => "$-1\r\n".as_bytes().to_vec(),
-            RespValue::Array(arr) => {
-                let mut bytes = match &arr {
-                    Some(a) => format!("*{}\r\n", a.len()).into_bytes(),
-                    None => return "*-1\r\n".as_bytes().to_vec(),
-                };
-                if let Some(values) = arr {
-                    for value in values {
-                        bytes.extend(value.as_bytes());
-                    }
-                }
-                bytes
+            RespValue::SimpleString(s) | RespValue::BulkString(Some(s)) => Ok(s.into_owned()),
+            RespValue::VerbatimString(Some(v)) => Ok(v.text().to_owned()),
+            RespValue::BulkBytes(ref b) if let Ok(s) = std::str::from_utf8(b) => Ok(s.to_owned()),
+            _ => Err(FromRespError::new("String", &value)),
+        }
+    }
+}
+
+/// Converts strings like `String`, and null strings and nulls to `None`.
+impl TryFrom<RespValue<'_>> for Option<String> {
+    type Error = FromRespError;
+
+    fn try_from(value: RespValue<'_>) -> Result<Option<String>, FromRespError> {
+        match value {
+            RespValue::BulkString(None) | RespValue::VerbatimString(None) | RespValue::Null => {
+                Ok(None)
             }
-            _ => panic!("Cannot convert {:?} to Vec<u8>", value),
+            value => String::try_from(value)
+                .map(Some)
+                .map_err(|e| FromRespError {
+                    expected: "Option<String>",
+                    ..e
+                }),
         }
     }
 }
 
-impl Into<bool> for RespValue<'_> {
-    fn into(self) -> bool {
-        match self {
-            RespValue::Boolean(value) => value,
-            _ => panic!("Cannot convert {:?} to bool", self),
+impl TryFrom<RespValue<'_>> for i64 {
+    type Error = FromRespError;
+
+    fn try_from(value: RespValue<'_>) -> Result<i64, FromRespError> {
+        match value {
+            RespValue::Integer(value) => Ok(value),
+            _ => Err(FromRespError::new("i64", &value)),
         }
     }
 }
 
-impl Into<f64> for RespValue<'_> {
-    fn into(self) -> f64 {
-        match self {
-            RespValue::Double(value) => value,
-            _ => panic!("Cannot convert {:?} to f64", self),
+impl TryFrom<RespValue<'_>> for bool {
+    type Error = FromRespError;
+
+    fn try_from(value: RespValue<'_>) -> Result<bool, FromRespError> {
+        match value {
+            RespValue::Boolean(value) => Ok(value),
+            _ => Err(FromRespError::new("bool", &value)),
         }
     }
 }
 
-impl<'a> Into<Vec<(RespValue<'a>, RespValue<'a>)>> for RespValue<'a> {
-    fn into(self) -> Vec<(RespValue<'a>, RespValue<'a>)> {
-        match self {
-            RespValue::Map(value) => value.unwrap().clone(),
-            _ => panic!("Cannot convert {:?} to Vec<(RespValue, RespValue)>", self),
+impl TryFrom<RespValue<'_>> for f64 {
+    type Error = FromRespError;
+
+    fn try_from(value: RespValue<'_>) -> Result<f64, FromRespError> {
+        match value {
+            RespValue::Double(value) => Ok(value),
+            _ => Err(FromRespError::new("f64", &value)),
         }
     }
 }
 
-impl<'a> Default for RespValue<'a> {
-    fn default() -> Self {
-        RespValue::Null
+/// Converts the elements of arrays, sets and pushes.
+impl<'a> TryFrom<RespValue<'a>> for Vec<RespValue<'a>> {
+    type Error = FromRespError;
+
+    fn try_from(value: RespValue<'a>) -> Result<Vec<RespValue<'a>>, FromRespError> {
+        match value {
+            RespValue::Array(Some(values))
+            | RespValue::Set(Some(values))
+            | RespValue::Push(Some(values)) => Ok(values.into_vec()),
+            _ => Err(FromRespError::new("Vec<RespValue>", &value)),
+        }
+    }
+}
+
+impl<'a> TryFrom<RespValue<'a>> for Vec<(RespValue<'a>, RespValue<'a>)> {
+    type Error = FromRespError;
+
+    fn try_from(
+        value: RespValue<'a>,
+    ) -> Result<Vec<(RespValue<'a>, RespValue<'a>)>, FromRespError> {
+        match value {
+            RespValue::Map(Some(entries)) => Ok(entries.into_vec()),
+            _ => Err(FromRespError::new("Vec<(RespValue, RespValue)>", &value)),
+        }
+    }
+}
+
+impl<'a> From<RespValue<'a>> for Vec<u8> {
+    fn from(value: RespValue<'a>) -> Vec<u8> {
+        value.as_bytes()
     }
 }
 
+/// Panicking conversions, standing in for the `Into` impls that `TryFrom` replaced.
+///
+/// The standard library's blanket `TryFrom` impl for every `Into` rules out keeping
+/// both, so code that relied on `value.into()` can switch to these first and to
+/// `try_into` at its own pace.
+impl<'a> RespValue<'a> {
+    #[deprecated(
+        since = "1.3.0",
+        note = "panics on other types; use `String::try_from`"
+    )]
+    pub fn into_string_unchecked(self) -> String {
+        String::try_from(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[deprecated(
+        since = "1.3.0",
+        note = "panics on other types; use `Option<String>::try_from`"
+    )]
+    pub fn into_option_string_unchecked(self) -> Option<String> {
+        <Option<String>>::try_from(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[deprecated(since = "1.3.0", note = "panics on other types; use `i64::try_from`")]
+    pub fn into_i64_unchecked(self) -> i64 {
+        i64::try_from(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[deprecated(since = "1.3.0", note = "panics on other types; use `bool::try_from`")]
+    pub fn into_bool_unchecked(self) -> bool {
+        bool::try_from(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[deprecated(since = "1.3.0", note = "panics on other types; use `f64::try_from`")]
+    pub fn into_f64_unchecked(self) -> f64 {
+        f64::try_from(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[deprecated(
+        since = "1.3.0",
+        note = "panics on other types; use `Vec<RespValue>::try_from`"
+    )]
+    pub fn into_vec_unchecked(self) -> Vec<RespValue<'a>> {
+        <Vec<RespValue<'a>>>::try_from(self).unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[deprecated(
+        since = "1.3.0",
+        note = "panics on other types; use `Vec<(RespValue, RespValue)>::try_from`"
+    )]
+    pub fn into_map_unchecked(self) -> Vec<(RespValue<'a>, RespValue<'a>)> {
+        <Vec<(RespValue<'a>, RespValue<'a>)>>::try_from(self)
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+}
+
+/// Options for `RespValue::as_bytes_with`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    /// Writes doubles with an exponent where that is shorter, as in `,1e21\r\n`.
+    ///
+    /// Doubles are always written in canonical form: the shortest digits that parse
+    /// back to the same value, without a trailing `.0`. By default no exponent is used,
+    /// so `1e21` is written out in full.
+    pub double_exponents: bool,
+    /// Writes doubles rounded to this many significant digits, the way C's `%.17g`
+    /// writes them for Redis, instead of the shortest digits that parse back to the
+    /// same value. Trailing zeros are dropped, and with `double_exponents` an exponent
+    /// is used where `%g` would use one, as in `,1.0000000000000001e+21\r\n`.
+    pub double_precision: Option<u8>,
+    /// Writes `Null` as the RESP2 null bulk string, `$-1\r\n`, for clients that don't
+    /// speak RESP3. Other nulls are always written in the form they are in.
+    pub resp2_nulls: bool,
+}
+
 impl RespValue<'_> {
     pub fn as_bytes(&self) -> Vec<u8> {
+        self.as_bytes_with(EncodeOptions::default())
+    }
+
+    /// Encodes the value like `as_bytes`, with the given options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::{EncodeOptions, RespValue};
+    ///
+    /// let options = EncodeOptions {
+    ///     double_exponents: true,
+    ///     ..EncodeOptions::default()
+    /// };
+    /// assert_eq!(RespValue::Double(1e21).as_bytes_with(options), b",1e21\r\n");
+    /// assert_eq!(RespValue::Double(10.0).as_bytes_with(options), b",10\r\n");
+    ///
+    /// let redis = EncodeOptions {
+    ///     double_exponents: true,
+    ///     double_precision: Some(17),
+    ///     ..EncodeOptions::default()
+    /// };
+    /// assert_eq!(RespValue::Double(0.1).as_bytes_with(redis), b",0.10000000000000001\r\n");
+    /// ```
+    pub fn as_bytes_with(&self, options: EncodeOptions) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_len_with(options));
+        self.write_to_with(&mut bytes, options);
+        bytes
+    }
+
+    /// Appends the encoding of the value to an existing buffer, reserving room for
+    /// all of it up front.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bytes::BytesMut;
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let mut out = BytesMut::from(&b"+OK\r\n"[..]);
+    /// RespValue::Integer(7).encode_into(&mut out);
+    /// assert_eq!(&out[..], b"+OK\r\n:7\r\n");
+    /// ```
+    pub fn encode_into(&self, out: &mut bytes::BytesMut) {
+        out.reserve(self.encoded_len());
+        self.write_to(out);
+    }
+
+    /// Writes the encoding of the value to any `BufMut`, such as a `Vec<u8>` or a
+    /// network buffer, formatting numbers in place instead of through intermediate
+    /// allocations.
+    pub fn write_to(&self, out: &mut impl BufMut) {
+        self.write_to_with(out, EncodeOptions::default());
+    }
+
+    /// Same as `write_to`, encoding like `as_bytes_with` and the given options.
+    pub fn write_to_with<B: BufMut>(&self, out: &mut B, options: EncodeOptions) {
+        fn line<B: BufMut>(out: &mut B, marker: u8, payload: &[u8]) {
+            out.put_u8(marker);
+            out.put_slice(payload);
+            out.put_slice(b"\r\n");
+        }
+
+        fn bulk<B: BufMut>(out: &mut B, marker: u8, payload: &[u8]) {
+            put_header(out, marker, payload.len());
+            out.put_slice(payload);
+            out.put_slice(b"\r\n");
+        }
+
         match self {
-            RespValue::SimpleString(s) => format!("+{}\r\n", s).into_bytes(),
-            RespValue::Error(e) => format!("-{}\r\n", e).into_bytes(),
-            RespValue::Integer(i) => format!(":{}\r\n", i).into_bytes(),
-            RespValue::BulkString(Some(s)) => format!("${}\r\n{}\r\n", s.len(), s).into_bytes(),
-            RespValue::BulkString(None) => "$-1\r\n".as_bytes().to_vec(),
-            RespValue::Array(Some(arr)) => {
-                let mut bytes = format!("*{}\r\n", arr.len()).into_bytes();
-                for item in arr {
-                    bytes.extend(item.as_bytes());
+            RespValue::SimpleString(s) => line(out, b'+', s.as_bytes()),
+            RespValue::Error(e) => line(out, b'-', e.as_bytes()),
+            RespValue::Integer(i) => line(out, b':', itoa::Buffer::new().format(*i).as_bytes()),
+            RespValue::BulkString(Some(s)) => bulk(out, b'$', s.as_bytes()),
+            RespValue::BulkString(None) => out.put_slice(b"$-1\r\n"),
+            RespValue::BulkBytes(b) => bulk(out, b'$', b),
+            // The body went elsewhere, so only the header can be written
+            RespValue::BulkStreamed { len } => put_header(out, b'$', *len),
+            RespValue::Array(Some(values)) => {
+                put_header(out, b'*', values.len());
+                for value in values.iter() {
+                    value.write_to_with(out, options);
+                }
+            }
+            RespValue::Array(None) => out.put_slice(b"*-1\r\n"),
+            RespValue::Null if options.resp2_nulls => out.put_slice(b"$-1\r\n"),
+            RespValue::Null => out.put_slice(b"_\r\n"),
+            RespValue::Boolean(b) => out.put_slice(if *b { b"#t\r\n" } else { b"#f\r\n" }),
+            // RESP3 spells these `inf`, `-inf` and `nan`; Rust would write `NaN`
+            RespValue::Double(d) if d.is_nan() => out.put_slice(b",nan\r\n"),
+            RespValue::Double(d) if d.is_infinite() => {
+                out.put_slice(if *d > 0.0 { b",inf\r\n" } else { b",-inf\r\n" })
+            }
+            RespValue::Double(d) => {
+                out.put_u8(b',');
+                double_digits(*d, options, &mut |piece| out.put_slice(piece));
+                out.put_slice(b"\r\n");
+            }
+            RespValue::BigNumber(n) => line(out, b'(', n.as_bytes()),
+            RespValue::BulkError(Some(e)) => bulk(out, b'!', e.as_bytes()),
+            RespValue::BulkError(None) => out.put_slice(b"!-1\r\n"),
+            RespValue::VerbatimString(Some(v)) => {
+                put_header(out, b'=', v.payload_len());
+                out.put_slice(v.format().as_bytes());
+                out.put_u8(b':');
+                out.put_slice(v.text().as_bytes());
+                out.put_slice(b"\r\n");
+            }
+            RespValue::VerbatimString(None) => out.put_slice(b"=-1\r\n"),
+            RespValue::Map(Some(entries)) => {
+                put_header(out, b'%', entries.len());
+                for (k, v) in entries.iter() {
+                    k.write_to_with(out, options);
+                    v.write_to_with(out, options);
                 }
-                bytes
-            }
-            RespValue::Array(None) => "*-1\r\n".as_bytes().to_vec(),
-            RespValue::Null => "_\r\n".as_bytes().to_vec(),
-            RespValue::Boolean(b) => format!("#{}\r\n", if *b { "t" } else { "f" }).into_bytes(),
-            RespValue::Double(d) => format!(",{}\r\n", d).into_bytes(),
-            RespValue::BigNumber(n) => format!("({}\r\n", n).into_bytes(),
-            RespValue::BulkError(Some(e)) => format!("!{}\r\n", e).into_bytes(),
-            RespValue::BulkError(None) => "!-1\r\n".as_bytes().to_vec(),
-            RespValue::VerbatimString(Some(s)) => format!("={}\r\n", s).into_bytes(),
-            RespValue::VerbatimString(None) => "=-1\r\n".as_bytes().to_vec(),
-            RespValue::Map(Some(m)) => {
-                let mut bytes = format!("%{}\r\n", m.len()).into_bytes();
-                for (k, v) in m {
-                    bytes.extend(k.as_bytes());
-                    bytes.extend(v.as_bytes());
+            }
+            RespValue::Map(None) => out.put_slice(b"%-1\r\n"),
+            RespValue::Set(Some(values)) => {
+                put_header(out, b'~', values.len());
+                for value in values.iter() {
+                    value.write_to_with(out, options);
                 }
-                bytes
             }
-            RespValue::Map(None) => "%-1\r\n".as_bytes().to_vec(),
-            RespValue::Set(Some(s)) => {
-                let mut bytes = format!("~{}\r\n", s.len()).into_bytes();
-                for item in s {
-                    bytes.extend(item.as_bytes());
+            RespValue::Set(None) => out.put_slice(b"~-1\r\n"),
+            RespValue::Push(Some(values)) => {
+                put_header(out, b'>', values.len());
+                for value in values.iter() {
+                    value.write_to_with(out, options);
                 }
-                bytes
             }
-            RespValue::Set(None) => "~-1\r\n".as_bytes().to_vec(),
-            RespValue::Push(Some(p)) => {
-                let mut bytes = format!(">{}\r\n", p.len()).as_bytes().to_vec();
-                for item in p {
-                    bytes.extend(item.as_bytes());
+            RespValue::Push(None) => out.put_slice(b">-1\r\n"),
+            RespValue::Attribute(attributed) => {
+                put_header(out, b'|', attributed.attributes.len());
+                for (k, v) in &attributed.attributes {
+                    k.write_to_with(out, options);
+                    v.write_to_with(out, options);
                 }
-                bytes
+                attributed.value.write_to_with(out, options);
+            }
+        }
+    }
+
+    /// Writes the encoding of the value to `writer`, returning the number of bytes
+    /// written.
+    ///
+    /// The frame is never built in memory as a whole: small values are gathered into
+    /// writes of a few kilobytes, and large bulk payloads are written straight from
+    /// the value, so a `TcpStream` needs no `BufWriter` in front of it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let reply: RespValue = (1..=3).map(RespValue::Integer).collect();
+    /// let mut out = Vec::new();
+    /// assert_eq!(reply.write_to_io(&mut out).unwrap(), 16);
+    /// assert_eq!(out, b"*3\r\n:1\r\n:2\r\n:3\r\n");
+    /// ```
+    pub fn write_to_io<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<usize> {
+        let mut chunks = WriteChunks::new(self);
+        let mut written = 0;
+        while let Some(chunk) = chunks.next_chunk() {
+            writer.write_all(chunk)?;
+            written += chunk.len();
+        }
+        Ok(written)
+    }
+
+    /// Writes the encoding of the value to an async `writer` the way `write_to_io`
+    /// does, returning the number of bytes written. Neither flushes the writer.
+    ///
+    /// Cancelling the future leaves a partial frame on the writer.
+    #[cfg(feature = "tokio")]
+    pub async fn write_to_async<W>(&self, writer: &mut W) -> std::io::Result<usize>
+    where
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        use tokio::io::AsyncWriteExt;
+
+        let mut chunks = WriteChunks::new(self);
+        let mut written = 0;
+        while let Some(chunk) = chunks.next_chunk() {
+            writer.write_all(chunk).await?;
+            written += chunk.len();
+        }
+        Ok(written)
+    }
+
+    /// Returns the number of bytes `as_bytes` encodes the value to, without encoding
+    /// it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let value = RespValue::Array(Some(Box::from(vec![
+    ///     RespValue::BulkString(Some("GET".into())),
+    ///     RespValue::Integer(-12),
+    /// ])));
+    /// assert_eq!(value.encoded_len(), value.as_bytes().len());
+    /// ```
+    pub fn encoded_len(&self) -> usize {
+        self.encoded_len_with(EncodeOptions::default())
+    }
+
+    /// Same as `encoded_len`, for `as_bytes_with` and the given options.
+    pub fn encoded_len_with(&self, options: EncodeOptions) -> usize {
+        // Type marker and CRLF around a line
+        let line = |len: usize| 1 + len + 2;
+        let bulk = |len: usize| line(decimal_len(len as u64)) + len + 2;
+        match self {
+            RespValue::SimpleString(s) | RespValue::Error(s) | RespValue::BigNumber(s) => {
+                line(s.len())
+            }
+            RespValue::Integer(i) => line(decimal_len(i.unsigned_abs()) + (*i < 0) as usize),
+            RespValue::BulkString(Some(s)) | RespValue::BulkError(Some(s)) => bulk(s.len()),
+            RespValue::BulkBytes(b) => bulk(b.len()),
+            RespValue::VerbatimString(Some(v)) => bulk(v.payload_len()),
+            RespValue::BulkStreamed { len } => line(decimal_len(*len as u64)),
+            RespValue::BulkString(None)
+            | RespValue::BulkError(None)
+            | RespValue::VerbatimString(None)
+            | RespValue::Array(None)
+            | RespValue::Map(None)
+            | RespValue::Set(None)
+            | RespValue::Push(None) => line(2),
+            RespValue::Null if options.resp2_nulls => line(2),
+            RespValue::Null => line(0),
+            RespValue::Boolean(_) => line(1),
+            RespValue::Double(d) => line(double_len(*d, options)),
+            RespValue::Array(Some(values))
+            | RespValue::Set(Some(values))
+            | RespValue::Push(Some(values)) => {
+                line(decimal_len(values.len() as u64))
+                    + values
+                        .iter()
+                        .map(|v| v.encoded_len_with(options))
+                        .sum::<usize>()
+            }
+            RespValue::Map(Some(entries)) => {
+                line(decimal_len(entries.len() as u64))
+                    + entries
+                        .iter()
+                        .map(|(k, v)| k.encoded_len_with(options) + v.encoded_len_with(options))
+                        .sum::<usize>()
+            }
+            RespValue::Attribute(attributed) => {
+                line(decimal_len(attributed.attributes.len() as u64))
+                    + attributed
+                        .attributes
+                        .iter()
+                        .map(|(k, v)| k.encoded_len_with(options) + v.encoded_len_with(options))
+                        .sum::<usize>()
+                    + attributed.value.encoded_len_with(options)
+            }
+        }
+    }
+
+    /// Compares like `==`, except that the elements of sets and the entries of maps
+    /// and attributes may come in any order, as they do in replies from Redis.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let a = RespValue::Set(Some(Box::from(vec![RespValue::Integer(1), RespValue::Integer(2)])));
+    /// let b = RespValue::Set(Some(Box::from(vec![RespValue::Integer(2), RespValue::Integer(1)])));
+    /// assert_ne!(a, b);
+    /// assert!(a.semantic_eq(&b));
+    /// ```
+    pub fn semantic_eq(&self, other: &RespValue<'_>) -> bool {
+        self.semantic_eq_with(other, 0.0)
+    }
+
+    /// Same as `semantic_eq`, also treating doubles at most `tolerance` apart as
+    /// equal. NaNs are equal to each other.
+    ///
+    /// Unordered collections are matched greedily, each element with the first equal
+    /// one not matched yet, which takes quadratic time.
+    pub fn semantic_eq_with(&self, other: &RespValue<'_>, tolerance: f64) -> bool {
+        let eq = |a: &RespValue, b: &RespValue| a.semantic_eq_with(b, tolerance);
+        let entry_eq = |(ak, av): &(RespValue, RespValue), (bk, bv): &(RespValue, RespValue)| {
+            eq(ak, bk) && eq(av, bv)
+        };
+        match (self, other) {
+            (RespValue::Double(a), RespValue::Double(b)) => {
+                (a.is_nan() && b.is_nan()) || a == b || (a - b).abs() <= tolerance
+            }
+            (RespValue::Array(Some(a)), RespValue::Array(Some(b)))
+            | (RespValue::Push(Some(a)), RespValue::Push(Some(b))) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| eq(a, b))
             }
-            RespValue::Push(None) => ">-1\r\n".as_bytes().to_vec(),
+            (RespValue::Set(Some(a)), RespValue::Set(Some(b))) => unordered_eq(a, b, eq),
+            (RespValue::Map(Some(a)), RespValue::Map(Some(b))) => unordered_eq(a, b, entry_eq),
+            (RespValue::Attribute(a), RespValue::Attribute(b)) => {
+                unordered_eq(&a.attributes, &b.attributes, entry_eq) && eq(&a.value, &b.value)
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Splits a simple or bulk error into its code and message; `None` for other
+    /// values.
+    pub fn error_reply(&self) -> Option<ErrorReply<'_>> {
+        match self {
+            RespValue::Error(text) | RespValue::BulkError(Some(text)) => {
+                Some(ErrorReply::parse(text))
+            }
+            _ => None,
         }
     }
 
@@ -264,6 +1894,8 @@ impl RespValue<'_> {
             RespValue::BulkString(s) => {
                 RespValue::BulkString(s.map(|s| Cow::Owned(s.into_owned())))
             }
+            RespValue::BulkStreamed { len } => RespValue::BulkStreamed { len },
+            RespValue::BulkBytes(b) => RespValue::BulkBytes(b),
             RespValue::Array(arr) => {
                 RespValue::Array(arr.map(|a| a.into_iter().map(|v| v.into_owned()).collect()))
             }
@@ -272,8 +1904,8 @@ impl RespValue<'_> {
             RespValue::Double(d) => RespValue::Double(d),
             RespValue::BigNumber(n) => RespValue::BigNumber(Cow::Owned(n.into_owned())),
             RespValue::BulkError(e) => RespValue::BulkError(e.map(|e| Cow::Owned(e.into_owned()))),
-            RespValue::VerbatimString(s) => {
-                RespValue::VerbatimString(s.map(|s| Cow::Owned(s.into_owned())))
+            RespValue::VerbatimString(v) => {
+                RespValue::VerbatimString(v.map(|v| Box::new(v.into_owned())))
             }
             RespValue::Map(m) => RespValue::Map(m.map(|m| {
                 m.into_iter()
@@ -286,37 +1918,433 @@ impl RespValue<'_> {
             RespValue::Push(p) => {
                 RespValue::Push(p.map(|p| p.into_iter().map(|v| v.into_owned()).collect()))
             }
+            RespValue::Attribute(attributed) => {
+                let Attributed { attributes, value } = *attributed;
+                RespValue::Attribute(Box::new(Attributed {
+                    attributes: attributes
+                        .into_iter()
+                        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                        .collect(),
+                    value: value.into_owned(),
+                }))
+            }
+        }
+    }
+
+    /// Returns `true` for protocol nulls: `_`, and the null forms of strings and
+    /// aggregates such as `$-1` and `*-1`. An attributed value is null if the value
+    /// is.
+    pub fn is_null(&self) -> bool {
+        match self {
+            RespValue::Null
+            | RespValue::BulkString(None)
+            | RespValue::BulkError(None)
+            | RespValue::VerbatimString(None)
+            | RespValue::Array(None)
+            | RespValue::Set(None)
+            | RespValue::Push(None)
+            | RespValue::Map(None) => true,
+            RespValue::Attribute(attributed) => attributed.value.is_null(),
+            _ => false,
+        }
+    }
+
+    /// Returns `true` for values that are present but empty: strings of any kind
+    /// with no bytes, such as `$0`, and aggregates with no elements, such as `*0`.
+    /// Nulls are not empty. An attributed value is empty if the value is.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            RespValue::SimpleString(s)
+            | RespValue::Error(s)
+            | RespValue::BulkString(Some(s))
+            | RespValue::BulkError(Some(s)) => s.is_empty(),
+            RespValue::BulkBytes(b) => b.is_empty(),
+            RespValue::BulkStreamed { len } => *len == 0,
+            RespValue::VerbatimString(Some(v)) => v.text().is_empty(),
+            RespValue::Array(Some(values))
+            | RespValue::Set(Some(values))
+            | RespValue::Push(Some(values)) => values.is_empty(),
+            RespValue::Map(Some(entries)) => entries.is_empty(),
+            RespValue::Attribute(attributed) => attributed.value.is_empty(),
+            _ => false,
         }
     }
 
+    /// Returns `true` if the value `is_null` or `is_empty`.
+    pub fn is_null_or_empty(&self) -> bool {
+        self.is_null() || self.is_empty()
+    }
+
+    #[deprecated(
+        since = "1.3.0",
+        note = "conflates nulls with empty values; use `is_null`, `is_empty` or `is_null_or_empty`"
+    )]
     pub fn is_none(&self) -> bool {
         match self {
             RespValue::SimpleString(_) => false,
             RespValue::Error(_) => false,
             RespValue::Integer(_) => false,
             RespValue::BulkString(value) => {
-                value.is_none() || value.as_ref().map_or(false, |s| s.is_empty())
+                value.is_none() || value.as_ref().is_some_and(|s| s.is_empty())
             }
+            RespValue::BulkStreamed { len } => *len == 0,
+            RespValue::BulkBytes(b) => b.is_empty(),
             RespValue::Array(value) => {
-                value.is_none() || value.as_ref().map_or(false, |arr| arr.is_empty())
+                value.is_none() || value.as_ref().is_some_and(|arr| arr.is_empty())
             }
             RespValue::Null => true,
             RespValue::Boolean(_) => false,
             RespValue::Double(_) => false,
             RespValue::BigNumber(_) => false,
             RespValue::VerbatimString(text) => {
-                text.is_none() || text.as_ref().map_or(false, |s| s.is_empty())
+                text.is_none() || text.as_ref().is_some_and(|v| v.text().is_empty())
             }
             RespValue::Map(value) => {
-                value.is_none() || value.as_ref().map_or(false, |m| m.is_empty())
+                value.is_none() || value.as_ref().is_some_and(|m| m.is_empty())
             }
             RespValue::Set(value) => {
-                value.is_none() || value.as_ref().map_or(false, |s| s.is_empty())
-            }
-            RespValue::Push(data) => {
-                data.is_none() || data.as_ref().map_or(false, |s| s.is_empty())
+                value.is_none() || value.as_ref().is_some_and(|s| s.is_empty())
             }
+            RespValue::Push(data) => data.is_none() || data.as_ref().is_some_and(|s| s.is_empty()),
             RespValue::BulkError(_) => false,
+            RespValue::Attribute(attributed) => attributed.value.is_none(),
+        }
+    }
+}
+
+/// Canned replies, and their encodings for writing straight to a connection.
+impl RespValue<'static> {
+    pub const OK: RespValue<'static> = RespValue::SimpleString(Cow::Borrowed("OK"));
+    pub const PONG: RespValue<'static> = RespValue::SimpleString(Cow::Borrowed("PONG"));
+    pub const QUEUED: RespValue<'static> = RespValue::SimpleString(Cow::Borrowed("QUEUED"));
+    pub const NULL: RespValue<'static> = RespValue::Null;
+    pub const NULL_BULK: RespValue<'static> = RespValue::BulkString(None);
+    pub const NULL_ARRAY: RespValue<'static> = RespValue::Array(None);
+    pub const ZERO: RespValue<'static> = RespValue::Integer(0);
+    pub const ONE: RespValue<'static> = RespValue::Integer(1);
+    pub const TRUE: RespValue<'static> = RespValue::Boolean(true);
+    pub const FALSE: RespValue<'static> = RespValue::Boolean(false);
+
+    pub const OK_BYTES: &'static [u8] = b"+OK\r\n";
+    pub const PONG_BYTES: &'static [u8] = b"+PONG\r\n";
+    pub const QUEUED_BYTES: &'static [u8] = b"+QUEUED\r\n";
+    pub const NULL_BYTES: &'static [u8] = b"_\r\n";
+    pub const NULL_BULK_BYTES: &'static [u8] = b"$-1\r\n";
+    pub const NULL_ARRAY_BYTES: &'static [u8] = b"*-1\r\n";
+    pub const EMPTY_ARRAY_BYTES: &'static [u8] = b"*0\r\n";
+    pub const ZERO_BYTES: &'static [u8] = b":0\r\n";
+    pub const ONE_BYTES: &'static [u8] = b":1\r\n";
+    pub const TRUE_BYTES: &'static [u8] = b"#t\r\n";
+    pub const FALSE_BYTES: &'static [u8] = b"#f\r\n";
+
+    /// An empty array, encoded as `EMPTY_ARRAY_BYTES`.
+    pub fn empty_array() -> Self {
+        RespValue::Array(Some(Box::default()))
+    }
+}
+
+impl<'a> RespValue<'a> {
+    /// Returns the type of the value.
+    pub fn kind(&self) -> RespKind {
+        match self {
+            RespValue::SimpleString(_) => RespKind::SimpleString,
+            RespValue::Error(_) => RespKind::Error,
+            RespValue::Integer(_) => RespKind::Integer,
+            RespValue::BulkString(_) | RespValue::BulkBytes(_) | RespValue::BulkStreamed { .. } => {
+                RespKind::BulkString
+            }
+            RespValue::Array(_) => RespKind::Array,
+            RespValue::Null => RespKind::Null,
+            RespValue::Boolean(_) => RespKind::Boolean,
+            RespValue::Double(_) => RespKind::Double,
+            RespValue::BigNumber(_) => RespKind::BigNumber,
+            RespValue::BulkError(_) => RespKind::BulkError,
+            RespValue::VerbatimString(_) => RespKind::VerbatimString,
+            RespValue::Map(_) => RespKind::Map,
+            RespValue::Set(_) => RespKind::Set,
+            RespValue::Push(_) => RespKind::Push,
+            RespValue::Attribute(_) => RespKind::Attribute,
+        }
+    }
+
+    /// Returns the entries of a `Map` with their keys decoded as `MapKey`.
+    ///
+    /// Returns `None` if the value is not a map or is a null map.
+    pub fn entries_typed(&self) -> Option<impl Iterator<Item = (MapKey<'_>, &RespValue<'a>)>> {
+        match self {
+            RespValue::Map(Some(entries)) => {
+                Some(entries.iter().map(|(k, v)| (MapKey::from(k), v)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the text of a simple, bulk or verbatim string, or of shared bulk bytes
+    /// that are valid UTF-8.
+    ///
+    /// Returns `None` for other values and null strings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// assert_eq!(RespValue::OK.as_str(), Some("OK"));
+    /// assert_eq!(RespValue::BulkString(Some("OK".into())).as_str(), Some("OK"));
+    /// assert_eq!(RespValue::Integer(1).as_str(), None);
+    /// ```
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            RespValue::SimpleString(s) | RespValue::BulkString(Some(s)) => Some(s),
+            RespValue::VerbatimString(Some(v)) => Some(v.text()),
+            RespValue::BulkBytes(b) => std::str::from_utf8(b).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an `Integer`.
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            RespValue::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Double`.
+    pub fn as_double(&self) -> Option<f64> {
+        match self {
+            RespValue::Double(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Boolean`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            RespValue::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of an array, set or push; `None` for other values and
+    /// null aggregates.
+    pub fn as_array(&self) -> Option<&[RespValue<'a>]> {
+        match self {
+            RespValue::Array(Some(values))
+            | RespValue::Set(Some(values))
+            | RespValue::Push(Some(values)) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a map; `None` for other values and null maps.
+    pub fn as_map(&self) -> Option<&[(RespValue<'a>, RespValue<'a>)]> {
+        match self {
+            RespValue::Map(Some(entries)) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Looks up an element by position in an array, set or push, or a map value by a
+    /// string key matched against string-like keys. Returns `None` when absent.
+    pub fn get<I: RespIndex>(&self, index: I) -> Option<&RespValue<'a>> {
+        index.index_into(self)
+    }
+
+    /// Follows a dot-separated path such as `"2.keyspace.db0"` down nested values:
+    /// each segment indexes an array, set or push by position, or looks up a map by
+    /// key as `get` does. An empty path returns the value itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::parser::Parser;
+    ///
+    /// let mut parser = Parser::new(10, 1024);
+    /// parser.read_buf(b"*2\r\n:1\r\n%1\r\n+keyspace\r\n%1\r\n+db0\r\n:42\r\n");
+    /// let reply = parser.try_parse().unwrap().unwrap();
+    /// assert_eq!(reply.path("1.keyspace.db0").and_then(|v| v.as_int()), Some(42));
+    /// assert_eq!(reply.path("1.keyspace.db1"), None);
+    /// ```
+    pub fn path(&self, path: &str) -> Option<&RespValue<'a>> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('.')
+            .try_fold(self, |value, segment| match value {
+                RespValue::Map(_) => value.get(segment),
+                _ => value.get(segment.parse::<usize>().ok()?),
+            })
+    }
+
+    /// Returns a copy that is safe to log: strings longer than `max_bytes` are
+    /// replaced by a `"<1048576 bytes>"` placeholder, and aggregates stop once their
+    /// summarized elements encode to more than `max_bytes`, ending with a
+    /// `"<N more elements>"` simple string (a `"<N more entries>"` key mapped to
+    /// `Null` for maps).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let payload = RespValue::BulkString(Some("x".repeat(1 << 20).into()));
+    /// assert_eq!(payload.summarize(64).as_str(), Some("<1048576 bytes>"));
+    ///
+    /// let array: RespValue = (0..1000).map(RespValue::Integer).collect();
+    /// let summary = array.summarize(16);
+    /// assert_eq!(summary.as_array().unwrap().len(), 6);
+    /// assert_eq!(summary[5].as_str(), Some("<995 more elements>"));
+    /// ```
+    pub fn summarize(&self, max_bytes: usize) -> RespValue<'a> {
+        fn placeholder<'a>(len: usize) -> Cow<'a, str> {
+            format!("<{} bytes>", len).into()
+        }
+
+        match self {
+            RespValue::SimpleString(s) if s.len() > max_bytes => {
+                RespValue::SimpleString(placeholder(s.len()))
+            }
+            RespValue::Error(s) if s.len() > max_bytes => RespValue::Error(placeholder(s.len())),
+            RespValue::BulkString(Some(s)) if s.len() > max_bytes => {
+                RespValue::BulkString(Some(placeholder(s.len())))
+            }
+            RespValue::BulkError(Some(s)) if s.len() > max_bytes => {
+                RespValue::BulkError(Some(placeholder(s.len())))
+            }
+            RespValue::BulkBytes(b) if b.len() > max_bytes => {
+                RespValue::BulkString(Some(placeholder(b.len())))
+            }
+            RespValue::VerbatimString(Some(v)) if v.text().len() > max_bytes => {
+                let text = format!("<{} bytes>", v.text().len());
+                RespValue::VerbatimString(Verbatim::new(v.format(), text).map(Box::new))
+            }
+            RespValue::Array(Some(values)) => {
+                RespValue::Array(Some(Box::from(summarize_elements(values, max_bytes))))
+            }
+            RespValue::Set(Some(values)) => {
+                RespValue::Set(Some(Box::from(summarize_elements(values, max_bytes))))
+            }
+            RespValue::Push(Some(values)) => {
+                RespValue::Push(Some(Box::from(summarize_elements(values, max_bytes))))
+            }
+            RespValue::Map(Some(entries)) => {
+                RespValue::Map(Some(Box::from(summarize_entries(entries, max_bytes))))
+            }
+            RespValue::Attribute(attributed) => RespValue::Attribute(Box::new(Attributed {
+                attributes: summarize_entries(&attributed.attributes, max_bytes),
+                value: attributed.value.summarize(max_bytes),
+            })),
+            other => other.clone(),
+        }
+    }
+
+    /// Returns a copy of a command with its secrets replaced by `"(redacted)"`, so
+    /// it can be logged. Covers the arguments Redis itself hides from the slow log:
+    /// everything after `AUTH`, the username and password after `HELLO ... AUTH`
+    /// and `MIGRATE ... AUTH2`, the password after `MIGRATE ... AUTH`, and the values
+    /// of `requirepass` and `masterauth` in `CONFIG SET`. Other values are returned
+    /// unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stream_resp::resp::RespValue;
+    ///
+    /// let command: RespValue = ["auth", "default", "hunter2"]
+    ///     .into_iter()
+    ///     .map(RespValue::from)
+    ///     .collect();
+    /// let redacted = command.redacted();
+    /// assert_eq!(redacted[0].as_str(), Some("auth"));
+    /// assert_eq!(redacted[2].as_str(), Some("(redacted)"));
+    /// ```
+    pub fn redacted(&self) -> RespValue<'a> {
+        let RespValue::Array(Some(args)) = self else {
+            return self.clone();
+        };
+        let is = |i: usize, name: &str| {
+            args.get(i)
+                .and_then(RespValue::as_str)
+                .is_some_and(|arg| arg.eq_ignore_ascii_case(name))
+        };
+        let mut secret = vec![false; args.len()];
+        if is(0, "AUTH") {
+            secret[1..].fill(true);
+        } else if is(0, "HELLO") || is(0, "MIGRATE") {
+            // Options follow the protocol version of HELLO and the five fixed
+            // arguments of MIGRATE.
+            let (mut i, migrate) = if is(0, "HELLO") {
+                (2, false)
+            } else {
+                (6, true)
+            };
+            while i < args.len() {
+                let count = match (migrate, is(i, "AUTH"), is(i, "AUTH2")) {
+                    (false, true, _) | (true, _, true) => 2,
+                    (true, true, _) => 1,
+                    _ => 0,
+                };
+                let end = (i + 1 + count).min(args.len());
+                secret[i + 1..end].fill(true);
+                i = end;
+            }
+        } else if is(0, "CONFIG") && is(1, "SET") {
+            for i in (2..args.len()).step_by(2) {
+                if (is(i, "requirepass") || is(i, "masterauth")) && i + 1 < args.len() {
+                    secret[i + 1] = true;
+                }
+            }
+        }
+        RespValue::Array(Some(
+            args.iter()
+                .zip(secret)
+                .map(|(arg, secret)| {
+                    if secret {
+                        RespValue::BulkString(Some("(redacted)".into()))
+                    } else {
+                        arg.clone()
+                    }
+                })
+                .collect(),
+        ))
+    }
+
+    /// Iterates over the elements of an array, set or push; null aggregates and other
+    /// values have none.
+    pub fn iter(&self) -> std::slice::Iter<'_, RespValue<'a>> {
+        self.as_array().unwrap_or_default().iter()
+    }
+
+    /// Iterates over the entries of a map; null maps and other values have none.
+    pub fn entries(&self) -> std::slice::Iter<'_, (RespValue<'a>, RespValue<'a>)> {
+        self.as_map().unwrap_or_default().iter()
+    }
+
+    /// Converts a map into a `HashMap` keyed by its string keys, as returned by
+    /// `HELLO` or `CLIENT INFO`. Fails on other values, null maps included, and on
+    /// the first key that is not string-like. A repeated key keeps its last value.
+    pub fn into_string_map(self) -> Result<HashMap<String, RespValue<'a>>, FromRespError> {
+        let RespValue::Map(Some(entries)) = self else {
+            return Err(FromRespError::new("HashMap<String, RespValue>", &self));
+        };
+        let mut map = HashMap::with_capacity(entries.len());
+        for (key, value) in entries {
+            let key = String::try_from(key).map_err(|e| FromRespError {
+                expected: "String map key",
+                ..e
+            })?;
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+
+    /// Consumes a map, iterating over its entries; null maps and other values have
+    /// none.
+    pub fn into_entries(self) -> std::vec::IntoIter<(RespValue<'a>, RespValue<'a>)> {
+        match self {
+            RespValue::Map(Some(entries)) => entries.into_iter(),
+            _ => Vec::new().into_iter(),
         }
     }
 }
@@ -1,12 +1,17 @@
 #[allow(dead_code)]
-use crate::resp::RespValue;
+use crate::parser::Parser;
+use crate::resp::{
+    EncodeOptions, FromRespError, MapKey, OwnedRespValue, RespKind, RespValue, Verbatim,
+};
 use std::borrow::Cow;
 
 #[cfg(test)]
+#[allow(clippy::approx_constant)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(deprecated)]
     fn test_is_none() {
         assert!(!RespValue::SimpleString(Cow::Borrowed("test")).is_none());
         assert!(!RespValue::SimpleString(Cow::Borrowed("")).is_none());
@@ -19,22 +24,22 @@ mod tests {
         assert!(!RespValue::BulkString(Some(Cow::Borrowed("test"))).is_none());
 
         assert!(RespValue::Array(None).is_none());
-        assert!(RespValue::Array(Some(vec![])).is_none());
-        assert!(!RespValue::Array(Some(vec![RespValue::Integer(1)])).is_none());
+        assert!(RespValue::Array(Some(Box::default())).is_none());
+        assert!(!RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)]))).is_none());
 
         assert!(RespValue::Map(None).is_none());
-        assert!(RespValue::Map(Some(vec![])).is_none());
+        assert!(RespValue::Map(Some(Box::default())).is_none());
         assert!(
-            !RespValue::Map(Some(vec![(
+            !RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::SimpleString(Cow::Borrowed("value"))
-            )]))
+            )])))
             .is_none()
         );
 
         assert!(RespValue::Set(None).is_none());
-        assert!(RespValue::Set(Some(vec![])).is_none());
-        assert!(!RespValue::Set(Some(vec![RespValue::Integer(1)])).is_none());
+        assert!(RespValue::Set(Some(Box::default())).is_none());
+        assert!(!RespValue::Set(Some(Box::from(vec![RespValue::Integer(1)]))).is_none());
 
         assert!(RespValue::Null.is_none());
 
@@ -42,17 +47,17 @@ mod tests {
         assert!(!RespValue::Double(1.23).is_none());
         assert!(!RespValue::BigNumber(Cow::Borrowed("12345")).is_none());
 
-        assert!(!RespValue::VerbatimString(Some(Cow::Borrowed("hello"))).is_none());
+        assert!(!RespValue::VerbatimString(Some(Box::new(Verbatim::txt("hello")))).is_none());
 
         assert!(RespValue::Push(None).is_none());
-        assert!(!RespValue::Push(Some(vec![RespValue::Integer(1)])).is_none());
+        assert!(!RespValue::Push(Some(Box::from(vec![RespValue::Integer(1)]))).is_none());
 
         assert!(RespValue::Map(None).is_none());
         assert!(
-            !RespValue::Map(Some(vec![(
+            !RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::SimpleString(Cow::Borrowed("value"))
-            )]))
+            )])))
             .is_none()
         );
     }
@@ -120,23 +125,29 @@ mod tests {
 
     #[test]
     fn test_array() {
-        let value = RespValue::Array(Some(vec![]));
+        let value = RespValue::Array(Some(Box::default()));
         assert_eq!(value.as_bytes(), b"*0\r\n");
 
         let value = RespValue::Array(None);
         assert_eq!(value.as_bytes(), b"*-1\r\n");
 
-        let value = RespValue::Array(Some(vec![
+        let value = RespValue::Array(Some(Box::from(vec![
             RespValue::SimpleString(Cow::Borrowed("OK")),
             RespValue::Integer(123),
             RespValue::BulkString(Some(Cow::Borrowed("hello"))),
-        ]));
+        ])));
         assert_eq!(value.as_bytes(), b"*3\r\n+OK\r\n:123\r\n$5\r\nhello\r\n");
 
-        let value = RespValue::Array(Some(vec![
-            RespValue::Array(Some(vec![RespValue::Integer(1), RespValue::Integer(2)])),
-            RespValue::Array(Some(vec![RespValue::Integer(3), RespValue::Integer(4)])),
-        ]));
+        let value = RespValue::Array(Some(Box::from(vec![
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::Integer(1),
+                RespValue::Integer(2),
+            ]))),
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::Integer(3),
+                RespValue::Integer(4),
+            ]))),
+        ])));
         assert_eq!(
             value.as_bytes(),
             b"*2\r\n*2\r\n:1\r\n:2\r\n*2\r\n:3\r\n:4\r\n"
@@ -170,6 +181,208 @@ mod tests {
         assert_eq!(value.as_bytes(), b",0\r\n");
     }
 
+    #[test]
+    fn test_double_canonical_form() {
+        // Whole numbers have no decimal part, and no exponent by default
+        assert_eq!(RespValue::Double(10.0).as_bytes(), b",10\r\n");
+        assert_eq!(RespValue::Double(-0.0).as_bytes(), b",-0\r\n");
+        assert_eq!(RespValue::Double(0.1).as_bytes(), b",0.1\r\n");
+        assert_eq!(
+            RespValue::Double(1e21).as_bytes(),
+            b",1000000000000000000000\r\n"
+        );
+        assert_eq!(RespValue::Double(1.5e-7).as_bytes(), b",0.00000015\r\n");
+
+        let options = EncodeOptions {
+            double_exponents: true,
+            ..EncodeOptions::default()
+        };
+        assert_eq!(RespValue::Double(10.0).as_bytes_with(options), b",10\r\n");
+        assert_eq!(RespValue::Double(1e21).as_bytes_with(options), b",1e21\r\n");
+        assert_eq!(
+            RespValue::Double(1.5e-7).as_bytes_with(options),
+            b",1.5e-7\r\n"
+        );
+        assert_eq!(
+            RespValue::Double(f64::NAN).as_bytes_with(options),
+            b",nan\r\n"
+        );
+        assert_eq!(
+            RespValue::Array(Some(Box::from(vec![RespValue::Double(1e100)])))
+                .as_bytes_with(options),
+            b"*1\r\n,1e100\r\n"
+        );
+
+        // Either form parses back to the same value
+        let mut parser = Parser::new(10, 1024);
+        for d in [10.0, 0.1, 1e21, 1.5e-7, 1e100, f64::MAX, 5e-324] {
+            parser.read_buf(&RespValue::Double(d).as_bytes());
+            parser.read_buf(&RespValue::Double(d).as_bytes_with(options));
+            assert_eq!(parser.try_parse(), Ok(Some(RespValue::Double(d))));
+            assert_eq!(parser.try_parse(), Ok(Some(RespValue::Double(d))));
+        }
+    }
+
+    #[test]
+    fn test_double_matches_display() {
+        let mut doubles = vec![
+            0.0,
+            -0.0,
+            1.0,
+            -1.0,
+            123456789.0,
+            1e15,
+            1e16,
+            1.5e16,
+            9007199254740993.0,
+            1e-5,
+            1e-6,
+            1e-7,
+            -1.25e-300,
+            f64::MAX,
+            f64::MIN,
+            f64::MIN_POSITIVE,
+            f64::EPSILON,
+            5e-324,
+        ];
+        // Arbitrary bit patterns, from a fixed xorshift sequence
+        let mut bits = 0x9E37_79B9_7F4A_7C15_u64;
+        while doubles.len() < 2000 {
+            bits ^= bits << 13;
+            bits ^= bits >> 7;
+            bits ^= bits << 17;
+            let d = f64::from_bits(bits);
+            if d.is_finite() {
+                doubles.push(d);
+            }
+        }
+
+        for d in doubles {
+            let value = RespValue::Double(d);
+            assert_eq!(value.as_bytes(), format!(",{}\r\n", d).into_bytes());
+            assert_eq!(value.encoded_len(), value.as_bytes().len());
+        }
+    }
+
+    #[test]
+    fn test_double_precision() {
+        let redis = EncodeOptions {
+            double_exponents: true,
+            double_precision: Some(17),
+            ..EncodeOptions::default()
+        };
+        // As C's `%.17g` writes them
+        for (d, expected) in [
+            (0.1, "0.10000000000000001"),
+            (1e21, "1e+21"),
+            (1.5e-7, "1.4999999999999999e-07"),
+            (-0.0, "-0"),
+            (10.0, "10"),
+            (3.14, "3.1400000000000001"),
+            (1e16, "10000000000000000"),
+            (0.0001, "0.0001"),
+            (1e-5, "1.0000000000000001e-05"),
+            (123456789012345678.0, "1.2345678901234568e+17"),
+            (5e-324, "4.9406564584124654e-324"),
+            (f64::MAX, "1.7976931348623157e+308"),
+            (f64::NAN, "nan"),
+            (f64::NEG_INFINITY, "-inf"),
+        ] {
+            let value = RespValue::Double(d);
+            let bytes = value.as_bytes_with(redis);
+            assert_eq!(bytes, format!(",{}\r\n", expected).into_bytes());
+            assert_eq!(value.encoded_len_with(redis), bytes.len());
+        }
+
+        // As `%.3g` and `%.0g`, with and without exponents
+        let mut options = EncodeOptions {
+            double_exponents: true,
+            double_precision: Some(3),
+            ..EncodeOptions::default()
+        };
+        for (d, expected) in [
+            (1.5e-7, "1.5e-07"),
+            (3.14159, "3.14"),
+            (f64::MAX, "1.8e+308"),
+        ] {
+            assert_eq!(
+                RespValue::Double(d).as_bytes_with(options),
+                format!(",{}\r\n", expected).into_bytes()
+            );
+        }
+        options.double_precision = Some(0);
+        assert_eq!(
+            RespValue::Double(10.0).as_bytes_with(options),
+            b",1e+01\r\n"
+        );
+        options.double_exponents = false;
+        assert_eq!(RespValue::Double(10.0).as_bytes_with(options), b",10\r\n");
+        options.double_precision = Some(3);
+        for (d, expected) in [
+            (1.5e-7, "0.00000015"),
+            (123456789012345678.0, "123000000000000000"),
+            (-2.5e-5, "-0.000025"),
+        ] {
+            let value = RespValue::Double(d);
+            let bytes = value.as_bytes_with(options);
+            assert_eq!(bytes, format!(",{}\r\n", expected).into_bytes());
+            assert_eq!(value.encoded_len_with(options), bytes.len());
+        }
+
+        // Every precision is accepted, and parses back to the same value from 17 on
+        let mut parser = Parser::new(10, 1024);
+        for precision in [1, 17, 40, 255] {
+            let options = EncodeOptions {
+                double_precision: Some(precision),
+                ..redis
+            };
+            let value = RespValue::Double(-5e-324);
+            let bytes = value.as_bytes_with(options);
+            assert_eq!(value.encoded_len_with(options), bytes.len());
+            if precision >= 17 {
+                parser.read_buf(&bytes);
+                assert_eq!(parser.try_parse(), Ok(Some(value)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_integer_extremes() {
+        for i in [0, -1, i64::MAX, i64::MIN] {
+            let value = RespValue::Integer(i);
+            assert_eq!(value.as_bytes(), format!(":{}\r\n", i).into_bytes());
+            assert_eq!(value.encoded_len(), value.as_bytes().len());
+        }
+    }
+
+    #[test]
+    fn test_double_special_values() {
+        assert_eq!(RespValue::Double(f64::INFINITY).as_bytes(), b",inf\r\n");
+        assert_eq!(
+            RespValue::Double(f64::NEG_INFINITY).as_bytes(),
+            b",-inf\r\n"
+        );
+        assert_eq!(RespValue::Double(f64::NAN).as_bytes(), b",nan\r\n");
+        assert_eq!(RespValue::Double(-f64::NAN).as_bytes(), b",nan\r\n");
+
+        let mut parser = Parser::new(10, 1024);
+        for d in [
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            -0.0,
+            1e300,
+            f64::MIN_POSITIVE,
+        ] {
+            parser.read_buf(&RespValue::Double(d).as_bytes());
+            assert_eq!(parser.try_parse(), Ok(Some(RespValue::Double(d))));
+        }
+        parser.read_buf(&RespValue::Double(f64::NAN).as_bytes());
+        assert!(matches!(
+            parser.try_parse(),
+            Ok(Some(RespValue::Double(d))) if d.is_nan()
+        ));
+    }
+
     #[test]
     fn test_big_number() {
         let value =
@@ -191,7 +404,7 @@ mod tests {
     #[test]
     fn test_bulk_error() {
         let value = RespValue::BulkError(Some(Cow::Borrowed("Error details")));
-        assert_eq!(value.as_bytes(), b"!Error details\r\n");
+        assert_eq!(value.as_bytes(), b"!13\r\nError details\r\n");
 
         let value = RespValue::BulkError(None);
         assert_eq!(value.as_bytes(), b"!-1\r\n");
@@ -199,8 +412,8 @@ mod tests {
 
     #[test]
     fn test_verbatim_string() {
-        let value = RespValue::VerbatimString(Some(Cow::Borrowed("txt:Some text")));
-        assert_eq!(value.as_bytes(), b"=txt:Some text\r\n");
+        let value = RespValue::VerbatimString(Some(Box::new(Verbatim::txt("Some text"))));
+        assert_eq!(value.as_bytes(), b"=13\r\ntxt:Some text\r\n");
 
         let value = RespValue::VerbatimString(None);
         assert_eq!(value.as_bytes(), b"=-1\r\n");
@@ -208,13 +421,13 @@ mod tests {
 
     #[test]
     fn test_map() {
-        let value = RespValue::Map(Some(vec![]));
+        let value = RespValue::Map(Some(Box::default()));
         assert_eq!(value.as_bytes(), b"%0\r\n");
 
         let value = RespValue::Map(None);
         assert_eq!(value.as_bytes(), b"%-1\r\n");
 
-        let value = RespValue::Map(Some(vec![
+        let value = RespValue::Map(Some(Box::from(vec![
             (
                 RespValue::SimpleString(Cow::Borrowed("key1")),
                 RespValue::Integer(123),
@@ -223,7 +436,7 @@ mod tests {
                 RespValue::SimpleString(Cow::Borrowed("key2")),
                 RespValue::BulkString(Some(Cow::Borrowed("value"))),
             ),
-        ]));
+        ])));
         assert_eq!(
             value.as_bytes(),
             b"%2\r\n+key1\r\n:123\r\n+key2\r\n$5\r\nvalue\r\n"
@@ -232,32 +445,32 @@ mod tests {
 
     #[test]
     fn test_set() {
-        let value = RespValue::Set(Some(vec![]));
+        let value = RespValue::Set(Some(Box::default()));
         assert_eq!(value.as_bytes(), b"~0\r\n");
 
         let value = RespValue::Set(None);
         assert_eq!(value.as_bytes(), b"~-1\r\n");
 
-        let value = RespValue::Set(Some(vec![
+        let value = RespValue::Set(Some(Box::from(vec![
             RespValue::Integer(1),
             RespValue::SimpleString(Cow::Borrowed("two")),
             RespValue::BulkString(Some(Cow::Borrowed("three"))),
-        ]));
+        ])));
         assert_eq!(value.as_bytes(), b"~3\r\n:1\r\n+two\r\n$5\r\nthree\r\n");
     }
 
     #[test]
     fn test_push() {
-        let value = RespValue::Push(Some(vec![]));
+        let value = RespValue::Push(Some(Box::default()));
         assert_eq!(value.as_bytes(), b">0\r\n");
 
         let value = RespValue::Push(None);
         assert_eq!(value.as_bytes(), b">-1\r\n");
 
-        let value = RespValue::Push(Some(vec![
+        let value = RespValue::Push(Some(Box::from(vec![
             RespValue::SimpleString(Cow::Borrowed("message")),
             RespValue::Integer(42),
-        ]));
+        ])));
         assert_eq!(value.as_bytes(), b">2\r\n+message\r\n:42\r\n");
     }
 
@@ -273,25 +486,25 @@ mod tests {
             _ => panic!("Wrong variant"),
         }
 
-        let borrowed = RespValue::Array(Some(vec![
+        let borrowed = RespValue::Array(Some(Box::from(vec![
             RespValue::SimpleString(Cow::Borrowed("test")),
             RespValue::BulkString(Some(Cow::Borrowed("bulk"))),
-        ]));
+        ])));
         let owned = borrowed.into_owned();
         match owned {
             RespValue::Array(Some(arr)) => {
                 assert_eq!(arr.len(), 2);
                 match &arr[0] {
                     RespValue::SimpleString(s) => {
-                        assert!(matches!(s, Cow::Owned(_)));
-                        assert_eq!(s, "test");
+                        assert!(matches!(*s, Cow::Owned(_)));
+                        assert_eq!(*s, "test");
                     }
                     _ => panic!("Wrong variant"),
                 }
                 match &arr[1] {
                     RespValue::BulkString(Some(s)) => {
-                        assert!(matches!(s, Cow::Owned(_)));
-                        assert_eq!(s, "bulk");
+                        assert!(matches!(*s, Cow::Owned(_)));
+                        assert_eq!(*s, "bulk");
                     }
                     _ => panic!("Wrong variant"),
                 }
@@ -302,19 +515,22 @@ mod tests {
 
     #[test]
     fn test_complex_nested_structures() {
-        let value = RespValue::Array(Some(vec![
-            RespValue::Map(Some(vec![(
+        let value = RespValue::Array(Some(Box::from(vec![
+            RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key1")),
-                RespValue::Set(Some(vec![RespValue::Integer(1), RespValue::Integer(2)])),
-            )])),
-            RespValue::Push(Some(vec![
+                RespValue::Set(Some(Box::from(vec![
+                    RespValue::Integer(1),
+                    RespValue::Integer(2),
+                ]))),
+            )]))),
+            RespValue::Push(Some(Box::from(vec![
                 RespValue::BulkString(Some(Cow::Borrowed("notification"))),
-                RespValue::Array(Some(vec![
+                RespValue::Array(Some(Box::from(vec![
                     RespValue::SimpleString(Cow::Borrowed("data1")),
                     RespValue::SimpleString(Cow::Borrowed("data2")),
-                ])),
-            ])),
-        ]));
+                ]))),
+            ]))),
+        ])));
 
         let bytes = value.as_bytes();
         assert!(bytes.starts_with(b"*2\r\n"));
@@ -327,7 +543,12 @@ mod tests {
         println!("RespValue alignment: {}", std::mem::align_of::<RespValue>());
 
         // Ensure no unexpected padding
-        assert!(std::mem::size_of::<RespValue>() % 8 == 0);
+        assert!(std::mem::size_of::<RespValue>().is_multiple_of(8));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            std::mem::size_of::<RespValue>(),
+            crate::resp::RESP_VALUE_SIZE
+        );
     }
 
     #[test]
@@ -368,7 +589,10 @@ mod tests {
         let value: RespValue = vec![RespValue::Integer(1), RespValue::Integer(2)].into();
         assert_eq!(
             value,
-            RespValue::Array(Some(vec![RespValue::Integer(1), RespValue::Integer(2)]))
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::Integer(1),
+                RespValue::Integer(2)
+            ])))
         );
     }
 
@@ -393,10 +617,10 @@ mod tests {
             .into();
         assert_eq!(
             value,
-            RespValue::Map(Some(vec![(
+            RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::Integer(42)
-            )]))
+            )])))
         );
     }
 
@@ -409,61 +633,69 @@ mod tests {
         .into();
         assert_eq!(
             value,
-            RespValue::Map(Some(vec![(
+            RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::Integer(42)
-            )]))
+            )])))
         );
     }
 
     #[test]
     fn test_into_string() {
-        let value: String = RespValue::SimpleString(Cow::Owned("test".to_string())).into();
+        let value: String = RespValue::SimpleString(Cow::Owned("test".to_string()))
+            .try_into()
+            .unwrap();
         assert_eq!(value, "test".to_string());
     }
 
     #[test]
     fn test_into_i64() {
-        let value: i64 = RespValue::Integer(42).into();
+        let value: i64 = RespValue::Integer(42).try_into().unwrap();
         assert_eq!(value, 42);
     }
 
     #[test]
     fn test_into_option_string() {
-        let value: Option<String> =
-            RespValue::BulkString(Some(Cow::Owned("test".to_string()))).into();
+        let value: Option<String> = RespValue::BulkString(Some(Cow::Owned("test".to_string())))
+            .try_into()
+            .unwrap();
         assert_eq!(value, Some("test".to_string()));
 
-        let value: Option<String> = RespValue::BulkString(None).into();
+        let value: Option<String> = RespValue::BulkString(None).try_into().unwrap();
         assert_eq!(value, None);
     }
 
     #[test]
     fn test_into_vec_resp_value() {
-        let value: Vec<RespValue> =
-            RespValue::Array(Some(vec![RespValue::Integer(1), RespValue::Integer(2)])).into();
+        let value: Vec<RespValue> = RespValue::Array(Some(Box::from(vec![
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+        ])))
+        .try_into()
+        .unwrap();
         assert_eq!(value, vec![RespValue::Integer(1), RespValue::Integer(2)]);
     }
 
     #[test]
     fn test_into_bool() {
-        let value: bool = RespValue::Boolean(true).into();
-        assert_eq!(value, true);
+        let value: bool = RespValue::Boolean(true).try_into().unwrap();
+        assert!(value);
     }
 
     #[test]
     fn test_into_f64() {
-        let value: f64 = RespValue::Double(3.14).into();
+        let value: f64 = RespValue::Double(3.14).try_into().unwrap();
         assert_eq!(value, 3.14);
     }
 
     #[test]
     fn test_into_vec_tuple_resp_value() {
-        let value: Vec<(RespValue, RespValue)> = RespValue::Map(Some(vec![(
+        let value: Vec<(RespValue, RespValue)> = RespValue::Map(Some(Box::from(vec![(
             RespValue::SimpleString(Cow::Borrowed("key")),
             RespValue::Integer(42),
-        )]))
-        .into();
+        )])))
+        .try_into()
+        .unwrap();
         assert_eq!(
             value,
             vec![(
@@ -506,12 +738,12 @@ mod tests {
         );
 
         assert_eq!(
-            RespValue::Array(Some(vec![RespValue::Integer(1)])),
-            RespValue::Array(Some(vec![RespValue::Integer(1)]))
+            RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)])))
         );
         assert_ne!(
-            RespValue::Array(Some(vec![RespValue::Integer(1)])),
-            RespValue::Array(Some(vec![RespValue::Integer(2)]))
+            RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Array(Some(Box::from(vec![RespValue::Integer(2)])))
         );
 
         assert_eq!(RespValue::Null, RespValue::Null);
@@ -541,51 +773,51 @@ mod tests {
         );
 
         assert_eq!(
-            RespValue::VerbatimString(Some(Cow::Borrowed("verbatim"))),
-            RespValue::VerbatimString(Some(Cow::Borrowed("verbatim")))
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt("verbatim")))),
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt("verbatim"))))
         );
         assert_ne!(
-            RespValue::VerbatimString(Some(Cow::Borrowed("verbatim"))),
-            RespValue::VerbatimString(Some(Cow::Borrowed("different")))
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt("verbatim")))),
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt("different"))))
         );
 
         assert_eq!(
-            RespValue::Map(Some(vec![(
+            RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::Integer(42)
-            )])),
-            RespValue::Map(Some(vec![(
+            )]))),
+            RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::Integer(42)
-            )]))
+            )])))
         );
         assert_ne!(
-            RespValue::Map(Some(vec![(
+            RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::Integer(42)
-            )])),
-            RespValue::Map(Some(vec![(
+            )]))),
+            RespValue::Map(Some(Box::from(vec![(
                 RespValue::SimpleString(Cow::Borrowed("key")),
                 RespValue::Integer(43)
-            )]))
+            )])))
         );
 
         assert_eq!(
-            RespValue::Set(Some(vec![RespValue::Integer(1)])),
-            RespValue::Set(Some(vec![RespValue::Integer(1)]))
+            RespValue::Set(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Set(Some(Box::from(vec![RespValue::Integer(1)])))
         );
         assert_ne!(
-            RespValue::Set(Some(vec![RespValue::Integer(1)])),
-            RespValue::Set(Some(vec![RespValue::Integer(2)]))
+            RespValue::Set(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Set(Some(Box::from(vec![RespValue::Integer(2)])))
         );
 
         assert_eq!(
-            RespValue::Push(Some(vec![RespValue::Integer(1)])),
-            RespValue::Push(Some(vec![RespValue::Integer(1)]))
+            RespValue::Push(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Push(Some(Box::from(vec![RespValue::Integer(1)])))
         );
         assert_ne!(
-            RespValue::Push(Some(vec![RespValue::Integer(1)])),
-            RespValue::Push(Some(vec![RespValue::Integer(2)]))
+            RespValue::Push(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Push(Some(Box::from(vec![RespValue::Integer(2)])))
         );
     }
 
@@ -612,7 +844,10 @@ mod tests {
         let value = RespValue::Null;
         assert_eq!(value.as_bytes(), b"_\r\n");
 
-        let value = RespValue::Array(Some(vec![RespValue::Integer(1), RespValue::Integer(2)]));
+        let value = RespValue::Array(Some(Box::from(vec![
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+        ])));
         assert_eq!(value.as_bytes(), b"*2\r\n:1\r\n:2\r\n");
 
         let value = RespValue::Boolean(true);
@@ -625,21 +860,27 @@ mod tests {
         assert_eq!(value.as_bytes(), b"(12345\r\n");
 
         let value = RespValue::BulkError(Some(Cow::Borrowed("error")));
-        assert_eq!(value.as_bytes(), b"!error\r\n");
+        assert_eq!(value.as_bytes(), b"!5\r\nerror\r\n");
 
-        let value = RespValue::VerbatimString(Some(Cow::Borrowed("verbatim")));
-        assert_eq!(value.as_bytes(), b"=verbatim\r\n");
+        let value = RespValue::VerbatimString(Some(Box::new(Verbatim::txt("verbatim"))));
+        assert_eq!(value.as_bytes(), b"=12\r\ntxt:verbatim\r\n");
 
-        let value = RespValue::Map(Some(vec![(
+        let value = RespValue::Map(Some(Box::from(vec![(
             RespValue::SimpleString(Cow::Borrowed("key")),
             RespValue::Integer(42),
-        )]));
+        )])));
         assert_eq!(value.as_bytes(), b"%1\r\n+key\r\n:42\r\n");
 
-        let value = RespValue::Set(Some(vec![RespValue::Integer(1), RespValue::Integer(2)]));
+        let value = RespValue::Set(Some(Box::from(vec![
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+        ])));
         assert_eq!(value.as_bytes(), b"~2\r\n:1\r\n:2\r\n");
 
-        let value = RespValue::Push(Some(vec![RespValue::Integer(1), RespValue::Integer(2)]));
+        let value = RespValue::Push(Some(Box::from(vec![
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+        ])));
         assert_eq!(value.as_bytes(), b">2\r\n:1\r\n:2\r\n");
     }
 
@@ -658,7 +899,7 @@ mod tests {
     #[test]
     fn test_bulk_error_empty() {
         let value = RespValue::BulkError(Some(Cow::Borrowed("")));
-        assert_eq!(value.as_bytes(), b"!\r\n");
+        assert_eq!(value.as_bytes(), b"!0\r\n\r\n");
     }
 
     #[test]
@@ -669,8 +910,8 @@ mod tests {
 
     #[test]
     fn test_verbatim_string_empty() {
-        let value = RespValue::VerbatimString(Some(Cow::Borrowed("")));
-        assert_eq!(value.as_bytes(), b"=\r\n");
+        let value = RespValue::VerbatimString(Some(Box::new(Verbatim::txt(""))));
+        assert_eq!(value.as_bytes(), b"=4\r\ntxt:\r\n");
     }
 
     #[test]
@@ -681,7 +922,7 @@ mod tests {
 
     #[test]
     fn test_map_empty() {
-        let value = RespValue::Map(Some(vec![]));
+        let value = RespValue::Map(Some(Box::default()));
         assert_eq!(value.as_bytes(), b"%0\r\n");
     }
 
@@ -693,7 +934,7 @@ mod tests {
 
     #[test]
     fn test_set_empty() {
-        let value = RespValue::Set(Some(vec![]));
+        let value = RespValue::Set(Some(Box::default()));
         assert_eq!(value.as_bytes(), b"~0\r\n");
     }
 
@@ -705,7 +946,7 @@ mod tests {
 
     #[test]
     fn test_push_empty() {
-        let value = RespValue::Push(Some(vec![]));
+        let value = RespValue::Push(Some(Box::default()));
         assert_eq!(value.as_bytes(), b">0\r\n");
     }
 
@@ -716,6 +957,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_is_none_bulk_string() {
         let value = RespValue::BulkString(Some(Cow::Borrowed("")));
         assert!(value.is_none());
@@ -725,8 +967,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_is_none_array() {
-        let value = RespValue::Array(Some(vec![]));
+        let value = RespValue::Array(Some(Box::default()));
         assert!(value.is_none());
 
         let value = RespValue::Array(None);
@@ -734,8 +977,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_is_none_map() {
-        let value = RespValue::Map(Some(vec![]));
+        let value = RespValue::Map(Some(Box::default()));
         assert!(value.is_none());
 
         let value = RespValue::Map(None);
@@ -743,8 +987,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_is_none_set() {
-        let value = RespValue::Set(Some(vec![]));
+        let value = RespValue::Set(Some(Box::default()));
         assert!(value.is_none());
 
         let value = RespValue::Set(None);
@@ -752,8 +997,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_is_none_push() {
-        let value = RespValue::Push(Some(vec![]));
+        let value = RespValue::Push(Some(Box::default()));
         assert!(value.is_none());
 
         let value = RespValue::Push(None);
@@ -761,8 +1007,9 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_is_none_verbatim_string() {
-        let value = RespValue::VerbatimString(Some(Cow::Borrowed("")));
+        let value = RespValue::VerbatimString(Some(Box::new(Verbatim::txt(""))));
         assert!(value.is_none());
 
         let value = RespValue::VerbatimString(None);
@@ -778,7 +1025,7 @@ mod tests {
     #[test]
     fn test_from_bulk_error() {
         let value: RespValue = RespValue::BulkError(Some(Cow::Borrowed("error")));
-        assert_eq!(value.as_bytes(), b"!error\r\n");
+        assert_eq!(value.as_bytes(), b"!5\r\nerror\r\n");
 
         let value: RespValue = RespValue::BulkError(None);
         assert_eq!(value.as_bytes(), b"!-1\r\n");
@@ -786,8 +1033,8 @@ mod tests {
 
     #[test]
     fn test_from_verbatim_string() {
-        let value: RespValue = RespValue::VerbatimString(Some(Cow::Borrowed("verbatim")));
-        assert_eq!(value.as_bytes(), b"=verbatim\r\n");
+        let value: RespValue = RespValue::VerbatimString(Some(Box::new(Verbatim::txt("verbatim"))));
+        assert_eq!(value.as_bytes(), b"=12\r\ntxt:verbatim\r\n");
 
         let value: RespValue = RespValue::VerbatimString(None);
         assert_eq!(value.as_bytes(), b"=-1\r\n");
@@ -795,7 +1042,7 @@ mod tests {
 
     #[test]
     fn test_from_map() {
-        let value: RespValue = RespValue::Map(Some(vec![
+        let value: RespValue = RespValue::Map(Some(Box::from(vec![
             (
                 RespValue::SimpleString(Cow::Borrowed("key1")),
                 RespValue::Integer(123),
@@ -804,7 +1051,7 @@ mod tests {
                 RespValue::SimpleString(Cow::Borrowed("key2")),
                 RespValue::BulkString(Some(Cow::Borrowed("value"))),
             ),
-        ]));
+        ])));
         assert_eq!(
             value.as_bytes(),
             b"%2\r\n+key1\r\n:123\r\n+key2\r\n$5\r\nvalue\r\n"
@@ -816,11 +1063,11 @@ mod tests {
 
     #[test]
     fn test_from_set() {
-        let value: RespValue = RespValue::Set(Some(vec![
+        let value: RespValue = RespValue::Set(Some(Box::from(vec![
             RespValue::Integer(1),
             RespValue::SimpleString(Cow::Borrowed("two")),
             RespValue::BulkString(Some(Cow::Borrowed("three"))),
-        ]));
+        ])));
         assert_eq!(value.as_bytes(), b"~3\r\n:1\r\n+two\r\n$5\r\nthree\r\n");
 
         let value: RespValue = RespValue::Set(None);
@@ -829,13 +1076,1044 @@ mod tests {
 
     #[test]
     fn test_from_push() {
-        let value: RespValue = RespValue::Push(Some(vec![
+        let value: RespValue = RespValue::Push(Some(Box::from(vec![
             RespValue::SimpleString(Cow::Borrowed("message")),
             RespValue::Integer(42),
-        ]));
+        ])));
         assert_eq!(value.as_bytes(), b">2\r\n+message\r\n:42\r\n");
 
         let value: RespValue = RespValue::Push(None);
         assert_eq!(value.as_bytes(), b">-1\r\n");
     }
+
+    #[test]
+    fn test_entries_typed() {
+        let value = RespValue::Map(Some(Box::from(vec![
+            (
+                RespValue::SimpleString("name".into()),
+                RespValue::Integer(1),
+            ),
+            (
+                RespValue::BulkString(Some("bulk".into())),
+                RespValue::Integer(2),
+            ),
+            (RespValue::Integer(42), RespValue::Integer(3)),
+            (RespValue::Double(1.5), RespValue::Integer(4)),
+            (RespValue::Boolean(true), RespValue::Integer(5)),
+        ])));
+
+        let entries: Vec<_> = value.entries_typed().unwrap().collect();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[0], (MapKey::Str("name"), &RespValue::Integer(1)));
+        assert_eq!(entries[1], (MapKey::Str("bulk"), &RespValue::Integer(2)));
+        assert_eq!(entries[2], (MapKey::Int(42), &RespValue::Integer(3)));
+        assert_eq!(entries[3], (MapKey::Double(1.5), &RespValue::Integer(4)));
+        assert_eq!(
+            entries[4],
+            (
+                MapKey::Other(&RespValue::Boolean(true)),
+                &RespValue::Integer(5)
+            )
+        );
+
+        assert!(RespValue::Map(None).entries_typed().is_none());
+        assert!(RespValue::Integer(1).entries_typed().is_none());
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(
+            RespValue::SimpleString("OK".into()).kind(),
+            RespKind::SimpleString
+        );
+        assert_eq!(RespValue::BulkString(None).kind(), RespKind::BulkString);
+        assert_eq!(RespValue::Map(Some(Box::default())).kind(), RespKind::Map);
+        assert_eq!(RespValue::Null.kind(), RespKind::Null);
+    }
+
+    #[test]
+    fn test_null_forms_round_trip() {
+        let wire = b"$-1\r\n*-1\r\n_\r\n%-1\r\n~-1\r\n>-1\r\n!-1\r\n=-1\r\n";
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(wire);
+        let mut encoded = Vec::new();
+        while let Ok(Some(value)) = parser.try_parse() {
+            encoded.extend(Vec::<u8>::from(value));
+        }
+        assert_eq!(encoded, wire);
+
+        // RESP2 clients get the null bulk string for `Null`
+        let options = EncodeOptions {
+            resp2_nulls: true,
+            ..EncodeOptions::default()
+        };
+        assert_eq!(RespValue::Null.as_bytes_with(options), b"$-1\r\n");
+        assert_eq!(
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::Null,
+                RespValue::Array(None)
+            ])))
+            .as_bytes_with(options),
+            b"*2\r\n$-1\r\n*-1\r\n"
+        );
+    }
+
+    #[test]
+    fn test_verbatim() {
+        let verbatim = Verbatim::parse(Cow::Owned("txt:a:b".to_string())).unwrap();
+        assert_eq!(verbatim.format(), "txt");
+        assert_eq!(verbatim.text(), "a:b");
+        assert_eq!(verbatim.payload_len(), 7);
+        assert_eq!(verbatim, Verbatim::txt("a:b"));
+        assert_eq!(Verbatim::parse("mkd:".into()), Some(Verbatim::mkd("")));
+
+        for payload in ["txt", "tx:a", "text:a", "t t:a", ""] {
+            assert_eq!(Verbatim::parse(payload.into()), None);
+        }
+        assert_eq!(Verbatim::new("csv", "a,b").unwrap().format(), "csv");
+        for format in ["cs", "csvx", "c:v", "é1"] {
+            assert!(Verbatim::new(format, "a").is_none());
+        }
+
+        let value = RespValue::VerbatimString(Some(Box::new(Verbatim::new("csv", "a,b").unwrap())));
+        assert_eq!(value.as_bytes(), b"=7\r\ncsv:a,b\r\n");
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(&value.as_bytes());
+        assert_eq!(parser.try_parse(), Ok(Some(value)));
+    }
+
+    #[test]
+    fn test_owned_resp_value() {
+        fn assert_send_static<T: Send + 'static>(_: &T) {}
+
+        let text = String::from("value");
+        let value = RespValue::Array(Some(Box::from(vec![
+            RespValue::BulkString(Some(text.as_str().into())),
+            RespValue::Integer(1),
+        ])));
+        let owned = OwnedRespValue::from(value.clone());
+        assert_send_static(&owned);
+        assert_eq!(*owned, value);
+        drop(value);
+        drop(text);
+
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"*2\r\n$5\r\nvalue\r\n:1\r\n");
+        let parsed = OwnedRespValue::from(parser.try_parse().unwrap().unwrap());
+        assert_eq!(parsed, owned);
+        assert_eq!(RespValue::from(parsed).kind(), RespKind::Array);
+    }
+
+    #[test]
+    fn test_canned_replies() {
+        for (value, bytes) in [
+            (RespValue::OK, RespValue::OK_BYTES),
+            (RespValue::PONG, RespValue::PONG_BYTES),
+            (RespValue::QUEUED, RespValue::QUEUED_BYTES),
+            (RespValue::empty_array(), RespValue::EMPTY_ARRAY_BYTES),
+            (RespValue::NULL, RespValue::NULL_BYTES),
+            (RespValue::NULL_BULK, RespValue::NULL_BULK_BYTES),
+            (RespValue::NULL_ARRAY, RespValue::NULL_ARRAY_BYTES),
+            (RespValue::ZERO, RespValue::ZERO_BYTES),
+            (RespValue::ONE, RespValue::ONE_BYTES),
+            (RespValue::TRUE, RespValue::TRUE_BYTES),
+            (RespValue::FALSE, RespValue::FALSE_BYTES),
+        ] {
+            assert_eq!(value.as_bytes(), bytes);
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(bytes);
+            assert_eq!(parser.try_parse(), Ok(Some(value)));
+        }
+    }
+
+    #[test]
+    fn test_hash_and_eq() {
+        use std::collections::{HashMap, HashSet};
+
+        assert_eq!(RespValue::Double(f64::NAN), RespValue::Double(-f64::NAN));
+        assert_eq!(RespValue::Double(0.0), RespValue::Double(-0.0));
+        assert_ne!(RespValue::Double(1.0), RespValue::Integer(1));
+        assert_ne!(
+            RespValue::SimpleString("a".into()),
+            RespValue::BulkString(Some("a".into()))
+        );
+
+        let set: HashSet<RespValue> = [
+            RespValue::Double(f64::NAN),
+            RespValue::Double(f64::NAN),
+            RespValue::Double(0.0),
+            RespValue::Double(-0.0),
+            RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Array(Some(Box::from(vec![RespValue::Integer(1)]))),
+            RespValue::Array(None),
+            RespValue::Null,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.len(), 5);
+
+        let mut map = HashMap::new();
+        map.insert(RespValue::BulkString(Some("key".into())), 1);
+        assert_eq!(
+            map.get(&RespValue::BulkString(Some(Cow::Owned("key".to_string())))),
+            Some(&1)
+        );
+    }
+
+    #[test]
+    fn test_total_order() {
+        use std::collections::BTreeSet;
+
+        let bulk = |s: &'static str| RespValue::BulkString(Some(s.into()));
+        let mut values = vec![
+            RespValue::Array(Some(Box::from(vec![RespValue::Integer(2)]))),
+            bulk("b"),
+            RespValue::Double(f64::NAN),
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::Integer(1),
+                RespValue::Null,
+            ]))),
+            RespValue::Integer(10),
+            RespValue::Double(f64::INFINITY),
+            RespValue::BulkString(None),
+            RespValue::Null,
+            bulk("a"),
+            RespValue::Double(-0.0),
+            RespValue::Integer(-3),
+            RespValue::SimpleString("z".into()),
+            RespValue::Array(None),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                RespValue::Null,
+                RespValue::Integer(-3),
+                RespValue::Integer(10),
+                RespValue::Double(0.0),
+                RespValue::Double(f64::INFINITY),
+                RespValue::Double(f64::NAN),
+                RespValue::SimpleString("z".into()),
+                RespValue::BulkString(None),
+                bulk("a"),
+                bulk("b"),
+                RespValue::Array(None),
+                RespValue::Array(Some(Box::from(vec![
+                    RespValue::Integer(1),
+                    RespValue::Null
+                ]))),
+                RespValue::Array(Some(Box::from(vec![RespValue::Integer(2)]))),
+            ]
+        );
+        assert_eq!(values.binary_search(&bulk("b")), Ok(9));
+
+        let set: BTreeSet<RespValue> = [
+            RespValue::Double(0.0),
+            RespValue::Double(-0.0),
+            RespValue::Double(f64::NAN),
+            RespValue::Double(-f64::NAN),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_semantic_eq() {
+        let bulk = |s: &'static str| RespValue::BulkString(Some(s.into()));
+        let map = |entries: Vec<(RespValue<'static>, RespValue<'static>)>| {
+            RespValue::Map(Some(Box::from(entries)))
+        };
+        let a = map(vec![
+            (bulk("a"), RespValue::Double(0.1 + 0.2)),
+            (
+                bulk("b"),
+                RespValue::Set(Some(Box::from(vec![bulk("x"), bulk("y")]))),
+            ),
+        ]);
+        let b = map(vec![
+            (
+                bulk("b"),
+                RespValue::Set(Some(Box::from(vec![bulk("y"), bulk("x")]))),
+            ),
+            (bulk("a"), RespValue::Double(0.3)),
+        ]);
+        assert!(!a.semantic_eq(&b));
+        assert!(a.semantic_eq_with(&b, 1e-9));
+
+        // Arrays stay ordered and duplicates must match up one to one
+        let array = |values| RespValue::Array(Some(Box::from(values)));
+        assert!(!array(vec![bulk("x"), bulk("y")]).semantic_eq(&array(vec![bulk("y"), bulk("x")])));
+        let set = |values| RespValue::Set(Some(Box::from(values)));
+        assert!(!set(vec![bulk("x"), bulk("x")]).semantic_eq(&set(vec![bulk("x"), bulk("y")])));
+        assert!(RespValue::Double(f64::NAN).semantic_eq(&RespValue::Double(f64::NAN)));
+        assert!(!RespValue::Integer(1).semantic_eq(&RespValue::Double(1.0)));
+    }
+
+    #[test]
+    fn test_kind_type_byte() {
+        let values = [
+            RespValue::OK,
+            RespValue::Error("ERR".into()),
+            RespValue::ONE,
+            RespValue::NULL_BULK,
+            RespValue::BulkBytes(Box::new(bytes::Bytes::new())),
+            RespValue::empty_array(),
+            RespValue::NULL,
+            RespValue::TRUE,
+            RespValue::Double(1.5),
+            RespValue::BigNumber("1".into()),
+            RespValue::BulkError(None),
+            RespValue::VerbatimString(None),
+            RespValue::Map(None),
+            RespValue::Set(None),
+            RespValue::Push(None),
+        ];
+        for value in values {
+            let kind = value.kind();
+            assert_eq!(kind.type_byte(), value.as_bytes()[0]);
+            assert_eq!(RespKind::from_type_byte(kind.type_byte()), Some(kind));
+        }
+        assert_eq!(RespKind::Attribute.type_byte(), b'|');
+        assert_eq!(RespKind::from_type_byte(b'?'), None);
+    }
+
+    #[test]
+    fn test_encoded_len() {
+        let config = crate::generator::GeneratorConfig {
+            kinds: vec![
+                (RespKind::SimpleString, 1),
+                (RespKind::Integer, 1),
+                (RespKind::BulkString, 1),
+                (RespKind::Array, 1),
+                (RespKind::Null, 1),
+                (RespKind::Double, 1),
+                (RespKind::VerbatimString, 1),
+                (RespKind::Map, 1),
+                (RespKind::Attribute, 1),
+            ],
+            ..Default::default()
+        };
+        for frame in crate::generator::frames(7, config).take(500) {
+            let value = frame.value.unwrap();
+            assert_eq!(value.encoded_len(), frame.bytes.len(), "{:?}", value);
+        }
+
+        let options = EncodeOptions {
+            double_exponents: true,
+            resp2_nulls: true,
+            ..EncodeOptions::default()
+        };
+        for value in [
+            RespValue::Integer(i64::MIN),
+            RespValue::Integer(0),
+            RespValue::Double(1e21),
+            RespValue::Double(-f64::INFINITY),
+            RespValue::Double(f64::NAN),
+            RespValue::Double(-0.5),
+            RespValue::Null,
+            RespValue::BulkBytes(Box::new(bytes::Bytes::from_static(b"0123456789"))),
+            RespValue::BulkStreamed { len: 100 },
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt("hi")))),
+        ] {
+            assert_eq!(value.encoded_len(), value.as_bytes().len(), "{:?}", value);
+            assert_eq!(
+                value.encoded_len_with(options),
+                value.as_bytes_with(options).len(),
+                "{:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(
+            b"*6\r\n$3\r\nGET\r\n+OK\r\n:7\r\n,1.5\r\n#t\r\n%1\r\n=8\r\ntxt:text\r\n~0\r\n",
+        );
+        let value = parser.try_parse().unwrap().unwrap();
+        let elements = value.as_array().unwrap();
+        assert_eq!(elements.len(), 6);
+        assert_eq!(elements[0].as_str(), Some("GET"));
+        assert_eq!(elements[1].as_str(), Some("OK"));
+        assert_eq!(elements[2].as_int(), Some(7));
+        assert_eq!(elements[2].as_str(), None);
+        assert_eq!(elements[3].as_double(), Some(1.5));
+        assert_eq!(elements[3].as_int(), None);
+        assert_eq!(elements[4].as_bool(), Some(true));
+        let entries = elements[5].as_map().unwrap();
+        assert_eq!(entries[0].0.as_str(), Some("text"));
+        assert_eq!(entries[0].1.as_array(), Some(&[][..]));
+        assert_eq!(value.as_map(), None);
+
+        assert_eq!(RespValue::BulkString(None).as_str(), None);
+        assert_eq!(RespValue::Array(None).as_array(), None);
+        assert_eq!(
+            RespValue::BulkBytes(Box::new(bytes::Bytes::from_static(b"\xff"))).as_str(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_try_from_mismatched_type() {
+        let error = i64::try_from(RespValue::OK).unwrap_err();
+        assert_eq!(
+            error,
+            FromRespError {
+                expected: "i64",
+                found: RespKind::SimpleString
+            }
+        );
+        assert_eq!(error.to_string(), "Cannot convert SimpleString to i64");
+
+        assert!(String::try_from(RespValue::BulkString(None)).is_err());
+        assert_eq!(
+            String::try_from(RespValue::BulkString(Some("bulk".into()))),
+            Ok("bulk".to_string())
+        );
+        assert_eq!(Option::<String>::try_from(RespValue::Null), Ok(None));
+        assert_eq!(
+            Option::<String>::try_from(RespValue::Integer(1))
+                .unwrap_err()
+                .expected,
+            "Option<String>"
+        );
+        assert!(Vec::<RespValue>::try_from(RespValue::Array(None)).is_err());
+        assert!(bool::try_from(RespValue::Integer(1)).is_err());
+        assert!(f64::try_from(RespValue::Integer(1)).is_err());
+        assert!(Vec::<(RespValue, RespValue)>::try_from(RespValue::Set(None)).is_err());
+    }
+
+    #[test]
+    fn test_from_primitives_and_bytes() {
+        assert_eq!(RespValue::from(7u32), RespValue::Integer(7));
+        assert_eq!(RespValue::from(-7i32), RespValue::Integer(-7));
+        assert_eq!(RespValue::from(7usize), RespValue::Integer(7));
+        assert_eq!(
+            RespValue::from(i64::MAX as u64),
+            RespValue::Integer(i64::MAX)
+        );
+        assert_eq!(
+            RespValue::from(u64::MAX),
+            RespValue::BigNumber("18446744073709551615".into())
+        );
+        let value: RespValue = 123.into();
+        assert_eq!(value, RespValue::Integer(123));
+
+        let value = RespValue::from(&b"\x00\xff"[..]);
+        assert_eq!(value.as_bytes(), b"$2\r\n\x00\xff\r\n");
+        assert_eq!(RespValue::from(b"\x00\xff".to_vec()), value);
+        assert_eq!(
+            RespValue::from(bytes::Bytes::from_static(b"\x00\xff")),
+            value
+        );
+    }
+
+    #[test]
+    fn test_from_collections() {
+        use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+        let map: BTreeMap<&str, i64> = [("b", 2), ("a", 1)].into_iter().collect();
+        assert_eq!(
+            RespValue::from(map).as_bytes(),
+            b"%2\r\n+a\r\n:1\r\n+b\r\n:2\r\n"
+        );
+
+        let set: BTreeSet<i64> = [3, 1, 2].into_iter().collect();
+        assert_eq!(RespValue::from(set).as_bytes(), b"~3\r\n:1\r\n:2\r\n:3\r\n");
+
+        let map: HashMap<String, bool> = [("x".to_string(), true)].into_iter().collect();
+        assert_eq!(RespValue::from(map).as_bytes(), b"%1\r\n+x\r\n#t\r\n");
+
+        let set: HashSet<i64> = [1, 2].into_iter().collect();
+        let value = RespValue::from(set);
+        assert!(value.semantic_eq(&RespValue::Set(Some(Box::from(vec![
+            RespValue::Integer(2),
+            RespValue::Integer(1),
+        ])))));
+    }
+
+    #[test]
+    fn test_collect_aggregates() {
+        let array: RespValue = ["a", "b"].into_iter().map(RespValue::from).collect();
+        assert_eq!(
+            array,
+            RespValue::Array(Some(Box::from(vec!["a".into(), "b".into()])))
+        );
+        let empty: RespValue = std::iter::empty::<RespValue>().collect();
+        assert_eq!(empty, RespValue::empty_array());
+
+        let map: RespValue = (1..=2)
+            .map(|i| (RespValue::from(i), RespValue::from(i * 10)))
+            .collect();
+        assert_eq!(map.as_bytes(), b"%2\r\n:1\r\n:10\r\n:2\r\n:20\r\n");
+    }
+
+    #[test]
+    fn test_iterators() {
+        let array = RespValue::Array(Some(Box::from(vec![
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+        ])));
+        let borrowed: Vec<i64> = (&array).into_iter().filter_map(RespValue::as_int).collect();
+        assert_eq!(borrowed, vec![1, 2]);
+        assert_eq!(array.iter().count(), 2);
+        let owned: Vec<RespValue> = array.into_iter().collect();
+        assert_eq!(owned, vec![RespValue::Integer(1), RespValue::Integer(2)]);
+
+        let set = RespValue::Set(Some(Box::from(vec![RespValue::Boolean(true)])));
+        assert_eq!(set.into_iter().next(), Some(RespValue::Boolean(true)));
+        let push = RespValue::Push(Some(Box::from(vec![RespValue::Null])));
+        assert_eq!(push.iter().count(), 1);
+
+        assert_eq!(RespValue::Array(None).into_iter().count(), 0);
+        assert_eq!(RespValue::Set(None).iter().count(), 0);
+        assert_eq!(RespValue::Integer(5).into_iter().count(), 0);
+
+        let map = RespValue::Map(Some(Box::from(vec![(
+            RespValue::SimpleString("k".into()),
+            RespValue::Integer(1),
+        )])));
+        assert_eq!(map.iter().count(), 0);
+        assert_eq!(map.entries().count(), 1);
+        let (key, value) = map.into_entries().next().unwrap();
+        assert_eq!(key.as_str(), Some("k"));
+        assert_eq!(value, RespValue::Integer(1));
+        assert_eq!(RespValue::Map(None).entries().count(), 0);
+        assert_eq!(RespValue::Map(None).into_entries().count(), 0);
+    }
+
+    #[test]
+    fn test_get_and_index() {
+        let array = RespValue::Array(Some(Box::from(vec![
+            RespValue::Integer(1),
+            RespValue::Boolean(false),
+        ])));
+        assert_eq!(array.get(1), Some(&RespValue::Boolean(false)));
+        assert_eq!(array.get(2), None);
+        assert_eq!(array.get("field"), None);
+        assert_eq!(array[0], RespValue::Integer(1));
+        assert_eq!(array[5], RespValue::Null);
+
+        let map = RespValue::Map(Some(Box::from(vec![
+            (
+                RespValue::SimpleString("simple".into()),
+                RespValue::Integer(1),
+            ),
+            (
+                RespValue::BulkString(Some("bulk".into())),
+                RespValue::Integer(2),
+            ),
+            (RespValue::Integer(3), RespValue::Integer(3)),
+        ])));
+        assert_eq!(map.get("simple"), Some(&RespValue::Integer(1)));
+        assert_eq!(map.get(String::from("bulk")), Some(&RespValue::Integer(2)));
+        assert_eq!(map.get("3"), None);
+        assert_eq!(map.get(0), None);
+        assert_eq!(map["bulk"], RespValue::Integer(2));
+        assert_eq!(map["missing"]["nested"], RespValue::Null);
+
+        assert_eq!(RespValue::Array(None).get(0), None);
+        assert_eq!(RespValue::Map(None).get("simple"), None);
+        assert_eq!(RespValue::Integer(1)[0], RespValue::Null);
+    }
+
+    #[test]
+    fn test_into_string_map() {
+        let mut parser = Parser::new(100, 1000);
+        parser.read_buf(
+            b"%3\r\n+server\r\n$5\r\nredis\r\n$5\r\nproto\r\n:3\r\n=8\r\ntxt:role\r\n+master\r\n",
+        );
+        let map = parser
+            .try_parse()
+            .unwrap()
+            .unwrap()
+            .into_string_map()
+            .unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["server"].as_str(), Some("redis"));
+        assert_eq!(map["proto"], RespValue::Integer(3));
+        assert_eq!(map["role"].as_str(), Some("master"));
+
+        let bad_key = RespValue::Map(Some(Box::from(vec![(
+            RespValue::Integer(1),
+            RespValue::Integer(2),
+        )])));
+        let err = bad_key.into_string_map().unwrap_err();
+        assert_eq!(err.found, RespKind::Integer);
+        assert_eq!(err.to_string(), "Cannot convert Integer to String map key");
+
+        let err = RespValue::Array(Some(Box::default()))
+            .into_string_map()
+            .unwrap_err();
+        assert_eq!(err.found, RespKind::Array);
+        assert!(RespValue::Map(None).into_string_map().is_err());
+    }
+
+    #[test]
+    fn test_path() {
+        let mut parser = Parser::new(100, 1000);
+        parser.read_buf(
+            b"*3\r\n+a\r\n~1\r\n:7\r\n%2\r\n$8\r\nkeyspace\r\n%1\r\n+db0\r\n*1\r\n:9\r\n+10\r\n:10\r\n",
+        );
+        let value = parser.try_parse().unwrap().unwrap();
+        assert_eq!(value.path(""), Some(&value));
+        assert_eq!(value.path("0").and_then(RespValue::as_str), Some("a"));
+        assert_eq!(value.path("1.0"), Some(&RespValue::Integer(7)));
+        assert_eq!(value.path("2.keyspace.db0.0"), Some(&RespValue::Integer(9)));
+        assert_eq!(value.path("2.10"), Some(&RespValue::Integer(10)));
+        assert_eq!(value.path("3"), None);
+        assert_eq!(value.path("x"), None);
+        assert_eq!(value.path("0.0"), None);
+        assert_eq!(value.path("2.keyspace.db1"), None);
+        assert_eq!(value.path("2..db0"), None);
+    }
+
+    #[test]
+    fn test_display() {
+        let cases: Vec<(&[u8], &str)> = vec![
+            (b"+OK\r\n", "OK"),
+            (b"-ERR unknown\r\n", "(error) ERR unknown"),
+            (b"!3\r\nBAD\r\n", "(error) BAD"),
+            (b":5\r\n", "(integer) 5"),
+            (b",3.5\r\n", "(double) 3.5"),
+            (b",-inf\r\n", "(double) -inf"),
+            (b"#t\r\n", "(true)"),
+            (
+                b"(12345678901234567890\r\n",
+                "(big number) 12345678901234567890",
+            ),
+            (b"$-1\r\n", "(nil)"),
+            (b"*-1\r\n", "(nil)"),
+            (b"_\r\n", "(nil)"),
+            (b"$7\r\na\"b\\c\n\x01\r\n", "\"a\\\"b\\\\c\\n\\x01\""),
+            (b"=7\r\ntxt:raw\r\n", "raw"),
+            (b"*0\r\n", "(empty array)"),
+            (b"%0\r\n", "(empty hash)"),
+            (b"~0\r\n", "(empty set)"),
+            (b"~2\r\n:1\r\n:2\r\n", "1~ (integer) 1\n2~ (integer) 2"),
+            (
+                b"%2\r\n+a\r\n:1\r\n+b\r\n*2\r\n:2\r\n:3\r\n",
+                "1# a => (integer) 1\n2# b => 1) (integer) 2\n   2) (integer) 3",
+            ),
+            (b"|1\r\n+ttl\r\n:3\r\n:2\r\n", "(integer) 2"),
+        ];
+        for (input, expected) in cases {
+            let mut parser = Parser::new(100, 1000);
+            parser.read_buf(input);
+            let value = parser.try_parse().unwrap().unwrap();
+            assert_eq!(value.to_string(), expected, "input: {:?}", input);
+        }
+
+        let wide: RespValue = (1..=10).map(RespValue::Integer).collect();
+        let wide = RespValue::Array(Some(Box::from(vec![wide])));
+        let rendered = wide.to_string();
+        assert!(rendered.starts_with("1)  1) (integer) 1\n    2) (integer) 2\n"));
+        assert!(rendered.ends_with("\n   10) (integer) 10"));
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut parser = Parser::new(100, 1000);
+        parser.read_buf(b"%2\r\n+key\r\n$10\r\n0123456789\r\n$-1\r\n*2\r\n,1.5\r\n=9\r\ntxt:\xc3\xa9t\xc3\xa9\r\n");
+        let value = parser.try_parse().unwrap().unwrap();
+        assert_eq!(
+            format!("{:?}", value),
+            r#"Map({SimpleString("key"): BulkString("0123456789"), BulkString(None): Array([Double(1.5), VerbatimString { format: "txt", text: "été" }])})"#
+        );
+        assert_eq!(
+            format!("{:#.4?}", value),
+            r#"Map({
+    SimpleString("key"): BulkString("0123"... (10 bytes)),
+    BulkString(None): Array([
+        Double(1.5),
+        VerbatimString { format: "txt", text: "ét"... (5 bytes) },
+    ]),
+})"#
+        );
+
+        let bytes = RespValue::BulkBytes(Box::new(bytes::Bytes::from_static(b"ab\x00cdef")));
+        assert_eq!(format!("{:?}", bytes), r#"BulkBytes(b"ab\x00cdef")"#);
+        assert_eq!(
+            format!("{:.3?}", bytes),
+            r#"BulkBytes(b"ab\x00"... (7 bytes))"#
+        );
+        assert_eq!(format!("{:?}", RespValue::Null), "Null");
+        assert_eq!(
+            format!("{:?}", RespValue::BulkStreamed { len: 3 }),
+            "BulkStreamed { len: 3 }"
+        );
+    }
+
+    #[test]
+    fn test_summarize() {
+        let long = "x".repeat(100);
+        let short = RespValue::BulkString(Some("short".into()));
+        assert_eq!(short.summarize(10), short);
+        assert_eq!(RespValue::Integer(5).summarize(0), RespValue::Integer(5));
+
+        let cases = vec![
+            (
+                RespValue::SimpleString(long.clone().into()),
+                RespValue::SimpleString("<100 bytes>".into()),
+            ),
+            (
+                RespValue::Error(long.clone().into()),
+                RespValue::Error("<100 bytes>".into()),
+            ),
+            (
+                RespValue::BulkBytes(Box::new(bytes::Bytes::from(long.clone()))),
+                RespValue::BulkString(Some("<100 bytes>".into())),
+            ),
+            (
+                RespValue::VerbatimString(Some(Box::new(
+                    Verbatim::new("txt", long.clone()).unwrap(),
+                ))),
+                RespValue::VerbatimString(Some(Box::new(
+                    Verbatim::new("txt", "<100 bytes>").unwrap(),
+                ))),
+            ),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(value.summarize(10), expected);
+        }
+
+        let nested = RespValue::Array(Some(Box::from(vec![
+            RespValue::BulkString(Some(long.clone().into())),
+            RespValue::Map(Some(
+                (0..10)
+                    .map(|i| (RespValue::Integer(i), RespValue::Integer(i)))
+                    .collect(),
+            )),
+        ])));
+        let summary = nested.summarize(20);
+        assert_eq!(summary[0].as_str(), Some("<100 bytes>"));
+        let entries = summary[1].as_map().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert_eq!(entries[3].0.as_str(), Some("<7 more entries>"));
+        assert_eq!(entries[3].1, RespValue::Null);
+        assert!(summary.encoded_len() < 100);
+
+        let array: RespValue = (0..3).map(RespValue::Integer).collect();
+        assert_eq!(array.summarize(100), array);
+    }
+
+    #[test]
+    fn test_redacted() {
+        fn command(args: &[&str]) -> RespValue<'static> {
+            args.iter()
+                .map(|arg| RespValue::BulkString(Some(arg.to_string().into())))
+                .collect()
+        }
+        let cases: Vec<(&[&str], &[&str])> = vec![
+            (&["AUTH", "secret"], &["AUTH", "(redacted)"]),
+            (
+                &["auth", "user", "secret"],
+                &["auth", "(redacted)", "(redacted)"],
+            ),
+            (
+                &["HELLO", "3", "AUTH", "user", "secret", "SETNAME", "app"],
+                &[
+                    "HELLO",
+                    "3",
+                    "AUTH",
+                    "(redacted)",
+                    "(redacted)",
+                    "SETNAME",
+                    "app",
+                ],
+            ),
+            (
+                &["HELLO", "3", "auth", "user"],
+                &["HELLO", "3", "auth", "(redacted)"],
+            ),
+            (
+                &[
+                    "MIGRATE", "h", "6379", "", "0", "5000", "AUTH", "secret", "KEYS", "k",
+                ],
+                &[
+                    "MIGRATE",
+                    "h",
+                    "6379",
+                    "",
+                    "0",
+                    "5000",
+                    "AUTH",
+                    "(redacted)",
+                    "KEYS",
+                    "k",
+                ],
+            ),
+            (
+                &[
+                    "MIGRATE", "h", "6379", "k", "0", "5000", "AUTH2", "user", "secret",
+                ],
+                &[
+                    "MIGRATE",
+                    "h",
+                    "6379",
+                    "k",
+                    "0",
+                    "5000",
+                    "AUTH2",
+                    "(redacted)",
+                    "(redacted)",
+                ],
+            ),
+            (
+                &["CONFIG", "SET", "maxmemory", "1gb", "requirepass", "secret"],
+                &[
+                    "CONFIG",
+                    "SET",
+                    "maxmemory",
+                    "1gb",
+                    "requirepass",
+                    "(redacted)",
+                ],
+            ),
+            (
+                &["config", "set", "MASTERAUTH", "secret"],
+                &["config", "set", "MASTERAUTH", "(redacted)"],
+            ),
+            (
+                &["CONFIG", "GET", "requirepass"],
+                &["CONFIG", "GET", "requirepass"],
+            ),
+            (&["SET", "AUTH", "secret"], &["SET", "AUTH", "secret"]),
+            (
+                &["HELLO", "3", "SETNAME", "AUTH2"],
+                &["HELLO", "3", "SETNAME", "AUTH2"],
+            ),
+            (
+                &["MIGRATE", "h", "6379", "AUTH", "0", "5000", "COPY"],
+                &["MIGRATE", "h", "6379", "AUTH", "0", "5000", "COPY"],
+            ),
+            (&["AUTH"], &["AUTH"]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(command(input).redacted(), command(expected), "{:?}", input);
+        }
+
+        let mut parser = Parser::new(100, 1000);
+        parser.read_buf(b"*2\r\n$4\r\nAUTH\r\n$6\r\nsecret\r\n");
+        let parsed = parser.try_parse().unwrap().unwrap();
+        assert_eq!(parsed.redacted()[1].as_str(), Some("(redacted)"));
+        assert_eq!(RespValue::Integer(1).redacted(), RespValue::Integer(1));
+    }
+
+    #[test]
+    fn test_is_null_and_is_empty() {
+        let nulls = [
+            &b"_\r\n"[..],
+            b"$-1\r\n",
+            b"*-1\r\n",
+            b"%-1\r\n",
+            b"~-1\r\n",
+            b"|1\r\n+ttl\r\n:3\r\n_\r\n",
+        ];
+        let empties = [
+            &b"$0\r\n\r\n"[..],
+            b"+\r\n",
+            b"*0\r\n",
+            b"%0\r\n",
+            b"~0\r\n",
+            b">0\r\n",
+            b"=4\r\ntxt:\r\n",
+        ];
+        let others = [
+            &b"$1\r\nx\r\n"[..],
+            b":0\r\n",
+            b"#f\r\n",
+            b"*1\r\n_\r\n",
+            b",0\r\n",
+        ];
+        let parse = |input: &[u8]| {
+            let mut parser = Parser::new(100, 1000);
+            parser.read_buf(input);
+            parser.try_parse().unwrap().unwrap().into_owned()
+        };
+        for input in nulls {
+            let value = parse(input);
+            assert!(value.is_null(), "{:?}", value);
+            assert!(!value.is_empty(), "{:?}", value);
+            assert!(value.is_null_or_empty(), "{:?}", value);
+        }
+        for input in empties {
+            let value = parse(input);
+            assert!(!value.is_null(), "{:?}", value);
+            assert!(value.is_empty(), "{:?}", value);
+            assert!(value.is_null_or_empty(), "{:?}", value);
+        }
+        for input in others {
+            let value = parse(input);
+            assert!(!value.is_null_or_empty(), "{:?}", value);
+        }
+        assert!(RespValue::BulkBytes(Box::new(bytes::Bytes::new())).is_empty());
+        assert!(RespValue::BulkStreamed { len: 0 }.is_empty());
+    }
+
+    #[test]
+    fn test_encode_into_and_write_to() {
+        let value = RespValue::Map(Some(Box::from(vec![(
+            RespValue::BulkString(Some("key".into())),
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::Double(0.25),
+                RespValue::Null,
+                RespValue::VerbatimString(Some(Box::new(Verbatim::txt("text")))),
+            ]))),
+        )])));
+
+        let mut out = bytes::BytesMut::new();
+        value.encode_into(&mut out);
+        assert_eq!(out.capacity(), value.encoded_len());
+        value.encode_into(&mut out);
+        assert_eq!(&out[..], [value.as_bytes(), value.as_bytes()].concat());
+
+        let mut vec = b"prefix".to_vec();
+        value.write_to(&mut vec);
+        assert_eq!(vec, [&b"prefix"[..], &value.as_bytes()].concat());
+
+        let options = EncodeOptions {
+            resp2_nulls: true,
+            ..EncodeOptions::default()
+        };
+        let mut vec = Vec::new();
+        value.write_to_with(&mut vec, options);
+        assert_eq!(vec, value.as_bytes_with(options));
+
+        let mut fixed = [0u8; 32];
+        let mut slice = &mut fixed[..];
+        RespValue::Integer(-15).write_to(&mut slice);
+        let remaining = slice.len();
+        assert_eq!(&fixed[..32 - remaining], b":-15\r\n");
+    }
+
+    #[test]
+    fn test_write_to_io() {
+        /// Records the size of every write.
+        #[derive(Default)]
+        struct Recorder {
+            bytes: Vec<u8>,
+            writes: Vec<usize>,
+        }
+
+        impl std::io::Write for Recorder {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.bytes.extend_from_slice(buf);
+                self.writes.push(buf.len());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let large = "x".repeat(100_000);
+        let value = RespValue::Array(Some(Box::from(vec![
+            RespValue::SimpleString("head".into()),
+            RespValue::BulkString(Some(large.clone().into())),
+            RespValue::BulkBytes(Box::new(bytes::Bytes::from(large.clone()))),
+            RespValue::VerbatimString(Some(Box::new(Verbatim::txt(large.clone())))),
+            (0..5000).map(RespValue::Integer).collect(),
+            RespValue::Attribute(Box::new(crate::resp::Attributed {
+                attributes: vec![(RespValue::Integer(1), RespValue::Integer(2))],
+                value: RespValue::Map(Some(Box::from(vec![(RespValue::Null, RespValue::Null)]))),
+            })),
+        ])));
+        let mut recorder = Recorder::default();
+        let written = value.write_to_io(&mut recorder).unwrap();
+        assert_eq!(recorder.bytes, value.as_bytes());
+        assert_eq!(written, recorder.bytes.len());
+        // The large payloads are written as they are, everything else in chunks
+        assert_eq!(recorder.writes.iter().filter(|&&n| n == 100_000).count(), 3);
+        assert!(recorder.writes.iter().all(|&n| n == 100_000 || n < 9000));
+
+        let mut out = Vec::new();
+        assert_eq!(RespValue::Integer(5).write_to_io(&mut out).unwrap(), 4);
+        assert_eq!(out, b":5\r\n");
+
+        let mut full = [0u8; 8];
+        let err = value.write_to_io(&mut &mut full[..]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::WriteZero);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_write_to_async() {
+        let large = "y".repeat(50_000);
+        let value = RespValue::Push(Some(Box::from(vec![
+            RespValue::SimpleString("message".into()),
+            RespValue::BulkString(Some(large.into())),
+            (0..3000).map(RespValue::Integer).collect(),
+            RespValue::Map(Some(Box::from(vec![(
+                RespValue::Integer(1),
+                RespValue::Null,
+            )]))),
+        ])));
+        let expected = value.as_bytes();
+
+        let (mut client, mut server) = tokio::io::duplex(4096);
+        let reader = tokio::spawn(async move {
+            use tokio::io::AsyncReadExt;
+            let mut received = Vec::new();
+            server.read_to_end(&mut received).await.unwrap();
+            received
+        });
+        let written = value.write_to_async(&mut client).await.unwrap();
+        drop(client);
+        assert_eq!(written, expected.len());
+        assert_eq!(reader.await.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_as_bytes_allocates_once() {
+        let config = crate::generator::GeneratorConfig::default();
+        for frame in crate::generator::frames(5, config).take(300) {
+            let value = frame.value.unwrap();
+            let bytes = value.as_bytes();
+            assert_eq!(bytes, frame.bytes);
+            assert_eq!(bytes.capacity(), bytes.len(), "{:?}", value);
+        }
+        let options = EncodeOptions {
+            double_exponents: true,
+            resp2_nulls: true,
+            ..EncodeOptions::default()
+        };
+        for value in [RespValue::Double(1e300), RespValue::Null] {
+            let bytes = value.as_bytes_with(options);
+            assert_eq!(bytes.capacity(), bytes.len());
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_into_unchecked() {
+        assert_eq!(RespValue::from("OK").into_string_unchecked(), "OK");
+        assert_eq!(
+            RespValue::BulkString(None).into_option_string_unchecked(),
+            None
+        );
+        assert_eq!(RespValue::Integer(42).into_i64_unchecked(), 42);
+        assert!(RespValue::Boolean(true).into_bool_unchecked());
+        assert_eq!(RespValue::Double(1.5).into_f64_unchecked(), 1.5);
+        let array: RespValue = (1..=2).map(RespValue::Integer).collect();
+        assert_eq!(
+            array.into_vec_unchecked(),
+            vec![RespValue::Integer(1), RespValue::Integer(2)]
+        );
+        let map = RespValue::Map(Some(Box::from(vec![("a".into(), RespValue::Integer(1))])));
+        assert_eq!(
+            map.into_map_unchecked(),
+            vec![("a".into(), RespValue::Integer(1))]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot convert Integer to String")]
+    #[allow(deprecated)]
+    fn test_into_unchecked_panics() {
+        RespValue::Integer(1).into_string_unchecked();
+    }
 }
@@ -0,0 +1,168 @@
+use crate::parser::ParseError;
+use memchr::memchr;
+
+/// Low-level scanning primitives used on the parser's hot paths.
+///
+/// Alternative implementations can be benchmarked against each other without
+/// touching the state machine; `ScannerKind` selects one at runtime.
+pub trait Scanner {
+    /// Finds the position of the next CRLF in `buf` at or after `start`.
+    fn find_crlf(buf: &[u8], start: usize) -> Option<usize>;
+
+    /// Returns the number of leading ASCII digits in `bytes`.
+    fn digit_run(bytes: &[u8]) -> usize;
+
+    /// Parses a run of ASCII digits, as found by `digit_run`, into an unsigned value.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError::Overflow` if the value does not fit in a `u64`.
+    fn parse_digits(digits: &[u8]) -> Result<u64, ParseError>;
+}
+
+/// Byte-at-a-time scanning without any dependency, for exotic targets.
+#[derive(Debug, Clone, Copy)]
+pub struct ScalarScanner;
+
+impl Scanner for ScalarScanner {
+    fn find_crlf(buf: &[u8], start: usize) -> Option<usize> {
+        buf.get(start..)?
+            .windows(2)
+            .position(|pair| pair == b"\r\n")
+            .map(|pos| start + pos)
+    }
+
+    #[inline(always)]
+    fn digit_run(bytes: &[u8]) -> usize {
+        bytes.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+
+    #[inline(always)]
+    fn parse_digits(digits: &[u8]) -> Result<u64, ParseError> {
+        digits.iter().try_fold(0u64, |value, &digit| {
+            value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add((digit - b'0') as u64))
+                .ok_or(ParseError::Overflow)
+        })
+    }
+}
+
+/// Finds `\r` with `memchr`, then checks for the following `\n`. The default.
+#[derive(Debug, Clone, Copy)]
+pub struct MemchrScanner;
+
+impl Scanner for MemchrScanner {
+    /// Lone `\r` bytes are skipped iteratively, so any amount of them costs no stack.
+    #[inline(always)]
+    fn find_crlf(buf: &[u8], start: usize) -> Option<usize> {
+        let mut from = start;
+        loop {
+            // Use memchr's more optimized implementation
+            let pos = from + memchr(b'\r', &buf[from..])?;
+
+            // Check if there's a \n after the \r
+            match buf.get(pos + 1) {
+                Some(b'\n') => return Some(pos),
+                Some(_) => from = pos + 1,
+                None => return None,
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn digit_run(bytes: &[u8]) -> usize {
+        ScalarScanner::digit_run(bytes)
+    }
+
+    #[inline(always)]
+    fn parse_digits(digits: &[u8]) -> Result<u64, ParseError> {
+        ScalarScanner::parse_digits(digits)
+    }
+}
+
+/// Vectorized two-byte CRLF search and SWAR digit handling. Experimental.
+#[cfg(feature = "simd")]
+#[derive(Debug, Clone, Copy)]
+pub struct SimdScanner;
+
+#[cfg(feature = "simd")]
+impl Scanner for SimdScanner {
+    /// Searches for the two-byte needle directly, so lone `\r` bytes never interrupt
+    /// the vectorized scan.
+    #[inline(always)]
+    fn find_crlf(buf: &[u8], start: usize) -> Option<usize> {
+        static CRLF_FINDER: std::sync::LazyLock<memchr::memmem::Finder<'static>> =
+            std::sync::LazyLock::new(|| memchr::memmem::Finder::new(b"\r\n"));
+        CRLF_FINDER.find(&buf[start..]).map(|pos| start + pos)
+    }
+
+    #[inline(always)]
+    fn digit_run(bytes: &[u8]) -> usize {
+        crate::simd::digit_run(bytes)
+    }
+
+    #[inline(always)]
+    fn parse_digits(digits: &[u8]) -> Result<u64, ParseError> {
+        crate::simd::parse_digits(digits)
+    }
+}
+
+/// The scanner used where no `ParserConfig` applies: `SimdScanner` with the `simd`
+/// feature, `MemchrScanner` otherwise.
+#[cfg(not(feature = "simd"))]
+pub type DefaultScanner = MemchrScanner;
+#[cfg(feature = "simd")]
+pub type DefaultScanner = SimdScanner;
+
+/// Selects the `Scanner` a parser uses; see `ParserConfig::scanner`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScannerKind {
+    Scalar,
+    Memchr,
+    #[cfg(feature = "simd")]
+    Simd,
+}
+
+impl Default for ScannerKind {
+    fn default() -> Self {
+        #[cfg(feature = "simd")]
+        return ScannerKind::Simd;
+        #[cfg(not(feature = "simd"))]
+        return ScannerKind::Memchr;
+    }
+}
+
+impl ScannerKind {
+    #[inline(always)]
+    pub(crate) fn find_crlf(self, buf: &[u8], start: usize) -> Option<usize> {
+        match self {
+            ScannerKind::Scalar => ScalarScanner::find_crlf(buf, start),
+            ScannerKind::Memchr => MemchrScanner::find_crlf(buf, start),
+            #[cfg(feature = "simd")]
+            ScannerKind::Simd => SimdScanner::find_crlf(buf, start),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn digit_run(self, bytes: &[u8]) -> usize {
+        match self {
+            ScannerKind::Scalar => ScalarScanner::digit_run(bytes),
+            ScannerKind::Memchr => MemchrScanner::digit_run(bytes),
+            #[cfg(feature = "simd")]
+            ScannerKind::Simd => SimdScanner::digit_run(bytes),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn parse_digits(self, digits: &[u8]) -> Result<u64, ParseError> {
+        match self {
+            ScannerKind::Scalar => ScalarScanner::parse_digits(digits),
+            ScannerKind::Memchr => MemchrScanner::parse_digits(digits),
+            #[cfg(feature = "simd")]
+            ScannerKind::Simd => SimdScanner::parse_digits(digits),
+        }
+    }
+}
+
+//EOF
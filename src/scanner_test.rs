@@ -0,0 +1,79 @@
+use crate::parser::{ParseError, Parser, ParserConfig};
+use crate::resp::RespValue;
+use crate::scanner::{MemchrScanner, ScalarScanner, Scanner, ScannerKind};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds() -> Vec<ScannerKind> {
+        vec![
+            ScannerKind::Scalar,
+            ScannerKind::Memchr,
+            #[cfg(feature = "simd")]
+            ScannerKind::Simd,
+        ]
+    }
+
+    #[test]
+    fn test_scanners_agree_on_crlf() {
+        let inputs: [&[u8]; 6] = [b"", b"\r", b"ab\r\n", b"\r\r\r\n", b"a\rb\nc\r\n", b"\n\r"];
+        for input in inputs {
+            for start in 0..=input.len() {
+                let expected = ScalarScanner::find_crlf(input, start);
+                assert_eq!(MemchrScanner::find_crlf(input, start), expected);
+                for kind in kinds() {
+                    assert_eq!(kind.find_crlf(input, start), expected, "{:?}", kind);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_scanners_agree_on_digits() {
+        for kind in kinds() {
+            assert_eq!(kind.digit_run(b"12345678901\r\n"), 11);
+            assert_eq!(kind.digit_run(b"-1"), 0);
+            assert_eq!(kind.parse_digits(b"12345678901"), Ok(12_345_678_901));
+            assert_eq!(
+                kind.parse_digits(b"99999999999999999999"),
+                Err(ParseError::Overflow)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parser_with_each_scanner() {
+        let input = b"*3\r\n$5\r\nhello\r\n:-1234567890123\r\n+a\rb\r\n";
+        for kind in kinds() {
+            let mut parser = Parser::with_config(ParserConfig {
+                scanner: kind,
+                ..ParserConfig::default()
+            });
+            parser.read_buf(input);
+            assert_eq!(
+                parser.try_parse(),
+                Err(ParseError::InvalidFormat(
+                    "Simple string cannot contain CR or LF".into()
+                )),
+                "{:?}",
+                kind
+            );
+
+            parser = Parser::with_config(ParserConfig {
+                scanner: kind,
+                ..ParserConfig::default()
+            });
+            parser.read_buf(b"*2\r\n$5\r\nhello\r\n:-1234567890123\r\n");
+            assert_eq!(
+                parser.try_parse(),
+                Ok(Some(RespValue::Array(Some(Box::from(vec![
+                    RespValue::BulkString(Some("hello".into())),
+                    RespValue::Integer(-1_234_567_890_123),
+                ]))))),
+                "{:?}",
+                kind
+            );
+        }
+    }
+}
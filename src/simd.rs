@@ -0,0 +1,78 @@
+//! SWAR (SIMD within a register) helpers backing the `simd` feature.
+//!
+//! Digits are checked and converted eight bytes at a time instead of one byte per
+//! parser iteration.
+
+use crate::parser::ParseError;
+
+const LANES: usize = 8;
+const ONES: u64 = 0x0101_0101_0101_0101;
+const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+
+#[inline(always)]
+fn load(chunk: &[u8]) -> u64 {
+    let mut word = [0u8; LANES];
+    word.copy_from_slice(chunk);
+    u64::from_le_bytes(word)
+}
+
+/// Returns a mask with the high bit set in every byte of `word` that is not an ASCII digit.
+#[inline(always)]
+fn non_digit_mask(word: u64) -> u64 {
+    let low = word & !HIGH_BITS;
+    // High bit set iff the low seven bits are >= b'0', respectively >= b':'
+    let at_least_zero = low + ONES * (0x80 - b'0' as u64);
+    let above_nine = low + ONES * (0x80 - b':' as u64);
+    (!at_least_zero | above_nine | word) & HIGH_BITS
+}
+
+/// Converts eight ASCII digits, most significant first, into their value.
+#[inline(always)]
+fn parse_eight(word: u64) -> u64 {
+    let word = (word & 0x0F0F_0F0F_0F0F_0F0F).wrapping_mul(2561) >> 8;
+    let word = (word & 0x00FF_00FF_00FF_00FF).wrapping_mul(6_553_601) >> 16;
+    (word & 0x0000_FFFF_0000_FFFF).wrapping_mul(42_949_672_960_001) >> 32
+}
+
+/// Returns the number of leading ASCII digits in `bytes`.
+#[inline(always)]
+pub(crate) fn digit_run(bytes: &[u8]) -> usize {
+    let mut len = 0;
+    while let Some(chunk) = bytes.get(len..len + LANES) {
+        let mask = non_digit_mask(load(chunk));
+        if mask != 0 {
+            return len + (mask.trailing_zeros() / 8) as usize;
+        }
+        len += LANES;
+    }
+    len + bytes[len..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count()
+}
+
+/// Parses a run of ASCII digits, as found by `digit_run`, into an unsigned value.
+///
+/// # Errors
+///
+/// Returns `ParseError::Overflow` if the value does not fit in a `u64`.
+#[inline(always)]
+pub(crate) fn parse_digits(digits: &[u8]) -> Result<u64, ParseError> {
+    let mut value: u64 = 0;
+    let mut chunks = digits.chunks_exact(LANES);
+    for chunk in &mut chunks {
+        value = value
+            .checked_mul(100_000_000)
+            .and_then(|v| v.checked_add(parse_eight(load(chunk))))
+            .ok_or(ParseError::Overflow)?;
+    }
+    for &digit in chunks.remainder() {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add((digit - b'0') as u64))
+            .ok_or(ParseError::Overflow)?;
+    }
+    Ok(value)
+}
+
+//EOF
@@ -0,0 +1,37 @@
+use crate::parser::ParseError;
+use crate::simd::{digit_run, parse_digits};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digit_run() {
+        assert_eq!(digit_run(b""), 0);
+        assert_eq!(digit_run(b"\r\n"), 0);
+        assert_eq!(digit_run(b"123\r\n"), 3);
+        assert_eq!(digit_run(b"12345678\r\n"), 8);
+        assert_eq!(digit_run(b"1234567890123/"), 13);
+        assert_eq!(digit_run(b"12345678:"), 8);
+        // Bytes with the high bit set are never digits
+        assert_eq!(digit_run(b"1234\xb0\xb95678"), 4);
+        for byte in 0..=255u8 {
+            let input = [b'1', b'2', b'3', b'4', b'5', b'6', byte, b'8', b'9'];
+            let expected = if byte.is_ascii_digit() { 9 } else { 6 };
+            assert_eq!(digit_run(&input), expected, "byte {:#x}", byte);
+        }
+    }
+
+    #[test]
+    fn test_parse_digits() {
+        assert_eq!(parse_digits(b""), Ok(0));
+        assert_eq!(parse_digits(b"7"), Ok(7));
+        assert_eq!(parse_digits(b"12345678"), Ok(12_345_678));
+        assert_eq!(parse_digits(b"00000000042"), Ok(42));
+        assert_eq!(parse_digits(b"18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(
+            parse_digits(b"18446744073709551616"),
+            Err(ParseError::Overflow)
+        );
+    }
+}
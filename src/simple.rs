@@ -0,0 +1,106 @@
+use crate::resp::RespValue;
+use std::borrow::Cow;
+
+/// A plain value without RESP protocol details, for use in domain layers.
+///
+/// Converting from `RespValue` is lossy:
+///
+/// - Simple, bulk and verbatim strings become `Str`; verbatim strings keep their
+///   text only, dropping the format such as `txt`.
+/// - Simple and bulk errors become `Str` as well, so they are no longer told apart
+///   from replies.
+/// - Big numbers become `Int` when they fit in an `i64`, `Str` otherwise.
+/// - Arrays, sets and pushes become `List`.
+/// - Attributes are dropped, keeping the value they were attached to.
+/// - `Null` and every null string or aggregate become `Null`, as do streamed bulk
+///   strings, whose body is not part of the value.
+///
+/// Converting back yields bulk strings, arrays and maps only. `Bytes` become
+/// `BulkBytes`, so binary payloads survive the round trip.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum SimpleValue {
+    Str(String),
+    Bytes(Vec<u8>),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    #[default]
+    Null,
+    List(Vec<SimpleValue>),
+    Map(Vec<(SimpleValue, SimpleValue)>),
+}
+
+impl From<RespValue<'_>> for SimpleValue {
+    fn from(value: RespValue<'_>) -> Self {
+        match value {
+            RespValue::SimpleString(s)
+            | RespValue::Error(s)
+            | RespValue::BulkString(Some(s))
+            | RespValue::BulkError(Some(s)) => SimpleValue::Str(s.into_owned()),
+            RespValue::VerbatimString(Some(v)) => SimpleValue::Str(v.text().to_owned()),
+            RespValue::BulkBytes(b) => match String::from_utf8(b.to_vec()) {
+                Ok(s) => SimpleValue::Str(s),
+                Err(e) => SimpleValue::Bytes(e.into_bytes()),
+            },
+            RespValue::BigNumber(n) => match n.parse() {
+                Ok(i) => SimpleValue::Int(i),
+                Err(_) => SimpleValue::Str(n.into_owned()),
+            },
+            RespValue::Integer(i) => SimpleValue::Int(i),
+            RespValue::Attribute(attributed) => SimpleValue::from(attributed.value),
+            RespValue::Double(d) => SimpleValue::Float(d),
+            RespValue::Boolean(b) => SimpleValue::Bool(b),
+            RespValue::Array(Some(values))
+            | RespValue::Set(Some(values))
+            | RespValue::Push(Some(values)) => SimpleValue::List(from_replies(values)),
+            RespValue::Map(Some(entries)) => SimpleValue::Map(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (SimpleValue::from(k), SimpleValue::from(v)))
+                    .collect(),
+            ),
+            RespValue::BulkString(None)
+            | RespValue::BulkError(None)
+            | RespValue::VerbatimString(None)
+            | RespValue::Array(None)
+            | RespValue::Set(None)
+            | RespValue::Push(None)
+            | RespValue::Map(None)
+            | RespValue::Null
+            | RespValue::BulkStreamed { .. } => SimpleValue::Null,
+        }
+    }
+}
+
+impl From<SimpleValue> for RespValue<'static> {
+    fn from(value: SimpleValue) -> Self {
+        match value {
+            SimpleValue::Str(s) => RespValue::BulkString(Some(Cow::Owned(s))),
+            SimpleValue::Bytes(b) => RespValue::from(b),
+            SimpleValue::Int(i) => RespValue::Integer(i),
+            SimpleValue::Float(f) => RespValue::Double(f),
+            SimpleValue::Bool(b) => RespValue::Boolean(b),
+            SimpleValue::Null => RespValue::Null,
+            SimpleValue::List(values) => RespValue::Array(Some(Box::from(into_replies(values)))),
+            SimpleValue::Map(entries) => RespValue::Map(Some(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (RespValue::from(k), RespValue::from(v)))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// Converts a batch of replies, such as the elements of an array or the replies to a
+/// pipeline, into `SimpleValue`s.
+pub fn from_replies<'a>(replies: impl IntoIterator<Item = RespValue<'a>>) -> Vec<SimpleValue> {
+    replies.into_iter().map(SimpleValue::from).collect()
+}
+
+/// Converts a batch of `SimpleValue`s into replies.
+pub fn into_replies(values: impl IntoIterator<Item = SimpleValue>) -> Vec<RespValue<'static>> {
+    values.into_iter().map(RespValue::from).collect()
+}
+
+//EOF
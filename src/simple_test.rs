@@ -0,0 +1,96 @@
+use crate::resp::{RespValue, Verbatim};
+use crate::simple::{SimpleValue, from_replies, into_replies};
+use bytes::Bytes;
+use std::borrow::Cow;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_resp_value() {
+        let cases = vec![
+            (
+                RespValue::SimpleString("OK".into()),
+                SimpleValue::Str("OK".into()),
+            ),
+            (
+                RespValue::Error("ERR x".into()),
+                SimpleValue::Str("ERR x".into()),
+            ),
+            (
+                RespValue::VerbatimString(Some(Box::new(Verbatim::txt("hi")))),
+                SimpleValue::Str("hi".into()),
+            ),
+            (RespValue::BigNumber("-42".into()), SimpleValue::Int(-42)),
+            (
+                RespValue::BigNumber("3492890328409238509324850943850943825024385".into()),
+                SimpleValue::Str("3492890328409238509324850943850943825024385".into()),
+            ),
+            (RespValue::Double(1.5), SimpleValue::Float(1.5)),
+            (RespValue::Boolean(true), SimpleValue::Bool(true)),
+            (RespValue::BulkString(None), SimpleValue::Null),
+            (RespValue::Set(None), SimpleValue::Null),
+            (
+                RespValue::Push(Some(Box::from(vec![RespValue::Integer(1)]))),
+                SimpleValue::List(vec![SimpleValue::Int(1)]),
+            ),
+            (
+                RespValue::Map(Some(Box::from(vec![(
+                    RespValue::BulkString(Some("k".into())),
+                    RespValue::Null,
+                )]))),
+                SimpleValue::Map(vec![(SimpleValue::Str("k".into()), SimpleValue::Null)]),
+            ),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(SimpleValue::from(value), expected);
+        }
+    }
+
+    #[test]
+    fn test_into_resp_value() {
+        let value = SimpleValue::List(vec![
+            SimpleValue::Str("a".into()),
+            SimpleValue::Bytes(vec![b'b', 0xff]),
+            SimpleValue::Map(vec![(SimpleValue::Int(1), SimpleValue::Bool(false))]),
+        ]);
+        assert_eq!(
+            RespValue::from(value),
+            RespValue::Array(Some(Box::from(vec![
+                RespValue::BulkString(Some(Cow::Borrowed("a"))),
+                RespValue::BulkBytes(Box::new(Bytes::from_static(b"b\xff"))),
+                RespValue::Map(Some(Box::from(vec![(
+                    RespValue::Integer(1),
+                    RespValue::Boolean(false)
+                )]))),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_batch_conversions() {
+        let replies = vec![RespValue::SimpleString("OK".into()), RespValue::Integer(2)];
+        let values = from_replies(replies);
+        assert_eq!(
+            values,
+            vec![SimpleValue::Str("OK".into()), SimpleValue::Int(2)]
+        );
+        assert_eq!(
+            into_replies(values),
+            vec![
+                RespValue::BulkString(Some("OK".into())),
+                RespValue::Integer(2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let value = SimpleValue::List(vec![
+            SimpleValue::Bytes(vec![0, 0xff, 0xfe, b'\r', b'\n']),
+            SimpleValue::Str("text".into()),
+        ]);
+        assert_eq!(SimpleValue::from(RespValue::from(value.clone())), value);
+    }
+}
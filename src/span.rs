@@ -0,0 +1,159 @@
+use crate::parser::{ParseError, Parser, ParserConfig, find_crlf, frame_end, parse_decimal};
+use crate::resp::RespValue;
+use std::ops::Range;
+
+const CRLF_LEN: usize = 2;
+// The element count of a streamed aggregate, which its `.` end marker closes
+const STREAMED: usize = usize::MAX;
+
+/// A decoded value together with the byte range it was encoded at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueWithSpan {
+    pub value: RespValue<'static>,
+    /// The range of the value's full encoding, including its trailing CRLF.
+    pub range: Range<usize>,
+    /// Number of aggregates enclosing the value; the frame itself is at depth 0.
+    pub depth: usize,
+}
+
+/// Walks a frame depth-first, yielding every value with its byte range.
+///
+/// An aggregate is yielded before its elements, so a caller can locate any element of
+/// a frame and patch its bytes in place without re-encoding the rest.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::RespValue;
+/// use stream_resp::span::Spans;
+///
+/// let frame = b"*2\r\n$3\r\nGET\r\n$3\r\nfoo\r\n";
+/// let key = Spans::new(frame).nth(2).unwrap().unwrap();
+/// assert_eq!(key.value, RespValue::BulkString(Some("foo".into())));
+///
+/// let mut patched = frame.to_vec();
+/// patched.splice(key.range, b"$3\r\nbar\r\n".iter().copied());
+/// assert_eq!(patched, b"*2\r\n$3\r\nGET\r\n$3\r\nbar\r\n");
+/// ```
+#[derive(Debug)]
+pub struct Spans<'a> {
+    frame: &'a [u8],
+    pos: usize,
+    end: Option<usize>,
+    // Remaining element counts of the aggregates currently open, `STREAMED` for
+    // streamed aggregates
+    pending: Vec<usize>,
+    config: ParserConfig,
+    parser: Parser,
+}
+
+impl<'a> Spans<'a> {
+    /// Creates a walker over the frame starting at the beginning of `frame`.
+    pub fn new(frame: &'a [u8]) -> Self {
+        Spans::with_config(frame, ParserConfig::default())
+    }
+
+    /// Creates a walker enforcing the limits of `config`.
+    pub fn with_config(frame: &'a [u8], config: ParserConfig) -> Self {
+        Spans {
+            frame,
+            pos: 0,
+            end: None,
+            pending: Vec::new(),
+            parser: Parser::with_config(config.clone()),
+            config,
+        }
+    }
+
+    fn next_span(&mut self) -> Result<ValueWithSpan, ParseError> {
+        let start = self.pos;
+        let end = frame_end(self.frame, start, &self.config)?;
+        self.end.get_or_insert(end);
+
+        self.parser.read_buf(&self.frame[start..end]);
+        let value = self.parser.try_parse()?.unwrap_or(RespValue::Null);
+        let depth = self.pending.len();
+
+        // Step into non-empty aggregates, over everything else
+        let marker = self.frame[start];
+        let children = match marker {
+            b'*' | b'%' | b'~' | b'>' | b'|' => {
+                let line_end = find_crlf(self.frame, start + 1).ok_or(ParseError::UnexpectedEof)?;
+                let line = &self.frame[start + 1..line_end];
+                self.pos = line_end + CRLF_LEN;
+                if line == b"?" {
+                    // An empty streamed aggregate is just its header and end marker
+                    if self.frame[self.pos] == b'.' {
+                        0
+                    } else {
+                        STREAMED
+                    }
+                } else {
+                    let len = parse_decimal(line)?.max(0) as usize;
+                    match marker {
+                        b'%' => len * 2,
+                        // The annotated value is the last child
+                        b'|' => len * 2 + 1,
+                        _ => len,
+                    }
+                }
+            }
+            _ => 0,
+        };
+
+        if children > 0 {
+            self.pending.push(children);
+        } else {
+            self.pos = end;
+            self.finish_value()?;
+        }
+
+        Ok(ValueWithSpan {
+            value,
+            range: start..end,
+            depth,
+        })
+    }
+
+    /// Counts a value ending at `pos` against the aggregates it completes, stepping
+    /// over the end markers of streamed ones.
+    fn finish_value(&mut self) -> Result<(), ParseError> {
+        while let Some(remaining) = self.pending.last_mut() {
+            if *remaining == STREAMED {
+                if self.frame[self.pos] != b'.' {
+                    break;
+                }
+                let line_end =
+                    find_crlf(self.frame, self.pos + 1).ok_or(ParseError::UnexpectedEof)?;
+                self.pos = line_end + CRLF_LEN;
+            } else {
+                *remaining -= 1;
+                if *remaining > 0 {
+                    break;
+                }
+            }
+            self.pending.pop();
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for Spans<'_> {
+    type Item = Result<ValueWithSpan, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.end == Some(self.pos) {
+            return None;
+        }
+        match self.next_span() {
+            Ok(span) => Some(Ok(span)),
+            Err(error) => {
+                // Stop after the first error
+                self.end = Some(self.pos);
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+//EOF
@@ -0,0 +1,79 @@
+use crate::parser::{ParseError, Parser};
+use crate::resp::RespValue;
+use crate::span::Spans;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spans_nested() {
+        let frame = b"*3\r\n:1\r\n%1\r\n+k\r\n$1\r\nv\r\n*0\r\n";
+        let spans: Vec<_> = Spans::new(frame).collect::<Result<_, _>>().unwrap();
+
+        let ranges: Vec<_> = spans.iter().map(|s| (s.range.clone(), s.depth)).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0..frame.len(), 0),
+                (4..8, 1),
+                (8..23, 1),
+                (12..16, 2),
+                (16..23, 2),
+                (23..27, 1),
+            ]
+        );
+        for span in &spans {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(&frame[span.range.clone()]);
+            assert_eq!(parser.try_parse(), Ok(Some(span.value.clone())));
+        }
+        assert_eq!(spans[4].value, RespValue::BulkString(Some("v".into())));
+    }
+
+    #[test]
+    fn test_spans_scalar_frame() {
+        let mut spans = Spans::new(b"+OK\r\n:1\r\n");
+        let span = spans.next().unwrap().unwrap();
+        assert_eq!(span.value, RespValue::SimpleString("OK".into()));
+        assert_eq!(span.range, 0..5);
+        assert!(spans.next().is_none());
+    }
+
+    #[test]
+    fn test_spans_incomplete_frame() {
+        let mut spans = Spans::new(b"*2\r\n:1\r\n");
+        assert_eq!(spans.next(), Some(Err(ParseError::UnexpectedEof)));
+        assert!(spans.next().is_none());
+    }
+
+    #[test]
+    fn test_spans_streamed_aggregates() {
+        let frame = b"*?\r\n:1\r\n%?\r\n+k\r\n:2\r\n.\r\n*?\r\n.\r\n.\r\n";
+        let spans: Vec<_> = Spans::new(frame).collect::<Result<_, _>>().unwrap();
+
+        let ranges: Vec<_> = spans.iter().map(|s| (s.range.clone(), s.depth)).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0..frame.len(), 0),
+                (4..8, 1),
+                (8..23, 1),
+                (12..16, 2),
+                (16..20, 2),
+                (23..30, 1),
+            ]
+        );
+        for span in &spans {
+            let mut parser = Parser::new(10, 1024);
+            parser.read_buf(&frame[span.range.clone()]);
+            assert_eq!(parser.try_parse(), Ok(Some(span.value.clone())));
+        }
+
+        let spans: Vec<_> = Spans::new(b"*?\r\n.\r\n")
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].value, RespValue::empty_array());
+    }
+}
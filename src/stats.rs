@@ -0,0 +1,207 @@
+use crate::parser::ParseError;
+
+/// Number of frame size buckets: powers of two from 1 byte to 1 GiB, plus overflow.
+pub const FRAME_SIZE_BUCKETS: usize = 32;
+
+/// Counters collected by a `Parser` over its lifetime.
+///
+/// Obtained through `Parser::stats`, which returns a snapshot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParserStats {
+    /// Number of frames returned by `try_parse` or `split_frame`.
+    pub frames_parsed: u64,
+    /// Number of bytes making up those frames.
+    pub bytes_consumed: u64,
+    /// Number of errors caused by malformed input, including recovered ones.
+    pub parse_errors: u64,
+    /// The same errors, broken down by kind.
+    pub parse_errors_by_kind: ParseErrorCounts,
+    /// Number of bytes discarded by error recovery.
+    pub skipped_bytes: u64,
+    /// Number of bytes currently buffered but not yet consumed.
+    pub buffered_bytes: u64,
+    /// The most bytes buffered at once.
+    pub peak_buffered_bytes: u64,
+    /// The deepest nesting of aggregates seen, 1 for a flat aggregate.
+    pub max_depth_seen: usize,
+    /// Distribution of frame sizes in bytes.
+    pub frame_sizes: FrameSizeHistogram,
+}
+
+/// Counts of malformed-input errors by `ParseError` variant.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseErrorCounts {
+    pub invalid_format: u64,
+    pub invalid_length: u64,
+    pub overflow: u64,
+    pub invalid_depth: u64,
+    pub invalid_utf8: u64,
+    pub frame_integrity: u64,
+    pub memory_budget: u64,
+}
+
+impl ParseErrorCounts {
+    /// Counts `error`; errors that do not stem from malformed input are ignored.
+    pub fn record(&mut self, error: &ParseError) {
+        let count = match error {
+            ParseError::InvalidFormat(_) => &mut self.invalid_format,
+            ParseError::InvalidLength => &mut self.invalid_length,
+            ParseError::Overflow => &mut self.overflow,
+            ParseError::InvalidDepth => &mut self.invalid_depth,
+            ParseError::InvalidUtf8 => &mut self.invalid_utf8,
+            ParseError::FrameIntegrityError { .. } => &mut self.frame_integrity,
+            ParseError::MemoryBudgetExceeded => &mut self.memory_budget,
+            ParseError::UnexpectedEof
+            | ParseError::NotEnoughData
+            | ParseError::IterationBudgetExceeded => return,
+        };
+        *count += 1;
+    }
+
+    /// Returns the counts as `(kind, count)` pairs, with stable snake_case kinds.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, u64)> {
+        [
+            ("invalid_format", self.invalid_format),
+            ("invalid_length", self.invalid_length),
+            ("overflow", self.overflow),
+            ("invalid_depth", self.invalid_depth),
+            ("invalid_utf8", self.invalid_utf8),
+            ("frame_integrity", self.frame_integrity),
+            ("memory_budget", self.memory_budget),
+        ]
+        .into_iter()
+    }
+}
+
+/// A histogram of frame sizes with power-of-two buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameSizeHistogram {
+    buckets: [u64; FRAME_SIZE_BUCKETS],
+    sum: u64,
+    count: u64,
+}
+
+impl Default for FrameSizeHistogram {
+    fn default() -> Self {
+        FrameSizeHistogram {
+            buckets: [0; FRAME_SIZE_BUCKETS],
+            sum: 0,
+            count: 0,
+        }
+    }
+}
+
+impl FrameSizeHistogram {
+    /// Records one frame of `size` bytes.
+    #[inline(always)]
+    pub fn observe(&mut self, size: usize) {
+        let index = if size <= 1 {
+            0
+        } else {
+            // Smallest power of two that is >= size
+            (usize::BITS - (size - 1).leading_zeros()) as usize
+        };
+        self.buckets[index.min(FRAME_SIZE_BUCKETS - 1)] += 1;
+        self.sum = self.sum.saturating_add(size as u64);
+        self.count += 1;
+    }
+
+    /// Returns cumulative bucket counts as `(upper_bound, count)` pairs.
+    ///
+    /// The last bucket has no upper bound and always equals `count()`, matching the
+    /// Prometheus `le="+Inf"` convention.
+    pub fn buckets(&self) -> impl Iterator<Item = (Option<u64>, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .scan(0, |cumulative, (index, count)| {
+                *cumulative += count;
+                let bound = (index < FRAME_SIZE_BUCKETS - 1).then(|| 1u64 << index);
+                Some((bound, *cumulative))
+            })
+    }
+
+    /// Returns the total size of all observed frames.
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// Returns the number of observed frames.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Publishes `ParserStats` through the `metrics` facade.
+///
+/// Counters and gauges are set to the absolute values of the snapshot, so exporting
+/// the same parser repeatedly is idempotent. Metric names are stable:
+///
+/// - `stream_resp_frames_parsed_total`
+/// - `stream_resp_bytes_consumed_total`
+/// - `stream_resp_parse_errors_total`
+/// - `stream_resp_parse_errors_by_kind_total{kind}`, with the kinds of
+///   `ParseErrorCounts::iter`
+/// - `stream_resp_skipped_bytes_total`
+/// - `stream_resp_buffered_bytes` and `stream_resp_peak_buffered_bytes` (gauges)
+/// - `stream_resp_max_depth_seen` (gauge)
+///
+/// Frame sizes are not part of the snapshot export: a histogram has to see every
+/// observation, so they go to `stream_resp_frame_size_bytes` as frames are parsed,
+/// once the parser is handed `frame_size_histogram` through
+/// `Parser::record_frame_sizes`.
+#[cfg(feature = "metrics")]
+#[derive(Debug, Clone, Default)]
+pub struct MetricsExporter {
+    labels: Vec<::metrics::Label>,
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsExporter {
+    /// Creates an exporter attaching `labels` (e.g. a connection id) to every metric.
+    pub fn new<K, V>(labels: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<::metrics::SharedString>,
+        V: Into<::metrics::SharedString>,
+    {
+        MetricsExporter {
+            labels: labels
+                .into_iter()
+                .map(|(key, value)| ::metrics::Label::new(key, value))
+                .collect(),
+        }
+    }
+
+    /// Publishes a stats snapshot to the installed `metrics` recorder.
+    pub fn export(&self, stats: &ParserStats) {
+        let labels = &self.labels;
+        ::metrics::counter!("stream_resp_frames_parsed_total", labels.iter())
+            .absolute(stats.frames_parsed);
+        ::metrics::counter!("stream_resp_bytes_consumed_total", labels.iter())
+            .absolute(stats.bytes_consumed);
+        ::metrics::counter!("stream_resp_parse_errors_total", labels.iter())
+            .absolute(stats.parse_errors);
+        ::metrics::counter!("stream_resp_skipped_bytes_total", labels.iter())
+            .absolute(stats.skipped_bytes);
+        for (kind, count) in stats.parse_errors_by_kind.iter() {
+            let mut kind_labels = labels.clone();
+            kind_labels.push(::metrics::Label::new("kind", kind));
+            ::metrics::counter!("stream_resp_parse_errors_by_kind_total", kind_labels)
+                .absolute(count);
+        }
+        ::metrics::gauge!("stream_resp_buffered_bytes", labels.iter())
+            .set(stats.buffered_bytes as f64);
+        ::metrics::gauge!("stream_resp_peak_buffered_bytes", labels.iter())
+            .set(stats.peak_buffered_bytes as f64);
+        ::metrics::gauge!("stream_resp_max_depth_seen", labels.iter())
+            .set(stats.max_depth_seen as f64);
+    }
+
+    /// Returns the `stream_resp_frame_size_bytes` histogram, with the exporter's
+    /// labels, from the installed `metrics` recorder.
+    pub fn frame_size_histogram(&self) -> ::metrics::Histogram {
+        ::metrics::histogram!("stream_resp_frame_size_bytes", self.labels.iter())
+    }
+}
+
+//EOF
@@ -0,0 +1,136 @@
+use crate::parser::{Parser, ParserConfig};
+use crate::stats::FrameSizeHistogram;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_size_histogram() {
+        let mut histogram = FrameSizeHistogram::default();
+        histogram.observe(1);
+        histogram.observe(5);
+        histogram.observe(8);
+        histogram.observe(9);
+
+        let buckets: Vec<_> = histogram.buckets().collect();
+        assert_eq!(buckets.len(), crate::stats::FRAME_SIZE_BUCKETS);
+        assert_eq!(buckets[0], (Some(1), 1));
+        assert_eq!(buckets[3], (Some(8), 3));
+        assert_eq!(buckets[4], (Some(16), 4));
+        assert_eq!(buckets.last(), Some(&(None, 4)));
+        assert_eq!(histogram.sum(), 23);
+        assert_eq!(histogram.count(), 4);
+
+        histogram.observe(usize::MAX);
+        assert_eq!(histogram.buckets().nth(30), Some((Some(1 << 30), 4)));
+        assert_eq!(histogram.buckets().last(), Some((None, 5)));
+    }
+
+    #[test]
+    fn test_parser_stats() {
+        let mut parser = Parser::new(10, 1024);
+        parser.read_buf(b"+OK\r\n*2\r\n:1\r\n");
+        parser.try_parse().unwrap();
+        assert!(parser.try_parse().is_err());
+
+        // Bytes dropped mid-aggregate still count towards the frame
+        parser.read_buf(b":2\r\n#x\r\n");
+        parser.try_parse().unwrap();
+        assert!(parser.try_parse().is_err());
+
+        let stats = parser.stats();
+        assert_eq!(stats.frames_parsed, 2);
+        assert_eq!(stats.bytes_consumed, 17);
+        assert_eq!(stats.parse_errors, 1);
+        assert_eq!(stats.skipped_bytes, 0);
+        assert_eq!(stats.buffered_bytes, 4);
+        assert_eq!(stats.frame_sizes.count(), 2);
+        assert_eq!(stats.frame_sizes.sum(), 17);
+        assert_eq!(stats.parse_errors_by_kind.invalid_format, 1);
+        assert_eq!(stats.peak_buffered_bytes, 13);
+        assert_eq!(stats.max_depth_seen, 1);
+    }
+
+    #[test]
+    fn test_parse_errors_by_kind_and_depth() {
+        let mut parser = Parser::with_config(ParserConfig {
+            max_depth: 2,
+            recover_errors: true,
+            ..ParserConfig::default()
+        });
+        parser.read_buf(b"*1\r\n*1\r\n*1\r\n:1\r\n:99999999999999999999\r\n*1\r\n*1\r\n:1\r\n");
+        assert!(parser.try_parse().unwrap().is_some());
+
+        let stats = parser.stats();
+        assert_eq!(stats.parse_errors, 2);
+        assert_eq!(stats.parse_errors_by_kind.invalid_depth, 1);
+        assert_eq!(
+            stats
+                .parse_errors_by_kind
+                .iter()
+                .map(|(_, n)| n)
+                .sum::<u64>(),
+            stats.parse_errors
+        );
+        assert_eq!(stats.max_depth_seen, 2);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics_exporter() {
+        use crate::stats::MetricsExporter;
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        let exporter = MetricsExporter::new([("conn", "1")]);
+        let mut parser = Parser::new(10, 1024);
+        parser.record_frame_sizes(metrics::with_local_recorder(&recorder, || {
+            exporter.frame_size_histogram()
+        }));
+        parser.read_buf(b"+OK\r\n:1\r\n");
+        parser.try_parse().unwrap();
+        parser.split_frame().unwrap();
+        metrics::with_local_recorder(&recorder, || exporter.export(&parser.stats()));
+
+        let snapshot = snapshotter.snapshot().into_vec();
+        let find = |name: &str| {
+            snapshot
+                .iter()
+                .find(|(key, _, _, _)| {
+                    key.key().name() == name
+                        && key
+                            .key()
+                            .labels()
+                            .any(|l| l.key() == "conn" && l.value() == "1")
+                })
+                .map(|(_, _, _, value)| value)
+        };
+
+        assert_eq!(
+            find("stream_resp_frames_parsed_total"),
+            Some(&DebugValue::Counter(2))
+        );
+        assert_eq!(
+            find("stream_resp_bytes_consumed_total"),
+            Some(&DebugValue::Counter(9))
+        );
+        assert_eq!(
+            find("stream_resp_frame_size_bytes"),
+            Some(&DebugValue::Histogram(vec![5.0.into(), 4.0.into()]))
+        );
+        assert_eq!(
+            find("stream_resp_peak_buffered_bytes"),
+            Some(&DebugValue::Gauge(9.0.into()))
+        );
+        assert!(snapshot.iter().any(|(key, _, _, _)| {
+            key.key().name() == "stream_resp_parse_errors_by_kind_total"
+                && key
+                    .key()
+                    .labels()
+                    .any(|l| l.key() == "kind" && l.value() == "invalid_utf8")
+        }));
+    }
+}
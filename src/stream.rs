@@ -0,0 +1,251 @@
+use crate::parser::{ParseError, Parser, ParserConfig};
+use crate::resp::RespValue;
+use bytes::{Buf, BytesMut};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+// Frames yielded back to back before `RespStream` yields to the executor
+const DEFAULT_FRAME_BUDGET: usize = 128;
+
+// Buffered bytes above which `RespSink::poll_ready` writes them out first
+const SINK_BACKPRESSURE: usize = 8 * 1024;
+
+/// Errors yielded by `RespStream`.
+#[derive(Debug)]
+pub enum StreamError {
+    Io(std::io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(error) => write!(f, "I/O error: {}", error),
+            StreamError::Parse(error) => write!(f, "Parse error: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Io(error) => Some(error),
+            StreamError::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for StreamError {
+    fn from(error: std::io::Error) -> Self {
+        StreamError::Io(error)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(error: ParseError) -> Self {
+        StreamError::Parse(error)
+    }
+}
+
+/// A `Stream` of the frames decoded from an `AsyncRead`.
+///
+/// The stream ends when the reader does, or after yielding an error. A frame cut short
+/// by the end of input is yielded as `ParseError::UnexpectedEof`. With
+/// `ParserConfig::recover_errors` malformed frames are skipped instead.
+///
+/// A burst of pipelined frames is decoded from a single read, so the stream would
+/// otherwise never return `Pending` while working through it. After yielding a
+/// frame budget's worth of frames (128 by default, see `with_frame_budget`) it wakes
+/// itself and returns `Pending` once, letting other tasks run. It does the same when
+/// `ParserConfig::max_iterations` runs out in the middle of a large frame.
+///
+/// # Example
+///
+/// ```ignore
+/// use tokio_stream::StreamExt;
+///
+/// let mut frames = RespStream::new(socket);
+/// while let Some(frame) = frames.next().await {
+///     // handle frame?
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RespStream<R> {
+    reader: R,
+    parser: Parser,
+    eof: bool,
+    done: bool,
+    frame_budget: usize,
+    // Frames yielded since the stream last returned `Pending`
+    frames: usize,
+}
+
+impl<R: AsyncRead + Unpin> RespStream<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_config(reader, ParserConfig::default())
+    }
+
+    pub fn with_config(reader: R, config: ParserConfig) -> Self {
+        RespStream {
+            reader,
+            parser: Parser::with_config(config),
+            eof: false,
+            done: false,
+            frame_budget: DEFAULT_FRAME_BUDGET,
+            frames: 0,
+        }
+    }
+
+    /// Sets how many frames are yielded back to back before yielding to the
+    /// executor. `usize::MAX` disables yielding.
+    pub fn with_frame_budget(mut self, frame_budget: usize) -> Self {
+        self.frame_budget = frame_budget.max(1);
+        self
+    }
+
+    /// Returns the underlying parser, e.g. to read its statistics.
+    pub fn parser(&self) -> &Parser {
+        &self.parser
+    }
+
+    /// Returns the reader and the parser, with any input not decoded yet.
+    pub fn into_parts(self) -> (R, Parser) {
+        (self.reader, self.parser)
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for RespStream<R> {
+    type Item = Result<RespValue<'static>, StreamError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        if this.frames >= this.frame_budget {
+            this.frames = 0;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        loop {
+            match this.parser.try_parse() {
+                Ok(Some(value)) => {
+                    this.frames += 1;
+                    return Poll::Ready(Some(Ok(value)));
+                }
+                Ok(None) | Err(ParseError::UnexpectedEof | ParseError::NotEnoughData) => {}
+                Err(ParseError::IterationBudgetExceeded) => {
+                    // Resumed on the next poll
+                    this.frames = 0;
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                Err(error) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(error.into())));
+                }
+            }
+
+            if this.eof {
+                this.done = true;
+                if this.parser.buffer().is_empty() {
+                    return Poll::Ready(None);
+                }
+                return Poll::Ready(Some(Err(ParseError::UnexpectedEof.into())));
+            }
+
+            let polled = this.parser.poll_read_from(cx, Pin::new(&mut this.reader));
+            if polled.is_pending() {
+                this.frames = 0;
+            }
+            match ready!(polled) {
+                Ok(read) => this.eof = read == 0,
+                Err(error) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(error.into())));
+                }
+            }
+        }
+    }
+}
+
+/// A `Sink` encoding `RespValue`s onto an `AsyncWrite`.
+///
+/// Frames are buffered and written out once more than 8 KiB are pending, or on
+/// `poll_flush` and `poll_close`. Closing the sink shuts the writer down.
+///
+/// # Example
+///
+/// ```ignore
+/// use futures::SinkExt;
+///
+/// let mut sink = RespSink::new(socket);
+/// sink.feed(RespValue::SimpleString("OK".into())).await?;
+/// sink.send(RespValue::Integer(1)).await?; // flushes both
+/// ```
+#[derive(Debug)]
+pub struct RespSink<W> {
+    writer: W,
+    buffer: BytesMut,
+}
+
+impl<W: AsyncWrite + Unpin> RespSink<W> {
+    pub fn new(writer: W) -> Self {
+        RespSink {
+            writer,
+            buffer: BytesMut::new(),
+        }
+    }
+
+    /// Returns the writer, dropping any frames not flushed yet.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Writes the buffer out, without flushing the writer.
+    fn poll_write_buffer(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while !self.buffer.is_empty() {
+            let written = ready!(Pin::new(&mut self.writer).poll_write(cx, &self.buffer))?;
+            if written == 0 {
+                return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+            }
+            self.buffer.advance(written);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: AsyncWrite + Unpin> Sink<RespValue<'_>> for RespSink<W> {
+    type Error = std::io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if this.buffer.len() >= SINK_BACKPRESSURE {
+            ready!(this.poll_write_buffer(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: RespValue<'_>) -> Result<(), Self::Error> {
+        item.encode_into(&mut self.get_mut().buffer);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_buffer(cx))?;
+        Pin::new(&mut this.writer).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_buffer(cx))?;
+        Pin::new(&mut this.writer).poll_shutdown(cx)
+    }
+}
+
+//EOF
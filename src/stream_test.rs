@@ -0,0 +1,149 @@
+use crate::parser::{ParseError, ParserConfig};
+use crate::resp::RespValue;
+use crate::stream::{RespSink, RespStream, StreamError};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::pin::Pin;
+use tokio::io::AsyncWriteExt;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+        std::future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    #[tokio::test]
+    async fn test_stream_frames_across_reads() {
+        let (mut client, server) = tokio::io::duplex(8);
+        let writer = tokio::spawn(async move {
+            for _ in 0..50 {
+                client
+                    .write_all(b"*2\r\n:1\r\n$5\r\nhello\r\n")
+                    .await
+                    .unwrap();
+            }
+        });
+
+        let mut stream = RespStream::new(server);
+        let mut frames = 0;
+        while let Some(frame) = next(&mut stream).await {
+            assert_eq!(
+                frame.unwrap(),
+                RespValue::Array(Some(Box::from(vec![
+                    RespValue::Integer(1),
+                    RespValue::BulkString(Some("hello".into())),
+                ])))
+            );
+            frames += 1;
+        }
+        writer.await.unwrap();
+        assert_eq!(frames, 50);
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_after_error() {
+        let mut stream = RespStream::new(&b"+OK\r\n?\r\n+OK\r\n"[..]);
+        assert!(matches!(next(&mut stream).await, Some(Ok(_))));
+        assert!(matches!(
+            next(&mut stream).await,
+            Some(Err(StreamError::Parse(ParseError::InvalidFormat(_))))
+        ));
+        assert!(next(&mut stream).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_recovers_errors() {
+        let config = ParserConfig {
+            recover_errors: true,
+            ..ParserConfig::default()
+        };
+        let mut stream = RespStream::with_config(&b"?\r\n+OK\r\n+trunc"[..], config);
+        assert!(matches!(
+            next(&mut stream).await,
+            Some(Ok(RespValue::SimpleString(_)))
+        ));
+        assert!(matches!(
+            next(&mut stream).await,
+            Some(Err(StreamError::Parse(ParseError::UnexpectedEof)))
+        ));
+        assert!(next(&mut stream).await.is_none());
+    }
+
+    async fn send<S: Sink<RespValue<'static>> + Unpin>(
+        sink: &mut S,
+        item: RespValue<'static>,
+    ) -> Result<(), S::Error> {
+        std::future::poll_fn(|cx| Pin::new(&mut *sink).poll_ready(cx)).await?;
+        Pin::new(&mut *sink).start_send(item)
+    }
+
+    #[tokio::test]
+    async fn test_sink_round_trip() {
+        let (client, server) = tokio::io::duplex(64);
+        let writer = tokio::spawn(async move {
+            let mut sink = RespSink::new(client);
+            for i in 0..1000 {
+                send(&mut sink, RespValue::Integer(i)).await.unwrap();
+            }
+            send(&mut sink, RespValue::SimpleString("OK".into()))
+                .await
+                .unwrap();
+            std::future::poll_fn(|cx| Pin::new(&mut sink).poll_close(cx))
+                .await
+                .unwrap();
+        });
+
+        let mut stream = RespStream::new(server);
+        for i in 0..1000 {
+            assert_eq!(
+                next(&mut stream).await.unwrap().unwrap(),
+                RespValue::Integer(i)
+            );
+        }
+        assert_eq!(
+            next(&mut stream).await.unwrap().unwrap(),
+            RespValue::SimpleString("OK".into())
+        );
+        assert!(next(&mut stream).await.is_none());
+        writer.await.unwrap();
+    }
+
+    #[test]
+    fn test_stream_yields_after_frame_budget() {
+        let input = b"+OK\r\n".repeat(5);
+        let mut stream = RespStream::new(&input[..]).with_frame_budget(2);
+        let waker = std::task::Waker::noop();
+        let mut cx = std::task::Context::from_waker(waker);
+        let mut polls = Vec::new();
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                std::task::Poll::Ready(Some(frame)) => polls.push(frame.is_ok()),
+                std::task::Poll::Ready(None) => break,
+                std::task::Poll::Pending => polls.push(false),
+            }
+        }
+        assert_eq!(polls, [true, true, false, true, true, false, true]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_large_bulk_across_reads() {
+        const LEN: usize = 4 * 1024 * 1024;
+        let (mut client, server) = tokio::io::duplex(16 * 1024);
+        let writer = tokio::spawn(async move {
+            client
+                .write_all(format!("${}\r\n", LEN).as_bytes())
+                .await
+                .unwrap();
+            client.write_all(&vec![b'x'; LEN]).await.unwrap();
+            client.write_all(b"\r\n").await.unwrap();
+        });
+
+        let mut stream = RespStream::new(server);
+        let frame = next(&mut stream).await.unwrap().unwrap();
+        assert!(matches!(frame, RespValue::BulkString(Some(s)) if s.len() == LEN));
+        writer.await.unwrap();
+        assert!(next(&mut stream).await.is_none());
+    }
+}
@@ -0,0 +1,235 @@
+use crate::resp::{Attributed, RespKind, RespValue, Verbatim};
+
+/// What a `RespVisitor` hook asks the walk to do next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Visit {
+    /// Keep walking, descending into the value's children.
+    #[default]
+    Continue,
+    /// Don't descend into the value's children; carry on with its next sibling.
+    Skip,
+    /// End the walk.
+    Stop,
+}
+
+/// Hooks called by `RespValue::visit` as it walks a value depth-first.
+///
+/// `visit_value` is called first for every value, then the hook for its variant.
+/// Aggregates visit their elements in order, maps each key before its value, and
+/// attributes their entries before the attributed value. Every hook defaults to
+/// `Visit::Continue`, so a visitor only overrides what it needs.
+///
+/// # Example
+///
+/// ```
+/// use stream_resp::resp::RespValue;
+/// use stream_resp::visit::{RespVisitor, Visit};
+///
+/// #[derive(Default)]
+/// struct Sum(i64);
+///
+/// impl RespVisitor for Sum {
+///     fn visit_integer(&mut self, value: i64) -> Visit {
+///         self.0 += value;
+///         Visit::Continue
+///     }
+///
+///     // Leave maps out of the total.
+///     fn visit_map(&mut self, _entries: &[(RespValue<'_>, RespValue<'_>)]) -> Visit {
+///         Visit::Skip
+///     }
+/// }
+///
+/// let reply = RespValue::Array(Some(Box::from(vec![
+///     RespValue::Integer(1),
+///     RespValue::Array(Some(Box::from(vec![RespValue::Integer(2)]))),
+///     RespValue::Map(Some(Box::from(vec![(RespValue::Integer(3), RespValue::Integer(4))]))),
+/// ])));
+/// let mut sum = Sum::default();
+/// reply.visit(&mut sum);
+/// assert_eq!(sum.0, 3);
+/// ```
+pub trait RespVisitor {
+    /// Called for every value before the hook for its variant. `Skip` prunes the
+    /// value: neither its variant hook nor its children are visited.
+    fn visit_value(&mut self, _value: &RespValue<'_>) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_simple_string(&mut self, _value: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_error(&mut self, _message: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_bulk_string(&mut self, _value: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_bulk_bytes(&mut self, _value: &[u8]) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_bulk_error(&mut self, _message: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_verbatim(&mut self, _value: &Verbatim<'_>) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_big_number(&mut self, _digits: &str) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_integer(&mut self, _value: i64) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_double(&mut self, _value: f64) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_boolean(&mut self, _value: bool) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_bulk_streamed(&mut self, _len: usize) -> Visit {
+        Visit::Continue
+    }
+
+    /// Called for `Null` and for the null forms of strings and aggregates, with the
+    /// kind of the null value.
+    fn visit_null(&mut self, _kind: RespKind) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_array(&mut self, _elements: &[RespValue<'_>]) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_set(&mut self, _elements: &[RespValue<'_>]) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_push(&mut self, _elements: &[RespValue<'_>]) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_map(&mut self, _entries: &[(RespValue<'_>, RespValue<'_>)]) -> Visit {
+        Visit::Continue
+    }
+
+    fn visit_attribute(&mut self, _attributed: &Attributed<'_>) -> Visit {
+        Visit::Continue
+    }
+
+    /// Called once the children of an aggregate or attribute have been visited.
+    /// Not called when the aggregate was skipped or the walk stopped inside it.
+    fn leave(&mut self, _kind: RespKind) {}
+}
+
+impl RespValue<'_> {
+    /// Walks this value and its children depth-first, calling `visitor`'s hooks.
+    pub fn visit(&self, visitor: &mut impl RespVisitor) {
+        walk(self, visitor);
+    }
+}
+
+/// Returns `false` once the walk has been stopped.
+fn walk(value: &RespValue<'_>, visitor: &mut impl RespVisitor) -> bool {
+    match visitor.visit_value(value) {
+        Visit::Continue => {}
+        Visit::Skip => return true,
+        Visit::Stop => return false,
+    }
+    let visit = match value {
+        RespValue::SimpleString(s) => visitor.visit_simple_string(s),
+        RespValue::Error(s) => visitor.visit_error(s),
+        RespValue::BulkString(Some(s)) => visitor.visit_bulk_string(s),
+        RespValue::BulkBytes(b) => visitor.visit_bulk_bytes(b),
+        RespValue::BulkError(Some(s)) => visitor.visit_bulk_error(s),
+        RespValue::VerbatimString(Some(v)) => visitor.visit_verbatim(v),
+        RespValue::BigNumber(s) => visitor.visit_big_number(s),
+        RespValue::Integer(i) => visitor.visit_integer(*i),
+        RespValue::Double(d) => visitor.visit_double(*d),
+        RespValue::Boolean(b) => visitor.visit_boolean(*b),
+        RespValue::BulkStreamed { len } => visitor.visit_bulk_streamed(*len),
+        RespValue::Array(Some(values)) => {
+            return walk_children(
+                visitor.visit_array(values),
+                values,
+                RespKind::Array,
+                visitor,
+            );
+        }
+        RespValue::Set(Some(values)) => {
+            return walk_children(visitor.visit_set(values), values, RespKind::Set, visitor);
+        }
+        RespValue::Push(Some(values)) => {
+            return walk_children(visitor.visit_push(values), values, RespKind::Push, visitor);
+        }
+        RespValue::Map(Some(entries)) => {
+            let visit = visitor.visit_map(entries);
+            return walk_entries(visit, entries, None, RespKind::Map, visitor);
+        }
+        RespValue::Attribute(attributed) => {
+            let visit = visitor.visit_attribute(attributed);
+            let Attributed { attributes, value } = &**attributed;
+            return walk_entries(visit, attributes, Some(value), RespKind::Attribute, visitor);
+        }
+        RespValue::Null
+        | RespValue::BulkString(None)
+        | RespValue::BulkError(None)
+        | RespValue::VerbatimString(None)
+        | RespValue::Array(None)
+        | RespValue::Set(None)
+        | RespValue::Push(None)
+        | RespValue::Map(None) => visitor.visit_null(value.kind()),
+    };
+    visit != Visit::Stop
+}
+
+fn walk_children(
+    visit: Visit,
+    values: &[RespValue<'_>],
+    kind: RespKind,
+    visitor: &mut impl RespVisitor,
+) -> bool {
+    match visit {
+        Visit::Continue => {}
+        Visit::Skip => return true,
+        Visit::Stop => return false,
+    }
+    if !values.iter().all(|value| walk(value, visitor)) {
+        return false;
+    }
+    visitor.leave(kind);
+    true
+}
+
+fn walk_entries(
+    visit: Visit,
+    entries: &[(RespValue<'_>, RespValue<'_>)],
+    value: Option<&RespValue<'_>>,
+    kind: RespKind,
+    visitor: &mut impl RespVisitor,
+) -> bool {
+    match visit {
+        Visit::Continue => {}
+        Visit::Skip => return true,
+        Visit::Stop => return false,
+    }
+    let entries_walked = entries
+        .iter()
+        .all(|(key, value)| walk(key, visitor) && walk(value, visitor));
+    if !entries_walked || !value.is_none_or(|value| walk(value, visitor)) {
+        return false;
+    }
+    visitor.leave(kind);
+    true
+}
+
+//EOF
@@ -0,0 +1,144 @@
+use crate::parser::Parser;
+use crate::resp::{RespKind, RespValue};
+use crate::visit::{RespVisitor, Visit};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records every hook call, pruning maps and stopping at the error `STOP`.
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        skip_maps: bool,
+    }
+
+    impl RespVisitor for Recorder {
+        fn visit_simple_string(&mut self, value: &str) -> Visit {
+            self.events.push(format!("simple {}", value));
+            Visit::Continue
+        }
+
+        fn visit_error(&mut self, message: &str) -> Visit {
+            self.events.push(format!("error {}", message));
+            if message == "STOP" {
+                Visit::Stop
+            } else {
+                Visit::Continue
+            }
+        }
+
+        fn visit_bulk_string(&mut self, value: &str) -> Visit {
+            self.events.push(format!("bulk {}", value));
+            Visit::Continue
+        }
+
+        fn visit_integer(&mut self, value: i64) -> Visit {
+            self.events.push(format!("int {}", value));
+            Visit::Continue
+        }
+
+        fn visit_null(&mut self, kind: RespKind) -> Visit {
+            self.events.push(format!("null {:?}", kind));
+            Visit::Continue
+        }
+
+        fn visit_array(&mut self, elements: &[RespValue<'_>]) -> Visit {
+            self.events.push(format!("array {}", elements.len()));
+            Visit::Continue
+        }
+
+        fn visit_map(&mut self, entries: &[(RespValue<'_>, RespValue<'_>)]) -> Visit {
+            self.events.push(format!("map {}", entries.len()));
+            if self.skip_maps {
+                Visit::Skip
+            } else {
+                Visit::Continue
+            }
+        }
+
+        fn visit_attribute(&mut self, _attributed: &crate::resp::Attributed<'_>) -> Visit {
+            self.events.push("attribute".to_string());
+            Visit::Continue
+        }
+
+        fn leave(&mut self, kind: RespKind) {
+            self.events.push(format!("leave {:?}", kind));
+        }
+    }
+
+    fn parse(input: &[u8]) -> RespValue<'static> {
+        let mut parser = Parser::new(100, 1000);
+        parser.read_buf(input);
+        parser.try_parse().unwrap().unwrap().into_owned()
+    }
+
+    #[test]
+    fn test_visit_order() {
+        let value = parse(b"*4\r\n:1\r\n%1\r\n+k\r\n$1\r\nv\r\n$-1\r\n|1\r\n+ttl\r\n:3\r\n:2\r\n");
+        let mut recorder = Recorder::default();
+        value.visit(&mut recorder);
+        assert_eq!(
+            recorder.events,
+            vec![
+                "array 4",
+                "int 1",
+                "map 1",
+                "simple k",
+                "bulk v",
+                "leave Map",
+                "null BulkString",
+                "attribute",
+                "simple ttl",
+                "int 3",
+                "int 2",
+                "leave Attribute",
+                "leave Array",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_visit_skip() {
+        let value = parse(b"*3\r\n%1\r\n+k\r\n:1\r\n*1\r\n:2\r\n:3\r\n");
+        let mut recorder = Recorder {
+            skip_maps: true,
+            ..Recorder::default()
+        };
+        value.visit(&mut recorder);
+        assert_eq!(
+            recorder.events,
+            vec![
+                "array 3",
+                "map 1",
+                "array 1",
+                "int 2",
+                "leave Array",
+                "int 3",
+                "leave Array",
+            ]
+        );
+
+        struct SkipAll(usize);
+        impl RespVisitor for SkipAll {
+            fn visit_value(&mut self, _value: &RespValue<'_>) -> Visit {
+                self.0 += 1;
+                Visit::Skip
+            }
+        }
+        let mut skip_all = SkipAll(0);
+        value.visit(&mut skip_all);
+        assert_eq!(skip_all.0, 1);
+    }
+
+    #[test]
+    fn test_visit_stop() {
+        let value = parse(b"*3\r\n*2\r\n:1\r\n-STOP\r\n:2\r\n:3\r\n");
+        let mut recorder = Recorder::default();
+        value.visit(&mut recorder);
+        assert_eq!(
+            recorder.events,
+            vec!["array 3", "array 2", "int 1", "error STOP"]
+        );
+    }
+}
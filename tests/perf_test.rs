@@ -0,0 +1,253 @@
+//! Coarse performance regression gates.
+//!
+//! Enabled with `cargo test --release --features perf-test`. Every threshold can be
+//! overridden through environment variables so the gates stay usable on slow CI hosts:
+//!
+//! - `STREAM_RESP_PERF_FRAMES` - number of `real_command` frames to parse (default 1,000,000)
+//! - `STREAM_RESP_PERF_REAL_COMMAND_MS` - time budget for those frames (default 30,000 ms)
+//! - `STREAM_RESP_PERF_LARGE_BULK_READ_MS` - time budget for reading a 64 MiB bulk string
+//!   through `Parser::read_from` in 16 KiB reads (default 1,000 ms)
+//! - `STREAM_RESP_PERF_SIMPLE_STRING_ALLOCS` - allocations allowed per parsed simple string
+//!   (default 0: `+OK` is interned, so the value borrows its text and nothing is allocated)
+//! - `STREAM_RESP_PERF_NESTED_ARRAY_ALLOCS` - allocations allowed per element of a nested
+//!   array, on top of one per aggregate (default 1, the element's payload)
+//!
+//! The gates count allocations with their own global allocator, so they are skipped
+//! when the `jemalloc` feature installs the library's.
+#![cfg(all(feature = "perf-test", not(feature = "jemalloc")))]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use stream_resp::encoder::Encoder;
+use stream_resp::parser::{ParseError, Parser, ParserConfig};
+use stream_resp::resp::RespValue;
+
+const REAL_COMMAND: &[u8] = b"*3\r\n$3\r\nSET\r\n$4\r\nkey1\r\n$6\r\nvalue1\r\n";
+const BATCH: usize = 1000;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|count| count.get())
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+#[test]
+fn perf_real_command_throughput() {
+    let frames: usize = env_or("STREAM_RESP_PERF_FRAMES", 1_000_000);
+    let budget = Duration::from_millis(env_or("STREAM_RESP_PERF_REAL_COMMAND_MS", 30_000));
+
+    let batch = REAL_COMMAND.repeat(BATCH);
+    let mut parser = Parser::new(10, 1024);
+    let mut parsed = 0;
+
+    let started = Instant::now();
+    while parsed < frames {
+        parser.read_buf(&batch);
+        for _ in 0..BATCH {
+            match parser.try_parse() {
+                Ok(Some(_)) => parsed += 1,
+                other => panic!("Expected complete frame, got {:?}", other),
+            }
+        }
+    }
+    let elapsed = started.elapsed();
+
+    assert!(
+        elapsed <= budget,
+        "parsing {} real_command frames took {:?}, budget is {:?}",
+        parsed,
+        elapsed,
+        budget
+    );
+}
+
+/// Hands out its input at most `chunk` bytes per read, as a socket does.
+struct ChunkedReader<'a> {
+    input: &'a [u8],
+    chunk: usize,
+}
+
+impl std::io::Read for ChunkedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.chunk).min(self.input.len());
+        buf[..len].copy_from_slice(&self.input[..len]);
+        self.input = &self.input[len..];
+        Ok(len)
+    }
+}
+
+#[test]
+fn perf_large_bulk_read_from() {
+    const LEN: usize = 64 * 1024 * 1024;
+    let budget = Duration::from_millis(env_or("STREAM_RESP_PERF_LARGE_BULK_READ_MS", 1_000));
+
+    let mut frame = format!("${}\r\n", LEN).into_bytes();
+    frame.resize(frame.len() + LEN, b'x');
+    frame.extend_from_slice(b"\r\n");
+    let mut reader = ChunkedReader {
+        input: &frame,
+        chunk: 16 * 1024,
+    };
+    let mut parser = Parser::with_config(ParserConfig::default());
+
+    let started = Instant::now();
+    let value = loop {
+        assert!(
+            parser.read_from(&mut reader).unwrap() > 0,
+            "input ended early"
+        );
+        match parser.try_parse() {
+            Ok(Some(value)) => break value,
+            Ok(None) | Err(ParseError::UnexpectedEof | ParseError::NotEnoughData) => {}
+            Err(error) => panic!("Expected a bulk string, got {:?}", error),
+        }
+    };
+    let elapsed = started.elapsed();
+    assert!(matches!(value, RespValue::BulkString(Some(s)) if s.len() == LEN));
+
+    // Only the window read into is zeroed, not the whole spare capacity every time
+    assert!(
+        elapsed <= budget,
+        "reading a {} byte bulk string took {:?}, budget is {:?}",
+        LEN,
+        elapsed,
+        budget
+    );
+}
+
+#[test]
+fn perf_simple_string_allocations() {
+    let max_per_frame: usize = env_or("STREAM_RESP_PERF_SIMPLE_STRING_ALLOCS", 0);
+
+    let batch = b"+OK\r\n".repeat(BATCH);
+    let mut parser = Parser::new(10, 1024);
+
+    // Warm up so buffer growth is not counted
+    parser.read_buf(&batch);
+    for _ in 0..BATCH {
+        parser.try_parse().unwrap();
+    }
+    parser.read_buf(&batch);
+
+    let before = allocations();
+    for _ in 0..BATCH {
+        let value = parser.try_parse().unwrap();
+        assert_eq!(value, Some(RespValue::SimpleString("OK".into())));
+    }
+    let used = allocations() - before;
+
+    assert!(
+        used <= max_per_frame * BATCH,
+        "parsing {} simple strings allocated {} times, limit is {} per frame",
+        BATCH,
+        used,
+        max_per_frame
+    );
+}
+
+#[test]
+fn perf_nested_array_allocations() {
+    const ELEMENTS: usize = 100;
+    let max_per_element: usize = env_or("STREAM_RESP_PERF_NESTED_ARRAY_ALLOCS", 1);
+
+    let mut frame = format!("*1\r\n*{}\r\n", ELEMENTS).into_bytes();
+    for _ in 0..ELEMENTS {
+        frame.extend_from_slice(b"$1\r\nx\r\n");
+    }
+    let mut parser = Parser::new(10, 1024);
+
+    // Warm up so buffer growth is not counted
+    parser.read_buf(&frame);
+    parser.try_parse().unwrap();
+    parser.read_buf(&frame);
+
+    let before = allocations();
+    let value = parser.try_parse().unwrap();
+    let used = allocations() - before;
+    assert!(matches!(value, Some(RespValue::Array(Some(_)))));
+
+    // Completed aggregates must be moved up the stack, never cloned
+    assert!(
+        used <= max_per_element * ELEMENTS + 2,
+        "parsing a nested array of {} elements allocated {} times",
+        ELEMENTS,
+        used
+    );
+}
+
+#[test]
+fn perf_number_encoding_allocations() {
+    let values: Vec<RespValue> = (0..BATCH as i64)
+        .flat_map(|i| {
+            [
+                RespValue::Integer(i * 7919 - 500_000),
+                RespValue::Double(i as f64 / 3.0),
+                RespValue::Double(1e21 * i as f64),
+            ]
+        })
+        .collect();
+    let mut encoder = Encoder::with_capacity(64);
+
+    let before = allocations();
+    for value in &values {
+        encoder.encode(value);
+    }
+    let used = allocations() - before;
+
+    // Numbers are formatted straight into the reused buffer
+    assert_eq!(
+        used,
+        0,
+        "encoding {} numbers allocated {} times",
+        values.len(),
+        used
+    );
+}
+
+#[test]
+fn perf_cmd_allocations() {
+    let key = String::from("scores");
+
+    let before = allocations();
+    for i in 0..BATCH {
+        let bytes = stream_resp::cmd!("ZADD", key, i as f64 / 3.0, i, i as i128 * -7919);
+        assert!(!bytes.is_empty());
+    }
+    let used = allocations() - before;
+
+    // The argument list and the output buffer; numbers are formatted on the stack
+    assert_eq!(
+        used,
+        2 * BATCH,
+        "encoding {} commands allocated {} times",
+        BATCH,
+        used
+    );
+}